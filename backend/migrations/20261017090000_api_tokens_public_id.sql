@@ -0,0 +1,7 @@
+-- Tokens are now issued as sh_<public_id>_<secret>, where token_hash is an
+-- HMAC-SHA256 of the secret. Rows created before this migration hold bcrypt
+-- hashes of unknown secrets and cannot be converted; they keep a NULL
+-- public_id, are reported as legacy and no longer authenticate.
+ALTER TABLE api_tokens ADD COLUMN public_id VARCHAR(32);
+
+CREATE UNIQUE INDEX idx_api_tokens_public_id ON api_tokens(public_id);
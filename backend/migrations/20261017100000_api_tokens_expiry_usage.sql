@@ -0,0 +1,4 @@
+ALTER TABLE api_tokens
+    ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN last_used_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN last_used_ip VARCHAR(45);
@@ -0,0 +1,4 @@
+-- Tokens issued before scopes existed keep full access through houses:admin.
+ALTER TABLE api_tokens
+    ADD COLUMN scopes TEXT[] NOT NULL DEFAULT '{houses:admin}',
+    ADD COLUMN house_id BIGINT REFERENCES houses(id) ON DELETE CASCADE;
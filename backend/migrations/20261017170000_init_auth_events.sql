@@ -0,0 +1,15 @@
+-- Events outlive the account they belong to, so user_id is cleared instead of
+-- deleting the rows with it.
+CREATE TABLE auth_events (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    event_type VARCHAR(32) NOT NULL,
+    email TEXT,
+    ip VARCHAR(45),
+    user_agent TEXT,
+    details TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_auth_events_user_id_created_at ON auth_events(user_id, created_at DESC);
+CREATE INDEX idx_auth_events_created_at ON auth_events(created_at DESC);
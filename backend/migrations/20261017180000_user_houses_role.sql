@@ -0,0 +1,5 @@
+CREATE TYPE house_role AS ENUM ('owner', 'admin', 'member', 'guest');
+
+-- Every existing link was created together with its house, so it belongs to the owner
+ALTER TABLE user_houses ADD COLUMN role house_role NOT NULL DEFAULT 'owner';
+ALTER TABLE user_houses ALTER COLUMN role SET DEFAULT 'member';
@@ -0,0 +1,19 @@
+ALTER TABLE user_houses ADD COLUMN joined_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW();
+
+CREATE TABLE house_invitations (
+    id BIGSERIAL PRIMARY KEY,
+    house_id BIGINT NOT NULL REFERENCES houses(id) ON DELETE CASCADE,
+    invited_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    -- NULL for open codes anyone with the code can accept
+    email VARCHAR(255),
+    role house_role NOT NULL,
+    code_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    declined_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_house_invitations_house_id ON house_invitations(house_id);
+CREATE INDEX idx_house_invitations_email ON house_invitations(email);
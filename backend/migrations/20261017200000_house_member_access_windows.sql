@@ -0,0 +1,15 @@
+-- Time-boxed access. NULL means no limit. The schedule is a JSON array of
+-- weekly windows ({"days": [...], "start": "HH:MM:SS", "end": "HH:MM:SS"}) in
+-- house-local time the member has access in. Changing the timezone of the
+-- house moves the windows of every member.
+ALTER TABLE user_houses
+    ADD COLUMN valid_from TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN valid_until TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN schedule JSONB;
+
+ALTER TABLE house_invitations
+    ADD COLUMN valid_from TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN valid_until TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN schedule JSONB;
+
+CREATE INDEX idx_user_houses_valid_until ON user_houses(valid_until) WHERE valid_until IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Rooms a membership is limited to, NULL for the whole house. Ids of deleted
+-- rooms simply no longer match anything.
+ALTER TABLE user_houses ADD COLUMN room_ids BIGINT[];
+
+ALTER TABLE house_invitations ADD COLUMN room_ids BIGINT[];
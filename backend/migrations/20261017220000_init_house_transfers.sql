@@ -0,0 +1,18 @@
+-- Ownership transfers. Rows are kept once resolved as the audit trail of who
+-- handed a house over to whom.
+CREATE TABLE house_transfers (
+    id BIGSERIAL PRIMARY KEY,
+    house_id BIGINT NOT NULL REFERENCES houses(id) ON DELETE CASCADE,
+    from_user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    to_user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    declined_at TIMESTAMP WITH TIME ZONE,
+    cancelled_at TIMESTAMP WITH TIME ZONE,
+    -- Role the previous owner got in exchange, the one the new owner had
+    previous_owner_role house_role
+);
+
+CREATE INDEX idx_house_transfers_house_id ON house_transfers(house_id);
+CREATE INDEX idx_house_transfers_to_user_id ON house_transfers(to_user_id);
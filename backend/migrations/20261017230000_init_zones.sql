@@ -0,0 +1,29 @@
+-- Floors, wings and outdoor areas grouping the rooms of a house
+CREATE TABLE zones (
+    id BIGSERIAL PRIMARY KEY,
+    house_id BIGINT NOT NULL REFERENCES houses(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    zone_type VARCHAR(50) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_zones_house_id ON zones(house_id);
+
+CREATE OR REPLACE FUNCTION update_zones_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_update_zones_updated_at
+BEFORE UPDATE ON zones
+FOR EACH ROW
+EXECUTE FUNCTION update_zones_updated_at();
+
+-- Rooms of a deleted zone stay in the house without a zone
+ALTER TABLE rooms ADD COLUMN zone_id BIGINT REFERENCES zones(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_rooms_zone_id ON rooms(zone_id);
@@ -0,0 +1,12 @@
+-- Where a house is and how its data is presented. Days and weeks of metrics
+-- are bucketed in the house's IANA time zone.
+ALTER TABLE houses
+    ADD COLUMN timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+    ADD COLUMN latitude DOUBLE PRECISION,
+    ADD COLUMN longitude DOUBLE PRECISION,
+    ADD COLUMN unit_system VARCHAR(16) NOT NULL DEFAULT 'metric',
+    ADD COLUMN currency CHAR(3) NOT NULL DEFAULT 'USD',
+    ADD CONSTRAINT houses_latitude_check CHECK (latitude BETWEEN -90 AND 90),
+    ADD CONSTRAINT houses_longitude_check CHECK (longitude BETWEEN -180 AND 180),
+    ADD CONSTRAINT houses_location_check CHECK ((latitude IS NULL) = (longitude IS NULL)),
+    ADD CONSTRAINT houses_unit_system_check CHECK (unit_system IN ('metric', 'imperial'));
@@ -0,0 +1,20 @@
+-- Rooms a device has been in, so metrics can be attributed to the room the
+-- device was in when they were measured
+CREATE TABLE device_location_history (
+    id BIGSERIAL PRIMARY KEY,
+    device_id BIGINT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    room_id BIGINT REFERENCES rooms(id) ON DELETE SET NULL,
+    -- NULL when the device was placed on creation
+    moved_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    placed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- NULL while the device is still in the room
+    removed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_device_location_history_device_id ON device_location_history(device_id, placed_at);
+
+CREATE UNIQUE INDEX idx_device_location_history_current
+ON device_location_history(device_id) WHERE removed_at IS NULL;
+
+INSERT INTO device_location_history (device_id, room_id, placed_at)
+SELECT id, room_id, created_at FROM devices;
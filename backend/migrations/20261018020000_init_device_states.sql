@@ -0,0 +1,11 @@
+-- Last reported and wanted values of the capabilities of each device, keyed by
+-- capability, e.g. {"on_off": 1, "brightness": 40}
+CREATE TABLE device_states (
+    device_id BIGINT PRIMARY KEY REFERENCES devices(id) ON DELETE CASCADE,
+    desired JSONB NOT NULL DEFAULT '{}',
+    reported JSONB NOT NULL DEFAULT '{}',
+    -- Bumped on every change, so clients can detect concurrent writes
+    version BIGINT NOT NULL DEFAULT 0,
+    desired_updated_at TIMESTAMPTZ,
+    reported_updated_at TIMESTAMPTZ
+);
@@ -0,0 +1,44 @@
+-- Commands sent to devices through their gateways. A command is pending until
+-- a gateway fetches it, then delivered until the device acknowledges it or
+-- reports that it failed. Delivered commands that are not acknowledged within
+-- their timeout are delivered again until they run out of attempts.
+CREATE TABLE device_commands (
+    id BIGSERIAL PRIMARY KEY,
+    device_id BIGINT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    command VARCHAR(16) NOT NULL CHECK (command IN ('set', 'reboot')),
+    -- Capability and value of `set` commands
+    capability VARCHAR(50),
+    value DOUBLE PRECISION,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'delivered', 'acknowledged', 'failed', 'expired')),
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL,
+    timeout_seconds INT NOT NULL,
+    -- Commands not completed by then are dropped
+    expires_at TIMESTAMPTZ NOT NULL,
+    delivered_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    error TEXT,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((command = 'set') = (capability IS NOT NULL AND value IS NOT NULL))
+);
+
+CREATE INDEX idx_device_commands_device_id ON device_commands(device_id, id);
+
+CREATE INDEX idx_device_commands_open ON device_commands(status)
+WHERE status IN ('pending', 'delivered');
+
+CREATE OR REPLACE FUNCTION update_device_commands_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_update_device_commands_updated_at
+BEFORE UPDATE ON device_commands
+FOR EACH ROW
+EXECUTE FUNCTION update_device_commands_updated_at();
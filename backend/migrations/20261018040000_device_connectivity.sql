@@ -0,0 +1,30 @@
+-- Devices are online from their last heartbeat or metric until they have not
+-- been heard from for the offline timeout of their type
+ALTER TABLE devices
+ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'offline' CHECK (status IN ('online', 'offline')),
+ADD COLUMN last_seen_at TIMESTAMPTZ;
+
+CREATE INDEX idx_devices_online ON devices(last_seen_at) WHERE status = 'online';
+
+-- Heartbeats do not count as edits of the device
+CREATE OR REPLACE FUNCTION update_devices_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF ROW(NEW.name, NEW.device_type, NEW.room_id)
+        IS DISTINCT FROM ROW(OLD.name, OLD.device_type, OLD.room_id) THEN
+        NEW.updated_at = NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Times devices came online or went offline
+CREATE TABLE device_connectivity_events (
+    id BIGSERIAL PRIMARY KEY,
+    device_id BIGINT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL CHECK (status IN ('online', 'offline')),
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_device_connectivity_events_device_id
+ON device_connectivity_events(device_id, occurred_at);
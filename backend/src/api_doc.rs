@@ -23,41 +23,168 @@ impl Modify for SecurityAddon {
 #[openapi(
     paths(
         handlers::auth::login,
+        handlers::auth::login_two_factor,
         handlers::auth::register,
+        handlers::auth::refresh,
+        handlers::auth::logout,
+        handlers::auth::forgot_password,
+        handlers::auth::reset_password,
+        handlers::auth::verify_email,
+        handlers::auth::jwks,
         handlers::users::get_user_profile,
+        handlers::users::update_user_profile,
+        handlers::users::change_password,
+        handlers::users::delete_account,
+        handlers::users::get_sessions,
+        handlers::users::revoke_session,
+        handlers::users::get_security_events,
+        handlers::users::resend_email_verification,
+        handlers::users::enroll_totp,
+        handlers::users::confirm_totp,
+        handlers::users::disable_totp,
         handlers::api_tokens::create_api_token,
         handlers::api_tokens::get_api_tokens,
+        handlers::api_tokens::delete_api_token,
         handlers::houses::get_user_houses,
         handlers::houses::get_user_house_by_id,
         handlers::houses::create_house,
+        handlers::houses::update_house,
         handlers::houses::delete_house,
+        handlers::house_members::list_members,
+        handlers::house_members::remove_member,
+        handlers::house_members::update_member_access,
+        handlers::house_members::leave_house,
+        handlers::house_members::create_invitation,
+        handlers::house_members::list_house_invitations,
+        handlers::house_members::revoke_invitation,
+        handlers::house_members::list_received_invitations,
+        handlers::house_members::accept_invitation_code,
+        handlers::house_members::accept_invitation,
+        handlers::house_members::decline_invitation,
+        handlers::house_transfers::create_transfer,
+        handlers::house_transfers::list_transfers,
+        handlers::house_transfers::cancel_transfer,
+        handlers::house_transfers::list_received_transfers,
+        handlers::house_transfers::accept_transfer,
+        handlers::house_transfers::decline_transfer,
         handlers::rooms::get_house_rooms,
         handlers::rooms::create_room,
+        handlers::rooms::update_room,
         handlers::rooms::delete_room,
+        handlers::zones::get_house_zones,
+        handlers::zones::create_zone,
+        handlers::zones::update_zone,
+        handlers::zones::delete_zone,
         handlers::devices::get_devices_by_house_id,
         handlers::devices::get_devices_by_room_id,
+        handlers::devices::get_devices_by_zone_id,
         handlers::devices::get_device_by_id,
         handlers::devices::create_device,
         handlers::devices::update_device,
         handlers::devices::delete_device,
+        handlers::devices::move_device,
+        handlers::devices::get_device_location_history,
+        handlers::devices::record_device_heartbeat,
+        handlers::devices::get_device_connectivity_events,
+        handlers::devices::list_device_types,
+        handlers::device_state::get_device_state,
+        handlers::device_state::update_device_state,
+        handlers::device_state::get_device_state_delta,
+        handlers::device_commands::create_device_command,
+        handlers::device_commands::get_device_commands,
+        handlers::device_commands::fetch_device_commands,
+        handlers::device_commands::fetch_house_commands,
+        handlers::device_commands::acknowledge_device_command,
+        handlers::device_commands::fail_device_command,
+        handlers::device_commands::expire_device_command,
+        handlers::admin::list_users,
+        handlers::admin::update_user_role,
+        handlers::admin::disable_user,
+        handlers::admin::enable_user,
+        handlers::admin::force_logout,
+        handlers::admin::get_system_stats,
+        handlers::admin::list_auth_events,
         crate::health_check
     ),
     components(
         schemas(
             models::auth::LoginRequest,
             models::auth::AuthResponse,
+            models::auth::LoginResponse,
+            models::two_factor::TwoFactorChallenge,
+            models::two_factor::TwoFactorLoginRequest,
+            models::two_factor::TotpEnrollment,
+            models::two_factor::TotpCodeRequest,
+            models::two_factor::RecoveryCodes,
             models::users::User,
+            models::users::UserRole,
+            models::users::UpdateProfileRequest,
+            models::users::ChangePasswordRequest,
+            models::users::DeleteAccountRequest,
             models::auth::RegisterUser,
+            models::auth::ForgotPasswordRequest,
+            models::auth::ResetPasswordRequest,
+            models::auth::VerifyEmailRequest,
+            models::jwks::JsonWebKey,
+            models::jwks::JsonWebKeySet,
+            models::sessions::RefreshTokenRequest,
+            models::sessions::TokenPair,
+            models::sessions::PublicSession,
             models::api_tokens::CreateApiToken,
             models::api_tokens::PublicApiToken,
             models::api_tokens::NewApiToken,
+            models::api_tokens::ApiTokenScope,
             models::houses::NewHouse,
+            models::houses::UpdateHouse,
             models::houses::House,
+            models::houses::UnitSystem,
+            models::user_houses::HouseRole,
+            models::user_houses::HouseMember,
+            models::user_houses::MemberAccess,
+            models::user_houses::AccessWindow,
+            models::user_houses::Weekday,
+            models::house_invitations::HouseInvitation,
+            models::house_invitations::ReceivedHouseInvitation,
+            models::house_invitations::CreateHouseInvitation,
+            models::house_invitations::CreatedHouseInvitation,
+            models::house_invitations::AcceptHouseInvitation,
+            models::house_transfers::HouseTransfer,
+            models::house_transfers::ReceivedHouseTransfer,
+            models::house_transfers::CreateHouseTransfer,
             models::rooms::Room,
             models::rooms::NewRoom,
+            models::rooms::UpdateRoom,
+            models::zones::Zone,
+            models::zones::NewZone,
+            models::zones::UpdateZone,
             models::devices::CreateDevice,
             models::devices::Device,
-            models::devices::UpdateDevice
+            models::devices::UpdateDevice,
+            models::devices::MoveDevice,
+            models::devices::DeviceLocation,
+            models::devices::DeviceDetails,
+            models::devices::DeviceStatus,
+            models::devices::DeviceFilters,
+            models::devices::ConnectivityEvent,
+            models::device_types::DeviceType,
+            models::device_types::Capability,
+            models::device_types::CapabilityKind,
+            models::device_state::DeviceState,
+            models::device_state::UpdateDeviceState,
+            models::device_state::DeviceStateDelta,
+            models::device_commands::CommandKind,
+            models::device_commands::CommandStatus,
+            models::device_commands::DeviceCommand,
+            models::device_commands::NewDeviceCommand,
+            models::device_commands::FailDeviceCommand,
+            models::device_commands::DeviceCommandFilters,
+            models::admin::AdminUserFilters,
+            models::admin::UpdateUserRoleRequest,
+            models::admin::SystemStats,
+            models::auth_events::AuthEvent,
+            models::auth_events::AuthEventType,
+            models::auth_events::AuthEventFilters,
+            models::auth_events::SecurityEventsQuery
         )
     ),
     tags(
@@ -65,8 +192,13 @@ impl Modify for SecurityAddon {
         (name = "users", description = "User management endpoints"),
         (name = "tokens", description = "API token management endpoints"),
         (name = "houses", description = "House management endpoints"),
+        (name = "house_members", description = "House member and invitation endpoints"),
+        (name = "house_transfers", description = "House ownership transfer endpoints"),
         (name = "rooms", description = "Room management endpoints"),
+        (name = "zones", description = "Zone management endpoints"),
         (name = "devices", description = "Device management endpoints"),
+        (name = "device_commands", description = "Device command queue endpoints"),
+        (name = "admin", description = "Administration endpoints"),
         (name = "health", description = "Health check endpoints")
     ),
     info(
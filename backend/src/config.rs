@@ -1,6 +1,18 @@
+use std::net::IpAddr;
+
 use dotenvy::dotenv;
 use serde::Deserialize;
 
+/// Algorithm access tokens are signed with.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum JwtAlgorithm {
+    /// Shared secret from `jwt_secret`.
+    #[default]
+    HS256,
+    RS256,
+    EdDSA,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub port: u16,
@@ -10,8 +22,42 @@ pub struct Config {
     pub db_user: String,
     pub db_pass: String,
     pub jwt_secret: String,
+    /// `RS256` and `EdDSA` need `jwt_private_key_path`, `jwt_key_id` and
+    /// `jwt_public_keys_dir`.
+    #[serde(default)]
+    pub jwt_algorithm: JwtAlgorithm,
+    /// PEM file with the private key access tokens are signed with.
+    #[serde(default)]
+    pub jwt_private_key_path: Option<String>,
+    /// `kid` of the signing key. Its public key must be `<kid>.pem` in
+    /// `jwt_public_keys_dir`.
+    #[serde(default)]
+    pub jwt_key_id: Option<String>,
+    /// Directory of `<kid>.pem` public keys accepted for verification and
+    /// published as JWKS. Retired keys stay here until their tokens expire.
+    #[serde(default)]
+    pub jwt_public_keys_dir: Option<String>,
     pub jwt_expires_in: u64,
+    pub refresh_token_expires_in: u64,
+    pub api_token_secret: String,
     pub frontend_origin: String,
+    /// SMTP server to send mail through, e.g. `smtp://localhost:1025`.
+    /// Mail is written to the `outbox` table when unset.
+    #[serde(default)]
+    pub smtp_url: Option<String>,
+    #[serde(default = "default_mail_from")]
+    pub mail_from: String,
+    /// Reject logins of users who have not verified their email address.
+    #[serde(default)]
+    pub require_email_verification: bool,
+    /// Reverse proxies whose `X-Forwarded-For` is trusted, comma separated.
+    /// Without any, client addresses are always the connection's peer address.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+fn default_mail_from() -> String {
+    "Smart Home <no-reply@smarthome.local>".to_string()
 }
 
 impl Config {
@@ -40,7 +86,14 @@ mod tests {
             "supersecretjwtkeythatisatleast32characterslong",
         );
         env::set_var("JWT_EXPIRES_IN", "3600");
+        env::set_var("REFRESH_TOKEN_EXPIRES_IN", "2592000");
+        env::set_var("API_TOKEN_SECRET", "supersecretapitokenkey");
         env::set_var("FRONTEND_ORIGIN", "http://localhost:3000");
+        env::set_var("SMTP_URL", "smtp://localhost:1025");
+        env::set_var("REQUIRE_EMAIL_VERIFICATION", "true");
+        env::set_var("JWT_ALGORITHM", "EdDSA");
+        env::set_var("JWT_KEY_ID", "ed-2026-10");
+        env::set_var("TRUSTED_PROXIES", "10.0.0.1,::1");
 
         let config = Config::from_env();
 
@@ -55,7 +108,22 @@ mod tests {
             "supersecretjwtkeythatisatleast32characterslong"
         );
         assert_eq!(config.jwt_expires_in, 3600);
+        assert_eq!(config.refresh_token_expires_in, 2592000);
+        assert_eq!(config.api_token_secret, "supersecretapitokenkey");
         assert_eq!(config.frontend_origin, "http://localhost:3000");
+        assert_eq!(config.smtp_url.as_deref(), Some("smtp://localhost:1025"));
+        assert_eq!(config.mail_from, "Smart Home <no-reply@smarthome.local>");
+        assert!(config.require_email_verification);
+        assert_eq!(config.jwt_algorithm, JwtAlgorithm::EdDSA);
+        assert_eq!(config.jwt_key_id.as_deref(), Some("ed-2026-10"));
+        assert!(config.jwt_private_key_path.is_none());
+        assert_eq!(
+            config.trusted_proxies,
+            vec![
+                "10.0.0.1".parse::<IpAddr>().unwrap(),
+                "::1".parse::<IpAddr>().unwrap()
+            ]
+        );
 
         // Clean up environment variables
         env::remove_var("PORT");
@@ -66,6 +134,13 @@ mod tests {
         env::remove_var("DB_PASS");
         env::remove_var("JWT_SECRET");
         env::remove_var("JWT_EXPIRES_IN");
+        env::remove_var("REFRESH_TOKEN_EXPIRES_IN");
+        env::remove_var("API_TOKEN_SECRET");
         env::remove_var("FRONTEND_ORIGIN");
+        env::remove_var("SMTP_URL");
+        env::remove_var("REQUIRE_EMAIL_VERIFICATION");
+        env::remove_var("JWT_ALGORITHM");
+        env::remove_var("JWT_KEY_ID");
+        env::remove_var("TRUSTED_PROXIES");
     }
 }
@@ -1,6 +1,6 @@
 use axum::{
     extract::rejection::JsonRejection,
-    http::StatusCode,
+    http::{header::RETRY_AFTER, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -23,8 +23,16 @@ pub enum AppError {
     NotFound(String),
     #[error("Bad request: {0}")]
     BadRequest(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
     #[error("Internal server error: {0}")]
     InternalServerError(String),
+    #[error("Too many requests: {message}")]
+    TooManyRequests {
+        message: String,
+        /// Seconds until the client may try again.
+        retry_after: u64,
+    },
     #[error("JWT error: {0}")]
     JwtError(#[from] jsonwebtoken::errors::Error),
     #[error("Bcrypt error: {0}")]
@@ -69,6 +77,15 @@ impl IntoResponse for AppError {
                 )
                     .into_response()
             }
+            AppError::TooManyRequests {
+                message,
+                retry_after,
+            } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, retry_after.to_string())],
+                Json(ErrorResponse { error: message }),
+            )
+                .into_response(),
             _ => {
                 let (status, message) = match self {
                     AppError::DatabaseError(_) => (
@@ -79,6 +96,7 @@ impl IntoResponse for AppError {
                     AppError::AuthorizationError(msg) => (StatusCode::FORBIDDEN, msg),
                     AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
                     AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+                    AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
                     AppError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
                     AppError::JwtError(_) => (
                         StatusCode::UNAUTHORIZED,
@@ -88,7 +106,9 @@ impl IntoResponse for AppError {
                         StatusCode::INTERNAL_SERVER_ERROR,
                         "Password processing error".to_string(),
                     ),
-                    AppError::ValidationError(_) => unreachable!(),
+                    AppError::ValidationError(_) | AppError::TooManyRequests { .. } => {
+                        unreachable!()
+                    }
                 };
 
                 let body = Json(ErrorResponse { error: message });
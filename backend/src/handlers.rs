@@ -1,7 +1,13 @@
+pub mod admin;
 pub mod api_tokens;
 pub mod auth;
+pub mod device_commands;
 pub mod device_metrics;
+pub mod device_state;
 pub mod devices;
+pub mod house_members;
+pub mod house_transfers;
 pub mod houses;
 pub mod rooms;
 pub mod users;
+pub mod zones;
@@ -0,0 +1,230 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::{
+    errors::{Result, ValidationErrorResponse},
+    middlewares::{auth::RequireAdmin, validator::ValidatedJson},
+    models::{
+        admin::{AdminUserFilters, SystemStats, UpdateUserRoleRequest},
+        auth_events::{AuthEvent, AuthEventFilters},
+        common::ListResponse,
+        users::User,
+    },
+    routes::admin::AdminRouterState,
+    services::{admin::AdminServiceTrait, auth_events::AuthEventsServiceTrait},
+};
+
+/// List users endpoint
+///
+/// Lists and searches all users. Results are ordered by id and paginated with
+/// `limit` and `offset`.
+#[utoipa::path(
+    get,
+    path = "/admin/users",
+    params(AdminUserFilters),
+    responses(
+        (status = 200, description = "Users found", body = ListResponse<User>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Admin access required", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn list_users(
+    State(state): State<AdminRouterState>,
+    _admin: RequireAdmin,
+    Query(filters): Query<AdminUserFilters>,
+) -> Result<Json<ListResponse<User>>> {
+    let users = state.admin_service.list_users(filters).await?;
+
+    Ok(Json(ListResponse { items: users }))
+}
+
+/// Change user role endpoint
+#[utoipa::path(
+    put,
+    path = "/admin/users/{id}/role",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    request_body = UpdateUserRoleRequest,
+    responses(
+        (status = 200, description = "Role changed", body = User),
+        (status = 400, description = "Bad Request - Invalid input or own account", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Admin access required", body = String),
+        (status = 404, description = "User not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn update_user_role(
+    State(state): State<AdminRouterState>,
+    RequireAdmin(admin_id): RequireAdmin,
+    Path(user_id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<UpdateUserRoleRequest>,
+) -> Result<Json<User>> {
+    let user = state
+        .admin_service
+        .update_role(admin_id, user_id, payload.role)
+        .await?;
+
+    Ok(Json(user))
+}
+
+/// Disable user endpoint
+///
+/// Disables the account and revokes all of its sessions. Disabled users can't
+/// sign in and their API tokens stop working.
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/disable",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User disabled", body = User),
+        (status = 400, description = "Bad Request - Own account", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Admin access required", body = String),
+        (status = 404, description = "User not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn disable_user(
+    State(state): State<AdminRouterState>,
+    RequireAdmin(admin_id): RequireAdmin,
+    Path(user_id): Path<i64>,
+) -> Result<Json<User>> {
+    let user = state.admin_service.disable_user(admin_id, user_id).await?;
+
+    Ok(Json(user))
+}
+
+/// Enable user endpoint
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/enable",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User enabled", body = User),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Admin access required", body = String),
+        (status = 404, description = "User not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn enable_user(
+    State(state): State<AdminRouterState>,
+    _admin: RequireAdmin,
+    Path(user_id): Path<i64>,
+) -> Result<Json<User>> {
+    let user = state.admin_service.enable_user(user_id).await?;
+
+    Ok(Json(user))
+}
+
+/// Force logout endpoint
+///
+/// Revokes all sessions of the user.
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/logout",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 204, description = "Sessions revoked"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Admin access required", body = String),
+        (status = 404, description = "User not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn force_logout(
+    State(state): State<AdminRouterState>,
+    _admin: RequireAdmin,
+    Path(user_id): Path<i64>,
+) -> Result<StatusCode> {
+    state.admin_service.force_logout(user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// System statistics endpoint
+///
+/// Counts users, houses, rooms, devices and stored metric rows.
+#[utoipa::path(
+    get,
+    path = "/admin/stats",
+    responses(
+        (status = 200, description = "System statistics", body = SystemStats),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Admin access required", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn get_system_stats(
+    State(state): State<AdminRouterState>,
+    _admin: RequireAdmin,
+) -> Result<Json<SystemStats>> {
+    let stats = state.admin_service.get_system_stats().await?;
+
+    Ok(Json(stats))
+}
+
+/// List authentication events endpoint
+///
+/// Searches the authentication audit log of all users, newest first. Results are
+/// paginated with `limit` and `offset`.
+#[utoipa::path(
+    get,
+    path = "/admin/auth-events",
+    params(AuthEventFilters),
+    responses(
+        (status = 200, description = "Authentication events found", body = ListResponse<AuthEvent>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Admin access required", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn list_auth_events(
+    State(state): State<AdminRouterState>,
+    _admin: RequireAdmin,
+    Query(filters): Query<AuthEventFilters>,
+) -> Result<Json<ListResponse<AuthEvent>>> {
+    let events = state.auth_events_service.search(filters).await?;
+
+    Ok(Json(ListResponse { items: events }))
+}
@@ -3,11 +3,12 @@ use crate::{
     models::{
         api_tokens::{CreateApiToken, NewApiToken, PublicApiToken},
         common::ListResponse,
+        sessions::ClientInfo,
     },
     routes::api_tokens::ApiTokensRouterState,
 };
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
     response::{IntoResponse, Json},
     Extension,
@@ -22,6 +23,7 @@ use axum::{
     responses(
         (status = 201, description = "API token created successfully", body = NewApiToken),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Scopes exceed the user's role in the house"),
         (status = 500, description = "Internal Server Error")
     ),
     security(
@@ -32,11 +34,12 @@ use axum::{
 pub async fn create_api_token(
     State(state): State<ApiTokensRouterState>,
     Extension(user_id): Extension<i64>,
+    client: ClientInfo,
     Json(payload): Json<CreateApiToken>,
 ) -> Result<impl IntoResponse> {
     let new_token = state
         .tokens_service
-        .create_api_token(user_id, payload)
+        .create_api_token(user_id, payload, client)
         .await?;
     Ok((StatusCode::CREATED, Json(new_token)))
 }
@@ -62,3 +65,34 @@ pub async fn get_api_tokens(
     let tokens = state.tokens_service.get_api_tokens(user_id).await?;
     Ok(Json(ListResponse { items: tokens }))
 }
+
+/// Revoke one of the current user's API tokens
+#[utoipa::path(
+    delete,
+    path = "/api/tokens/{id}",
+    params(
+        ("id" = i64, Path, description = "API token ID")
+    ),
+    responses(
+        (status = 204, description = "API token revoked"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "API token not found"),
+        (status = 500, description = "Internal Server Error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "tokens"
+)]
+pub async fn delete_api_token(
+    State(state): State<ApiTokensRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(token_id): Path<i64>,
+    client: ClientInfo,
+) -> Result<StatusCode> {
+    state
+        .tokens_service
+        .delete_api_token(user_id, token_id, client)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::{
+    errors::{Result, ValidationErrorResponse},
+    middlewares::validator::ValidatedJson,
+    models::{
+        common::ListResponse,
+        device_commands::{
+            DeviceCommand, DeviceCommandFilters, FailDeviceCommand, NewDeviceCommand,
+        },
+    },
+    routes::device_commands::DeviceCommandsRouterState,
+};
+
+/// Send a command to a device
+///
+/// Queues a command for the device's gateway. `set` commands must be for a
+/// capability of the device type that can be set, within its range.
+#[utoipa::path(
+    post,
+    path = "/devices/{id}/commands",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    request_body = NewDeviceCommand,
+    responses(
+        (status = 201, description = "Command queued", body = DeviceCommand),
+        (status = 400, description = "Bad Request - Invalid command", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing devices", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_commands"
+)]
+pub async fn create_device_command(
+    State(router_state): State<Arc<DeviceCommandsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+    ValidatedJson(new_command): ValidatedJson<NewDeviceCommand>,
+) -> Result<(StatusCode, Json<DeviceCommand>)> {
+    let command = router_state
+        .device_commands_service
+        .create_command(user_id, device_id, new_command)
+        .await?;
+    Ok((StatusCode::CREATED, Json(command)))
+}
+
+/// List device commands
+///
+/// Lists the commands sent to a device, newest first.
+#[utoipa::path(
+    get,
+    path = "/devices/{id}/commands",
+    params(
+        ("id" = i64, Path, description = "Device ID"),
+        DeviceCommandFilters
+    ),
+    responses(
+        (status = 200, description = "Device commands", body = ListResponse<DeviceCommand>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_commands"
+)]
+pub async fn get_device_commands(
+    State(router_state): State<Arc<DeviceCommandsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+    Query(filters): Query<DeviceCommandFilters>,
+) -> Result<Json<ListResponse<DeviceCommand>>> {
+    let commands = router_state
+        .device_commands_service
+        .get_commands(user_id, device_id, filters)
+        .await?;
+    Ok(Json(ListResponse { items: commands }))
+}
+
+/// Fetch pending device commands
+///
+/// Hands the commands due for delivery to the device over to its gateway and
+/// marks them delivered. Commands not acknowledged within their timeout are
+/// handed out again until they run out of attempts.
+#[utoipa::path(
+    post,
+    path = "/devices/{id}/commands/fetch",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    responses(
+        (status = 200, description = "Commands to run, oldest first", body = ListResponse<DeviceCommand>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow reporting for devices", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_commands"
+)]
+pub async fn fetch_device_commands(
+    State(router_state): State<Arc<DeviceCommandsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+) -> Result<Json<ListResponse<DeviceCommand>>> {
+    let commands = router_state
+        .device_commands_service
+        .fetch_device_commands(user_id, device_id)
+        .await?;
+    Ok(Json(ListResponse { items: commands }))
+}
+
+/// Fetch pending house commands
+///
+/// Like fetching the commands of a device, for a gateway serving all the
+/// devices of a house.
+#[utoipa::path(
+    post,
+    path = "/houses/{house_id}/commands/fetch",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    responses(
+        (status = 200, description = "Commands to run, oldest first", body = ListResponse<DeviceCommand>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow reporting for devices", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_commands"
+)]
+pub async fn fetch_house_commands(
+    State(router_state): State<Arc<DeviceCommandsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+) -> Result<Json<ListResponse<DeviceCommand>>> {
+    let commands = router_state
+        .device_commands_service
+        .fetch_house_commands(user_id, house_id)
+        .await?;
+    Ok(Json(ListResponse { items: commands }))
+}
+
+/// Acknowledge a device command
+///
+/// Reports that the device has run a delivered command.
+#[utoipa::path(
+    post,
+    path = "/devices/{id}/commands/{command_id}/ack",
+    params(
+        ("id" = i64, Path, description = "Device ID"),
+        ("command_id" = i64, Path, description = "Command ID")
+    ),
+    responses(
+        (status = 200, description = "Command acknowledged", body = DeviceCommand),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow reporting for devices", body = String),
+        (status = 404, description = "Command not found", body = String),
+        (status = 409, description = "Conflict - The command is not delivered", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_commands"
+)]
+pub async fn acknowledge_device_command(
+    State(router_state): State<Arc<DeviceCommandsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path((device_id, command_id)): Path<(i64, i64)>,
+) -> Result<Json<DeviceCommand>> {
+    let command = router_state
+        .device_commands_service
+        .acknowledge_command(user_id, device_id, command_id)
+        .await?;
+    Ok(Json(command))
+}
+
+/// Fail a device command
+///
+/// Reports that the device could not run a delivered command.
+#[utoipa::path(
+    post,
+    path = "/devices/{id}/commands/{command_id}/fail",
+    params(
+        ("id" = i64, Path, description = "Device ID"),
+        ("command_id" = i64, Path, description = "Command ID")
+    ),
+    request_body = FailDeviceCommand,
+    responses(
+        (status = 200, description = "Command failed", body = DeviceCommand),
+        (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow reporting for devices", body = String),
+        (status = 404, description = "Command not found", body = String),
+        (status = 409, description = "Conflict - The command is not delivered", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_commands"
+)]
+pub async fn fail_device_command(
+    State(router_state): State<Arc<DeviceCommandsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path((device_id, command_id)): Path<(i64, i64)>,
+    ValidatedJson(failure): ValidatedJson<FailDeviceCommand>,
+) -> Result<Json<DeviceCommand>> {
+    let command = router_state
+        .device_commands_service
+        .fail_command(user_id, device_id, command_id, failure)
+        .await?;
+    Ok(Json(command))
+}
+
+/// Expire a device command
+///
+/// Cancels a command that has not been acknowledged or failed yet.
+#[utoipa::path(
+    post,
+    path = "/devices/{id}/commands/{command_id}/expire",
+    params(
+        ("id" = i64, Path, description = "Device ID"),
+        ("command_id" = i64, Path, description = "Command ID")
+    ),
+    responses(
+        (status = 200, description = "Command expired", body = DeviceCommand),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing devices", body = String),
+        (status = 404, description = "Command not found", body = String),
+        (status = 409, description = "Conflict - The command is already completed", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_commands"
+)]
+pub async fn expire_device_command(
+    State(router_state): State<Arc<DeviceCommandsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path((device_id, command_id)): Path<(i64, i64)>,
+) -> Result<Json<DeviceCommand>> {
+    let command = router_state
+        .device_commands_service
+        .expire_command(user_id, device_id, command_id)
+        .await?;
+    Ok(Json(command))
+}
@@ -9,8 +9,12 @@ use axum::{
 use crate::{
     errors::Result,
     middlewares::validator::ValidatedJson,
-    models::device_metrics::{CreateDeviceMetric, DeviceMetric, DeviceMetricFilters},
+    models::{
+        api_tokens::ApiTokenGrant,
+        device_metrics::{CreateDeviceMetric, DeviceMetric, DeviceMetricFilters},
+    },
     routes::device_metrics::DeviceMetricsRouterState,
+    services::access_control_service::AccessTarget,
 };
 
 /// Create a new device metric
@@ -34,9 +38,17 @@ use crate::{
 pub async fn create_metric(
     State(router_state): State<Arc<DeviceMetricsRouterState>>,
     Extension(user_id): Extension<i64>,
+    grant: Option<Extension<ApiTokenGrant>>,
     ValidatedJson(new_metric): ValidatedJson<CreateDeviceMetric>,
 ) -> Result<(StatusCode, Json<DeviceMetric>)> {
     {
+        if let Some(Extension(grant)) = grant {
+            router_state
+                .access_control_service
+                .validate_token_house(&grant, AccessTarget::Device(new_metric.device_id))
+                .await?;
+        }
+
         let metric = router_state
             .device_metrics_service
             .create_metric(user_id, new_metric)
@@ -152,3 +164,39 @@ pub async fn get_metrics_for_house(
         Ok(Json(metrics))
     }
 }
+
+/// Get device metrics for a zone
+///
+/// Retrieves device metrics for the rooms of a zone with optional filters. Members
+/// limited to some rooms only get the metrics of those rooms.
+#[utoipa::path(
+    get,
+    path = "/houses/{house_id}/zones/{zone_id}/metrics",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("zone_id" = i64, Path, description = "Zone ID"),
+        DeviceMetricFilters
+    ),
+    responses(
+        (status = 200, description = "Device metrics found", body = Vec<DeviceMetric>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Zone not found"),
+        (status = 500, description = "Internal Server Error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "device_metrics"
+)]
+pub async fn get_metrics_for_zone(
+    State(router_state): State<Arc<DeviceMetricsRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, zone_id)): Path<(i64, i64)>,
+    Query(filters): Query<DeviceMetricFilters>,
+) -> Result<Json<Vec<DeviceMetric>>> {
+    let metrics = router_state
+        .device_metrics_service
+        .get_metrics_for_zone(user_id, house_id, zone_id, filters)
+        .await?;
+    Ok(Json(metrics))
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Path, State},
+    Json,
+};
+
+use crate::{
+    errors::Result,
+    middlewares::validator::ValidatedJson,
+    models::device_state::{DeviceState, DeviceStateDelta, UpdateDeviceState},
+    routes::device_state::DeviceStateRouterState,
+};
+
+/// Get device state
+///
+/// Retrieves the desired and reported values of a device.
+#[utoipa::path(
+    get,
+    path = "/devices/{id}/state",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    responses(
+        (status = 200, description = "Device state", body = DeviceState),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn get_device_state(
+    State(router_state): State<Arc<DeviceStateRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+) -> Result<Json<DeviceState>> {
+    let state = router_state
+        .device_state_service
+        .get_state(user_id, device_id)
+        .await?;
+    Ok(Json(state))
+}
+
+/// Update device state
+///
+/// Merges desired or reported values into the state of a device. Values must
+/// be for capabilities of the device type and within their range.
+#[utoipa::path(
+    patch,
+    path = "/devices/{id}/state",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    request_body = UpdateDeviceState,
+    responses(
+        (status = 200, description = "Device state updated", body = DeviceState),
+        (status = 400, description = "Bad Request - Invalid values", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow changing the device", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 409, description = "Conflict - The state has another version", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn update_device_state(
+    State(router_state): State<Arc<DeviceStateRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+    ValidatedJson(update): ValidatedJson<UpdateDeviceState>,
+) -> Result<Json<DeviceState>> {
+    let state = router_state
+        .device_state_service
+        .update_state(user_id, device_id, update)
+        .await?;
+    Ok(Json(state))
+}
+
+/// Get device state delta
+///
+/// Lists the desired values the device has not reported yet. Gateways poll it
+/// to learn which changes to apply.
+#[utoipa::path(
+    get,
+    path = "/devices/{id}/state/delta",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    responses(
+        (status = 200, description = "Desired values not applied yet", body = DeviceStateDelta),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn get_device_state_delta(
+    State(router_state): State<Arc<DeviceStateRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+) -> Result<Json<DeviceStateDelta>> {
+    let delta = router_state
+        .device_state_service
+        .get_delta(user_id, device_id)
+        .await?;
+    Ok(Json(delta))
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -10,10 +10,16 @@ use crate::{
     errors::{AppError, Result, ValidationErrorResponse},
     middlewares::validator::ValidatedJson,
     models::{
+        api_tokens::ApiTokenGrant,
         common::ListResponse,
-        devices::{CreateDevice, Device, UpdateDevice},
+        device_types::{DeviceType, DEVICE_TYPES},
+        devices::{
+            ConnectivityEvent, CreateDevice, Device, DeviceDetails, DeviceFilters, DeviceLocation,
+            MoveDevice, UpdateDevice,
+        },
     },
     routes::{devices::DeviceRouterState, rooms::HouseAccess},
+    services::access_control_service::{AccessTarget, Action},
 };
 
 /// Create a new device
@@ -27,6 +33,7 @@ use crate::{
         (status = 201, description = "Device created", body = Device),
         (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
         (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing devices", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
     security(
@@ -36,8 +43,22 @@ use crate::{
 )]
 pub async fn create_device(
     State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
+    grant: Option<Extension<ApiTokenGrant>>,
     ValidatedJson(new_device): ValidatedJson<CreateDevice>,
 ) -> Result<(StatusCode, Json<Device>)> {
+    router_state
+        .access_control_service
+        .can_access_room(user_id, new_device.room_id, Action::ManageDevices)
+        .await?;
+
+    if let Some(Extension(grant)) = grant {
+        router_state
+            .access_control_service
+            .validate_token_house(&grant, AccessTarget::Room(new_device.room_id))
+            .await?;
+    }
+
     let device = router_state
         .device_service
         .create_device(new_device)
@@ -47,7 +68,7 @@ pub async fn create_device(
 
 /// Get device by ID
 ///
-/// Retrieves a specific device by its ID.
+/// Retrieves a specific device by its ID, with the capabilities of its type.
 #[utoipa::path(
     get,
     path = "/devices/{id}",
@@ -55,7 +76,7 @@ pub async fn create_device(
         ("id" = i64, Path, description = "Device ID")
     ),
     responses(
-        (status = 200, description = "Device found", body = Device),
+        (status = 200, description = "Device found", body = DeviceDetails),
         (status = 401, description = "Unauthorized", body = String),
         (status = 404, description = "Device not found", body = String),
         (status = 500, description = "Internal Server Error", body = String)
@@ -67,18 +88,46 @@ pub async fn create_device(
 )]
 pub async fn get_device_by_id(
     State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
     Path(device_id): Path<i64>,
-) -> Result<Json<Device>> {
+) -> Result<Json<DeviceDetails>> {
+    router_state
+        .access_control_service
+        .can_access_device(user_id, device_id, Action::ViewHouse)
+        .await?;
+
     let device = router_state
         .device_service
         .get_device_by_id(device_id)
         .await?;
-    Ok(Json(device))
+    Ok(Json(device.into()))
+}
+
+/// List device types
+///
+/// Lists the device types devices can be created with and their capabilities.
+#[utoipa::path(
+    get,
+    path = "/device-types",
+    responses(
+        (status = 200, description = "Device types", body = ListResponse<DeviceType>),
+        (status = 401, description = "Unauthorized", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn list_device_types() -> Json<ListResponse<DeviceType>> {
+    Json(ListResponse {
+        items: DEVICE_TYPES.to_vec(),
+    })
 }
 
 /// Update a device
 ///
-/// Updates an existing device.
+/// Updates the name or type of an existing device. Devices are moved to another
+/// room with `POST /devices/{id}/move`.
 #[utoipa::path(
     put,
     path = "/devices/{id}",
@@ -90,6 +139,7 @@ pub async fn get_device_by_id(
         (status = 200, description = "Device updated", body = Device),
         (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
         (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing devices", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
     security(
@@ -99,9 +149,15 @@ pub async fn get_device_by_id(
 )]
 pub async fn update_device(
     State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
     Path(device_id): Path<i64>,
     ValidatedJson(updated_device): ValidatedJson<UpdateDevice>,
 ) -> Result<Json<Device>> {
+    router_state
+        .access_control_service
+        .can_access_device(user_id, device_id, Action::ManageDevices)
+        .await?;
+
     let device = router_state
         .device_service
         .update_device(device_id, updated_device)
@@ -109,6 +165,174 @@ pub async fn update_device(
     Ok(Json(device))
 }
 
+/// Move a device
+///
+/// Moves a device to another room, possibly in another house. The user must be
+/// allowed to manage devices in both the current and the new room. The move is
+/// recorded in the device's location history.
+#[utoipa::path(
+    post,
+    path = "/devices/{id}/move",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    request_body = MoveDevice,
+    responses(
+        (status = 200, description = "Device moved", body = Device),
+        (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing devices", body = String),
+        (status = 404, description = "Device or room not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn move_device(
+    State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
+    grant: Option<Extension<ApiTokenGrant>>,
+    Path(device_id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<MoveDevice>,
+) -> Result<Json<Device>> {
+    router_state
+        .access_control_service
+        .can_access_device(user_id, device_id, Action::ManageDevices)
+        .await?;
+    router_state
+        .access_control_service
+        .can_access_room(user_id, payload.room_id, Action::ManageDevices)
+        .await?;
+
+    if let Some(Extension(grant)) = grant {
+        router_state
+            .access_control_service
+            .validate_token_house(&grant, AccessTarget::Room(payload.room_id))
+            .await?;
+    }
+
+    let device = router_state
+        .device_service
+        .move_device(device_id, payload.room_id, user_id)
+        .await?;
+    Ok(Json(device))
+}
+
+/// Get device location history
+///
+/// Lists the rooms a device has been in, oldest first.
+#[utoipa::path(
+    get,
+    path = "/devices/{id}/locations",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    responses(
+        (status = 200, description = "Location history", body = ListResponse<DeviceLocation>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn get_device_location_history(
+    State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+) -> Result<Json<ListResponse<DeviceLocation>>> {
+    router_state
+        .access_control_service
+        .can_access_device(user_id, device_id, Action::ViewHouse)
+        .await?;
+
+    let history = router_state
+        .device_service
+        .get_location_history(device_id)
+        .await?;
+    Ok(Json(ListResponse { items: history }))
+}
+
+/// Send a device heartbeat
+///
+/// Records that the device is alive, bringing it online. Metrics reported for
+/// the device count as heartbeats too.
+#[utoipa::path(
+    post,
+    path = "/devices/{id}/heartbeat",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    responses(
+        (status = 200, description = "Device is online", body = Device),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow reporting for devices", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn record_device_heartbeat(
+    State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+) -> Result<Json<Device>> {
+    router_state
+        .access_control_service
+        .can_access_device(user_id, device_id, Action::WriteMetrics)
+        .await?;
+
+    let device = router_state
+        .device_service
+        .record_heartbeat(device_id)
+        .await?;
+    Ok(Json(device))
+}
+
+/// Get device connectivity events
+///
+/// Lists the times a device came online or went offline, newest first.
+#[utoipa::path(
+    get,
+    path = "/devices/{id}/connectivity",
+    params(
+        ("id" = i64, Path, description = "Device ID")
+    ),
+    responses(
+        (status = 200, description = "Connectivity events", body = ListResponse<ConnectivityEvent>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Device not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn get_device_connectivity_events(
+    State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path(device_id): Path<i64>,
+) -> Result<Json<ListResponse<ConnectivityEvent>>> {
+    router_state
+        .access_control_service
+        .can_access_device(user_id, device_id, Action::ViewHouse)
+        .await?;
+
+    let events = router_state
+        .device_service
+        .get_connectivity_events(device_id)
+        .await?;
+    Ok(Json(ListResponse { items: events }))
+}
+
 /// Delete a device
 ///
 /// Deletes a device by its ID.
@@ -121,6 +345,7 @@ pub async fn update_device(
     responses(
         (status = 204, description = "Device deleted successfully"),
         (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing devices", body = String),
         (status = 404, description = "Device not found", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
@@ -131,8 +356,14 @@ pub async fn update_device(
 )]
 pub async fn delete_device(
     State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
     Path(device_id): Path<i64>,
 ) -> Result<StatusCode> {
+    router_state
+        .access_control_service
+        .can_access_device(user_id, device_id, Action::ManageDevices)
+        .await?;
+
     router_state.device_service.delete_device(device_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
@@ -145,7 +376,8 @@ pub async fn delete_device(
     path = "/houses/{house_id}/rooms/{room_id}/devices",
     params(
         ("house_id" = i64, Path, description = "House ID"),
-        ("room_id" = i64, Path, description = "Room ID")
+        ("room_id" = i64, Path, description = "Room ID"),
+        DeviceFilters
     ),
     responses(
         (status = 200, description = "Devices found", body = ListResponse<Device>),
@@ -162,6 +394,7 @@ pub async fn get_devices_by_room_id(
     State(router_state): State<Arc<DeviceRouterState>>,
     Extension(user_id): Extension<i64>,
     Path((house_id, room_id)): Path<(i64, i64)>,
+    Query(filters): Query<DeviceFilters>,
 ) -> Result<Json<ListResponse<Device>>> {
     // Changed return type
     router_state
@@ -175,21 +408,74 @@ pub async fn get_devices_by_room_id(
         return Err(AppError::AuthenticationError("Access denied".to_string()));
     }
 
+    router_state
+        .access_control_service
+        .can_access_room(user_id, room_id, Action::ViewHouse)
+        .await?;
+
+    let devices = router_state
+        .device_service
+        .get_devices_by_room_id(room_id, filters)
+        .await?;
+    Ok(Json(ListResponse { items: devices }))
+}
+
+/// Get devices by zone ID
+///
+/// Retrieves devices in the rooms of a zone. Members limited to some rooms only
+/// see the devices in those rooms.
+#[utoipa::path(
+    get,
+    path = "/houses/{house_id}/zones/{zone_id}/devices",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("zone_id" = i64, Path, description = "Zone ID"),
+        DeviceFilters
+    ),
+    responses(
+        (status = 200, description = "Devices found", body = ListResponse<Device>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Zone not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "devices"
+)]
+pub async fn get_devices_by_zone_id(
+    State(router_state): State<Arc<DeviceRouterState>>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, zone_id)): Path<(i64, i64)>,
+    Query(filters): Query<DeviceFilters>,
+) -> Result<Json<ListResponse<Device>>> {
+    router_state
+        .zone_service
+        .get_zone(user_id, house_id, zone_id)
+        .await?;
+
+    let room_ids = router_state
+        .access_control_service
+        .visible_rooms(user_id, house_id)
+        .await?;
+
     let devices = router_state
         .device_service
-        .get_devices_by_room_id(room_id)
+        .get_devices_by_zone_id(zone_id, room_ids, filters)
         .await?;
     Ok(Json(ListResponse { items: devices }))
 }
 
 /// Get devices by house ID
 ///
-/// Retrieves devices associated with a specific house by its ID.
+/// Retrieves devices associated with a specific house by its ID. Members limited to
+/// some rooms only see the devices in those rooms.
 #[utoipa::path(
     get,
     path = "/houses/{house_id}/devices",
     params(
-        ("house_id" = i64, Path, description = "House ID")
+        ("house_id" = i64, Path, description = "House ID"),
+        DeviceFilters
     ),
     responses(
         (status = 200, description = "Devices found", body = ListResponse<Device>),
@@ -203,14 +489,17 @@ pub async fn get_devices_by_room_id(
 )]
 pub async fn get_devices_by_house_id(
     State(router_state): State<Arc<DeviceRouterState>>,
-    HouseAccess {
-        house_id,
-        user_id: _,
-    }: HouseAccess,
+    HouseAccess { house_id, user_id }: HouseAccess,
+    Query(filters): Query<DeviceFilters>,
 ) -> Result<Json<ListResponse<Device>>> {
+    let room_ids = router_state
+        .access_control_service
+        .visible_rooms(user_id, house_id)
+        .await?;
+
     let devices = router_state
         .device_service
-        .get_devices_by_house_id(house_id)
+        .get_devices_by_house_id(house_id, room_ids, filters)
         .await?;
     Ok(Json(ListResponse { items: devices }))
 }
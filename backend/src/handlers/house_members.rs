@@ -0,0 +1,403 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::{
+    errors::{Result, ValidationErrorResponse},
+    middlewares::validator::ValidatedJson,
+    models::{
+        common::ListResponse,
+        house_invitations::{
+            AcceptHouseInvitation, CreateHouseInvitation, CreatedHouseInvitation, HouseInvitation,
+            ReceivedHouseInvitation,
+        },
+        user_houses::{HouseMember, MemberAccess},
+    },
+    routes::house_members::HouseMembersRouterState,
+};
+
+/// List house members endpoint
+///
+/// Lists the users with access to a house and their roles, owners first.
+#[utoipa::path(
+    get,
+    path = "/houses/{house_id}/members",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    responses(
+        (status = 200, description = "House members", body = ListResponse<HouseMember>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn list_members(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+) -> Result<Json<ListResponse<HouseMember>>> {
+    let members = state
+        .house_members_service
+        .list_members(user_id, house_id)
+        .await?;
+
+    Ok(Json(ListResponse { items: members }))
+}
+
+/// Remove house member endpoint
+///
+/// Removes a member from a house. Requires the owner or admin role, and only owners
+/// can remove owners. The last owner of a house can't be removed.
+#[utoipa::path(
+    delete,
+    path = "/houses/{house_id}/members/{user_id}",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("user_id" = i64, Path, description = "ID of the member to remove")
+    ),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 400, description = "Bad Request - The house must keep an owner", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow removing this member", body = String),
+        (status = 404, description = "Member not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn remove_member(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, member_id)): Path<(i64, i64)>,
+) -> Result<StatusCode> {
+    state
+        .house_members_service
+        .remove_member(user_id, house_id, member_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Update member access endpoint
+///
+/// Replaces the access limits of a member: when their access starts and ends,
+/// the weekly windows and the rooms it is limited to. Omitted limits are removed.
+/// Members are removed from the house once their access ends and the owners are
+/// notified. Requires the owner or admin role. Owners can't be limited, and admins
+/// can't be limited to rooms.
+#[utoipa::path(
+    put,
+    path = "/houses/{house_id}/members/{user_id}/access",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("user_id" = i64, Path, description = "ID of the member")
+    ),
+    request_body = MemberAccess,
+    responses(
+        (status = 204, description = "Access limits updated"),
+        (status = 400, description = "Bad Request - Invalid access limits", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing members", body = String),
+        (status = 404, description = "Member not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn update_member_access(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, member_id)): Path<(i64, i64)>,
+    ValidatedJson(payload): ValidatedJson<MemberAccess>,
+) -> Result<StatusCode> {
+    state
+        .house_members_service
+        .update_member_access(user_id, house_id, member_id, payload)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Leave house endpoint
+///
+/// Removes the current user from a house. The last owner of a house can't leave it.
+#[utoipa::path(
+    post,
+    path = "/houses/{house_id}/leave",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    responses(
+        (status = 204, description = "Left the house"),
+        (status = 400, description = "Bad Request - The house must keep an owner", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn leave_house(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+) -> Result<StatusCode> {
+    state
+        .house_members_service
+        .leave_house(user_id, house_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Create house invitation endpoint
+///
+/// Invites someone to a house. Invitations for an email address are sent there and
+/// can only be accepted by that user; without an email the code can be shared with
+/// anyone. The code is only returned once. Access limits of the invitation apply to
+/// the membership once accepted. Requires the owner or admin role, and only owners
+/// can invite owners.
+#[utoipa::path(
+    post,
+    path = "/houses/{house_id}/invitations",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    request_body = CreateHouseInvitation,
+    responses(
+        (status = 201, description = "Invitation created", body = CreatedHouseInvitation),
+        (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow inviting members", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn create_invitation(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<CreateHouseInvitation>,
+) -> Result<(StatusCode, Json<CreatedHouseInvitation>)> {
+    let invitation = state
+        .house_members_service
+        .create_invitation(user_id, house_id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(invitation)))
+}
+
+/// List house invitations endpoint
+///
+/// Lists the pending invitations of a house. Requires the owner or admin role.
+#[utoipa::path(
+    get,
+    path = "/houses/{house_id}/invitations",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    responses(
+        (status = 200, description = "Pending invitations", body = ListResponse<HouseInvitation>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing members", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn list_house_invitations(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+) -> Result<Json<ListResponse<HouseInvitation>>> {
+    let invitations = state
+        .house_members_service
+        .list_house_invitations(user_id, house_id)
+        .await?;
+
+    Ok(Json(ListResponse { items: invitations }))
+}
+
+/// Revoke house invitation endpoint
+///
+/// Deletes an invitation so it can no longer be accepted. Requires the owner or
+/// admin role.
+#[utoipa::path(
+    delete,
+    path = "/houses/{house_id}/invitations/{id}",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("id" = i64, Path, description = "Invitation ID")
+    ),
+    responses(
+        (status = 204, description = "Invitation revoked"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing members", body = String),
+        (status = 404, description = "Invitation not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn revoke_invitation(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, invitation_id)): Path<(i64, i64)>,
+) -> Result<StatusCode> {
+    state
+        .house_members_service
+        .revoke_invitation(user_id, house_id, invitation_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List received invitations endpoint
+///
+/// Lists the pending invitations sent to the current user's email address. The
+/// email address has to be verified.
+#[utoipa::path(
+    get,
+    path = "/invitations",
+    responses(
+        (status = 200, description = "Pending invitations", body = ListResponse<ReceivedHouseInvitation>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Email address is not verified", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn list_received_invitations(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+) -> Result<Json<ListResponse<ReceivedHouseInvitation>>> {
+    let invitations = state
+        .house_members_service
+        .list_received_invitations(user_id)
+        .await?;
+
+    Ok(Json(ListResponse { items: invitations }))
+}
+
+/// Accept invitation code endpoint
+///
+/// Joins a house with an invitation code or the code from an invitation link.
+#[utoipa::path(
+    post,
+    path = "/invitations/accept",
+    request_body = AcceptHouseInvitation,
+    responses(
+        (status = 200, description = "Invitation accepted", body = HouseInvitation),
+        (status = 400, description = "Bad Request - Invalid or expired invitation", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Invitation is for a different email address", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn accept_invitation_code(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    ValidatedJson(payload): ValidatedJson<AcceptHouseInvitation>,
+) -> Result<Json<HouseInvitation>> {
+    let invitation = state
+        .house_members_service
+        .accept_invitation_code(user_id, &payload.code)
+        .await?;
+
+    Ok(Json(invitation))
+}
+
+/// Accept invitation endpoint
+///
+/// Accepts an invitation sent to the current user's verified email address.
+#[utoipa::path(
+    post,
+    path = "/invitations/{id}/accept",
+    params(
+        ("id" = i64, Path, description = "Invitation ID")
+    ),
+    responses(
+        (status = 200, description = "Invitation accepted", body = HouseInvitation),
+        (status = 400, description = "Bad Request - Already a member", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Email address is not verified", body = String),
+        (status = 404, description = "Invitation not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn accept_invitation(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(invitation_id): Path<i64>,
+) -> Result<Json<HouseInvitation>> {
+    let invitation = state
+        .house_members_service
+        .accept_invitation(user_id, invitation_id)
+        .await?;
+
+    Ok(Json(invitation))
+}
+
+/// Decline invitation endpoint
+///
+/// Declines an invitation sent to the current user's verified email address.
+#[utoipa::path(
+    post,
+    path = "/invitations/{id}/decline",
+    params(
+        ("id" = i64, Path, description = "Invitation ID")
+    ),
+    responses(
+        (status = 204, description = "Invitation declined"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Email address is not verified", body = String),
+        (status = 404, description = "Invitation not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_members"
+)]
+pub async fn decline_invitation(
+    State(state): State<HouseMembersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(invitation_id): Path<i64>,
+) -> Result<StatusCode> {
+    state
+        .house_members_service
+        .decline_invitation(user_id, invitation_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,219 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::{
+    errors::{Result, ValidationErrorResponse},
+    middlewares::validator::ValidatedJson,
+    models::{
+        common::ListResponse,
+        house_transfers::{CreateHouseTransfer, HouseTransfer, ReceivedHouseTransfer},
+    },
+    routes::house_transfers::HouseTransfersRouterState,
+};
+
+/// Transfer house endpoint
+///
+/// Nominates a member to take over the house and emails them. The nominee must
+/// have verified their email address and have active access to the house. Only
+/// owners can transfer a house, and a pending transfer of the house is replaced.
+#[utoipa::path(
+    post,
+    path = "/houses/{house_id}/transfers",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    request_body = CreateHouseTransfer,
+    responses(
+        (status = 201, description = "Transfer created", body = HouseTransfer),
+        (status = 400, description = "Bad Request - Invalid nominee", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Only owners can transfer the house", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_transfers"
+)]
+pub async fn create_transfer(
+    State(state): State<HouseTransfersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<CreateHouseTransfer>,
+) -> Result<(StatusCode, Json<HouseTransfer>)> {
+    let transfer = state
+        .house_transfers_service
+        .create_transfer(user_id, house_id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(transfer)))
+}
+
+/// List house transfers endpoint
+///
+/// Lists all transfers of a house, newest first, including accepted, declined
+/// and cancelled ones. Requires the owner or admin role.
+#[utoipa::path(
+    get,
+    path = "/houses/{house_id}/transfers",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    responses(
+        (status = 200, description = "House transfers", body = ListResponse<HouseTransfer>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing members", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_transfers"
+)]
+pub async fn list_transfers(
+    State(state): State<HouseTransfersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+) -> Result<Json<ListResponse<HouseTransfer>>> {
+    let transfers = state
+        .house_transfers_service
+        .list_transfers(user_id, house_id)
+        .await?;
+
+    Ok(Json(ListResponse { items: transfers }))
+}
+
+/// Cancel house transfer endpoint
+///
+/// Cancels a pending transfer so it can no longer be accepted. Only owners can
+/// cancel transfers.
+#[utoipa::path(
+    post,
+    path = "/houses/{house_id}/transfers/{id}/cancel",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("id" = i64, Path, description = "Transfer ID")
+    ),
+    responses(
+        (status = 204, description = "Transfer cancelled"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Only owners can cancel transfers", body = String),
+        (status = 404, description = "Transfer not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_transfers"
+)]
+pub async fn cancel_transfer(
+    State(state): State<HouseTransfersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, transfer_id)): Path<(i64, i64)>,
+) -> Result<StatusCode> {
+    state
+        .house_transfers_service
+        .cancel_transfer(user_id, house_id, transfer_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List received transfers endpoint
+///
+/// Lists the pending transfers of houses to the current user.
+#[utoipa::path(
+    get,
+    path = "/transfers",
+    responses(
+        (status = 200, description = "Pending transfers", body = ListResponse<ReceivedHouseTransfer>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_transfers"
+)]
+pub async fn list_received_transfers(
+    State(state): State<HouseTransfersRouterState>,
+    Extension(user_id): Extension<i64>,
+) -> Result<Json<ListResponse<ReceivedHouseTransfer>>> {
+    let transfers = state
+        .house_transfers_service
+        .list_received_transfers(user_id)
+        .await?;
+
+    Ok(Json(ListResponse { items: transfers }))
+}
+
+/// Accept transfer endpoint
+///
+/// Takes over a house. The current user becomes an owner without access limits,
+/// and the previous owner gets the role the current user had.
+#[utoipa::path(
+    post,
+    path = "/transfers/{id}/accept",
+    params(
+        ("id" = i64, Path, description = "Transfer ID")
+    ),
+    responses(
+        (status = 200, description = "Transfer accepted", body = HouseTransfer),
+        (status = 400, description = "Bad Request - Invalid or expired transfer", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Transfer not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_transfers"
+)]
+pub async fn accept_transfer(
+    State(state): State<HouseTransfersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(transfer_id): Path<i64>,
+) -> Result<Json<HouseTransfer>> {
+    let transfer = state
+        .house_transfers_service
+        .accept_transfer(user_id, transfer_id)
+        .await?;
+
+    Ok(Json(transfer))
+}
+
+/// Decline transfer endpoint
+///
+/// Declines a transfer of a house to the current user.
+#[utoipa::path(
+    post,
+    path = "/transfers/{id}/decline",
+    params(
+        ("id" = i64, Path, description = "Transfer ID")
+    ),
+    responses(
+        (status = 204, description = "Transfer declined"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 404, description = "Transfer not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "house_transfers"
+)]
+pub async fn decline_transfer(
+    State(state): State<HouseTransfersRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(transfer_id): Path<i64>,
+) -> Result<StatusCode> {
+    state
+        .house_transfers_service
+        .decline_transfer(user_id, transfer_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,15 +1,17 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Extension, Json,
-};
+use axum::{extract::State, http::StatusCode, Extension, Json};
 
 use crate::{
-    errors::{Result, ValidationErrorResponse},
+    errors::{AppError, Result, ValidationErrorResponse},
     middlewares::validator::ValidatedJson,
-    models::houses::{House, NewHouse},
+    models::{
+        api_tokens::ApiTokenGrant,
+        houses::{House, NewHouse, UpdateHouse},
+    },
     routes::{houses::HousesRouterState, rooms::HouseAccess},
-    services::house::HouseServiceTrait,
+    services::{
+        access_control_service::{AccessControlServiceTrait, Action},
+        house::HouseServiceTrait,
+    },
 };
 
 use crate::models::common::ListResponse;
@@ -33,8 +35,14 @@ use crate::models::common::ListResponse;
 pub async fn get_user_houses(
     State(state): State<HousesRouterState>,
     Extension(user_id): Extension<i64>,
+    grant: Option<Extension<ApiTokenGrant>>,
 ) -> Result<Json<ListResponse<House>>> {
-    let houses = state.house_service.get_user_houses(user_id).await?;
+    let mut houses = state.house_service.get_user_houses(user_id).await?;
+
+    // House-restricted API tokens only see their own house
+    if let Some(house_id) = grant.and_then(|Extension(grant)| grant.house_id) {
+        houses.retain(|house| house.id == house_id);
+    }
 
     Ok(Json(ListResponse { items: houses }))
 }
@@ -61,7 +69,7 @@ pub async fn get_user_houses(
 )]
 pub async fn get_user_house_by_id(
     State(state): State<HousesRouterState>,
-    Path(house_id): Path<i64>,
+    HouseAccess { house_id, .. }: HouseAccess,
 ) -> Result<Json<House>> {
     let house = state.house_service.get_house_by_id(house_id).await?;
 
@@ -89,16 +97,62 @@ pub async fn get_user_house_by_id(
 pub async fn create_house(
     State(state): State<HousesRouterState>,
     Extension(user_id): Extension<i64>,
+    grant: Option<Extension<ApiTokenGrant>>,
     ValidatedJson(payload): ValidatedJson<NewHouse>,
 ) -> Result<(StatusCode, Json<House>)> {
+    if grant.is_some_and(|Extension(grant)| grant.house_id.is_some()) {
+        return Err(AppError::AuthorizationError(
+            "API token is restricted to a single house".to_string(),
+        ));
+    }
+
     let house = state.house_service.create_house(user_id, payload).await?;
 
     Ok((StatusCode::CREATED, Json(house)))
 }
 
+/// Update house endpoint
+///
+/// Edits the details of a house. Only the given fields change. Requires the
+/// owner or admin role.
+#[utoipa::path(
+    patch,
+    path = "/houses/{house_id}",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    request_body = UpdateHouse,
+    responses(
+        (status = 200, description = "House updated successfully", body = House),
+        (status = 400, description = "Bad Request - Invalid input or address already taken", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow editing the house", body = String),
+        (status = 404, description = "House not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "houses"
+)]
+pub async fn update_house(
+    State(state): State<HousesRouterState>,
+    HouseAccess { house_id, user_id }: HouseAccess,
+    ValidatedJson(payload): ValidatedJson<UpdateHouse>,
+) -> Result<Json<House>> {
+    state
+        .access_control_service
+        .can(user_id, house_id, Action::UpdateHouse)
+        .await?;
+
+    let house = state.house_service.update_house(house_id, payload).await?;
+
+    Ok(Json(house))
+}
+
 /// Delete house endpoint
 ///
-/// Deletes a house by its ID.
+/// Deletes a house by its ID. Only owners can delete a house.
 #[utoipa::path(
     delete,
     path = "/houses/{id}",
@@ -108,6 +162,7 @@ pub async fn create_house(
     responses(
         (status = 204, description = "House deleted successfully", body = ()),
         (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Only owners can delete the house", body = String),
         (status = 404, description = "House not found", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
@@ -118,8 +173,13 @@ pub async fn create_house(
 )]
 pub async fn delete_house(
     State(state): State<HousesRouterState>,
-    HouseAccess { house_id, .. }: HouseAccess,
+    HouseAccess { house_id, user_id }: HouseAccess,
 ) -> Result<StatusCode> {
+    state
+        .access_control_service
+        .can(user_id, house_id, Action::DeleteHouse)
+        .await?;
+
     state.house_service.delete_house(house_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
@@ -132,7 +192,10 @@ mod tests {
     use super::*;
     use crate::{
         errors::AppError,
-        models::user_houses::UserHouse,
+        models::{
+            houses::UnitSystem,
+            user_houses::{HouseMembership, HouseRole, MemberAccess, UserHouse},
+        },
         repositories::{
             house_repository::MockHouseRepositoryTrait,
             user_houses_repository::MockUserHousesRepositoryTrait,
@@ -156,6 +219,11 @@ mod tests {
             updated_at: Utc::now(),
             description: "".to_string(),
             r#type: "apartment".to_string(),
+            timezone: "UTC".to_string(),
+            latitude: None,
+            longitude: None,
+            unit_system: UnitSystem::Metric,
+            currency: "USD".to_string(),
         }];
         let user_houses = houses.clone();
 
@@ -174,7 +242,7 @@ mod tests {
             access_control_service,
         };
 
-        let result = get_user_houses(State(state), Extension(1)).await;
+        let result = get_user_houses(State(state), Extension(1), None).await;
 
         assert!(result.is_ok());
         let Json(result_houses) = result.unwrap();
@@ -195,6 +263,11 @@ mod tests {
             updated_at: Utc::now(),
             description: "".to_string(),
             r#type: "apartment".to_string(),
+            timezone: "UTC".to_string(),
+            latitude: None,
+            longitude: None,
+            unit_system: UnitSystem::Metric,
+            currency: "USD".to_string(),
         };
 
         let cloned_house = house.clone();
@@ -214,7 +287,14 @@ mod tests {
             access_control_service,
         };
 
-        let result = get_user_house_by_id(State(state), Path(1)).await;
+        let result = get_user_house_by_id(
+            State(state),
+            HouseAccess {
+                house_id: 1,
+                user_id: 1,
+            },
+        )
+        .await;
 
         assert!(result.is_ok());
         let Json(result_house) = result.unwrap();
@@ -237,6 +317,11 @@ mod tests {
             address: address.clone(),
             description: description.clone(),
             r#type: r#type.clone(),
+            timezone: "UTC".to_string(),
+            latitude: None,
+            longitude: None,
+            unit_system: UnitSystem::Metric,
+            currency: "USD".to_string(),
         };
 
         let created_house = House {
@@ -245,13 +330,19 @@ mod tests {
             address: address.clone(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
-            description: description,
-            r#type: r#type,
+            description,
+            r#type,
+            timezone: "UTC".to_string(),
+            latitude: None,
+            longitude: None,
+            unit_system: UnitSystem::Metric,
+            currency: "USD".to_string(),
         };
 
         let user_house = UserHouse {
             user_id: 1,
             house_id: created_house.id,
+            role: HouseRole::Owner,
         };
 
         let cloned_created_house = created_house.clone();
@@ -264,9 +355,13 @@ mod tests {
 
         mock_user_house_repo
             .expect_add_house_to_user()
-            .with(eq(user_house.user_id), eq(user_house.house_id))
+            .with(
+                eq(user_house.user_id),
+                eq(user_house.house_id),
+                eq(HouseRole::Owner),
+            )
             .times(1)
-            .returning(move |_, _| Ok(user_house.clone()));
+            .returning(move |_, _, _| Ok(user_house.clone()));
 
         mock_house_repo
             .expect_create_house()
@@ -283,7 +378,7 @@ mod tests {
             access_control_service,
         };
 
-        let result = create_house(State(state), Extension(1), ValidatedJson(new_house)).await;
+        let result = create_house(State(state), Extension(1), None, ValidatedJson(new_house)).await;
 
         assert!(result.is_ok());
         let (status, Json(result_house)) = result.unwrap();
@@ -292,6 +387,22 @@ mod tests {
         assert_eq!(result_house.name, cloned_created_house.name);
     }
 
+    fn access_control_with_role(role: HouseRole) -> AccessControlService {
+        let mut mock_user_house_repo = MockUserHousesRepositoryTrait::new();
+        mock_user_house_repo
+            .expect_get_membership()
+            .with(eq(1), eq(1))
+            .returning(move |_, _| {
+                Ok(Some(HouseMembership {
+                    role,
+                    access: MemberAccess::default(),
+                    timezone: chrono_tz::Tz::UTC,
+                }))
+            });
+
+        AccessControlService::new(Arc::new(mock_user_house_repo))
+    }
+
     #[tokio::test]
     async fn test_delete_house_success() {
         let mut mock_house_repo = MockHouseRepositoryTrait::new();
@@ -305,8 +416,7 @@ mod tests {
 
         let house_service =
             HouseService::new(Arc::new(mock_house_repo), Arc::new(mock_user_house_repo));
-        let access_control_service =
-            AccessControlService::new(Arc::new(MockUserHousesRepositoryTrait::new()));
+        let access_control_service = access_control_with_role(HouseRole::Owner);
         let state = HousesRouterState {
             house_service,
             access_control_service,
@@ -337,8 +447,7 @@ mod tests {
 
         let house_service =
             HouseService::new(Arc::new(mock_house_repo), Arc::new(mock_user_house_repo));
-        let access_control_service =
-            AccessControlService::new(Arc::new(MockUserHousesRepositoryTrait::new()));
+        let access_control_service = access_control_with_role(HouseRole::Owner);
         let state = HousesRouterState {
             house_service,
             access_control_service,
@@ -359,4 +468,168 @@ mod tests {
             AppError::NotFound("House not found".to_string()).to_string()
         );
     }
+
+    #[tokio::test]
+    async fn test_delete_house_requires_owner() {
+        let mut mock_house_repo = MockHouseRepositoryTrait::new();
+        mock_house_repo.expect_delete_house().never();
+
+        let house_service = HouseService::new(
+            Arc::new(mock_house_repo),
+            Arc::new(MockUserHousesRepositoryTrait::new()),
+        );
+        let state = HousesRouterState {
+            house_service,
+            access_control_service: access_control_with_role(HouseRole::Admin),
+        };
+
+        let result = delete_house(
+            State(state),
+            HouseAccess {
+                house_id: 1,
+                user_id: 1,
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::AuthorizationError(_))));
+    }
+
+    fn house_update(address: Option<&str>) -> UpdateHouse {
+        UpdateHouse {
+            name: Some("Renamed House".to_string()),
+            address: address.map(str::to_string),
+            r#type: None,
+            description: None,
+            timezone: None,
+            latitude: None,
+            longitude: None,
+            unit_system: None,
+            currency: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_house_success() {
+        let mut mock_house_repo = MockHouseRepositoryTrait::new();
+        let update = house_update(Some("456 Elm St"));
+
+        mock_house_repo
+            .expect_find_house_by_address()
+            .with(eq("456 Elm St".to_string()))
+            .times(1)
+            .returning(|_| Ok(None));
+        mock_house_repo
+            .expect_update_house()
+            .with(eq(1), eq(update.clone()))
+            .times(1)
+            .returning(|id, update| {
+                Ok(House {
+                    id,
+                    name: update.name.unwrap(),
+                    address: update.address.unwrap(),
+                    r#type: "house".to_string(),
+                    description: String::new(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    timezone: "UTC".to_string(),
+                    latitude: None,
+                    longitude: None,
+                    unit_system: UnitSystem::Metric,
+                    currency: "USD".to_string(),
+                })
+            });
+
+        let state = HousesRouterState {
+            house_service: HouseService::new(
+                Arc::new(mock_house_repo),
+                Arc::new(MockUserHousesRepositoryTrait::new()),
+            ),
+            access_control_service: access_control_with_role(HouseRole::Admin),
+        };
+
+        let Json(house) = update_house(
+            State(state),
+            HouseAccess {
+                house_id: 1,
+                user_id: 1,
+            },
+            ValidatedJson(update),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(house.name, "Renamed House");
+        assert_eq!(house.address, "456 Elm St");
+    }
+
+    #[tokio::test]
+    async fn test_update_house_keeps_addresses_unique() {
+        let mut mock_house_repo = MockHouseRepositoryTrait::new();
+        mock_house_repo
+            .expect_find_house_by_address()
+            .returning(|address| {
+                Ok(Some(House {
+                    id: 2,
+                    name: "Other House".to_string(),
+                    address,
+                    r#type: "house".to_string(),
+                    description: String::new(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    timezone: "UTC".to_string(),
+                    latitude: None,
+                    longitude: None,
+                    unit_system: UnitSystem::Metric,
+                    currency: "USD".to_string(),
+                }))
+            });
+        mock_house_repo.expect_update_house().never();
+
+        let state = HousesRouterState {
+            house_service: HouseService::new(
+                Arc::new(mock_house_repo),
+                Arc::new(MockUserHousesRepositoryTrait::new()),
+            ),
+            access_control_service: access_control_with_role(HouseRole::Owner),
+        };
+
+        let result = update_house(
+            State(state),
+            HouseAccess {
+                house_id: 1,
+                user_id: 1,
+            },
+            ValidatedJson(house_update(Some("456 Elm St"))),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_house_requires_admin() {
+        let mut mock_house_repo = MockHouseRepositoryTrait::new();
+        mock_house_repo.expect_update_house().never();
+
+        let state = HousesRouterState {
+            house_service: HouseService::new(
+                Arc::new(mock_house_repo),
+                Arc::new(MockUserHousesRepositoryTrait::new()),
+            ),
+            access_control_service: access_control_with_role(HouseRole::Member),
+        };
+
+        let result = update_house(
+            State(state),
+            HouseAccess {
+                house_id: 1,
+                user_id: 1,
+            },
+            ValidatedJson(house_update(None)),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::AuthorizationError(_))));
+    }
 }
@@ -5,17 +5,22 @@ use axum::{
 };
 
 use crate::{
-    errors::{AppError, Result},
+    errors::{AppError, Result, ValidationErrorResponse},
     middlewares::validator::ValidatedJson,
     models::common::ListResponse,
-    models::rooms::{NewRoom, Room},
+    models::rooms::{NewRoom, Room, UpdateRoom},
     routes::rooms::{HouseAccess, RoomsRouterState},
-    services::{house::HouseServiceTrait, rooms::RoomsServiceTrait},
+    services::{
+        access_control_service::{AccessControlServiceTrait, Action},
+        house::HouseServiceTrait,
+        rooms::RoomsServiceTrait,
+    },
 };
 
 /// Get house rooms endpoint
 ///
-/// Retrieves a list of rooms associated with a specific house.
+/// Retrieves a list of rooms associated with a specific house. Members limited to
+/// some rooms only see those rooms.
 #[utoipa::path(
     get,
     path = "/houses/{id}/rooms",
@@ -31,30 +36,35 @@ use crate::{
 )]
 pub async fn get_house_rooms<R, H, A>(
     State(state): State<RoomsRouterState<R, H, A>>,
-    HouseAccess {
-        house_id,
-        user_id: _,
-    }: HouseAccess,
+    HouseAccess { house_id, user_id }: HouseAccess,
 ) -> Result<Json<ListResponse<Room>>>
 where
     R: RoomsServiceTrait,
     H: HouseServiceTrait,
-    A: crate::services::access_control_service::AccessControlServiceTrait,
+    A: AccessControlServiceTrait,
 {
-    let rooms = state.room_service.get_house_rooms(house_id).await?;
+    let mut rooms = state.room_service.get_house_rooms(house_id).await?;
+    if let Some(room_ids) = state
+        .access_control_service
+        .visible_rooms(user_id, house_id)
+        .await?
+    {
+        rooms.retain(|room| room_ids.contains(&room.id));
+    }
 
     Ok(Json(ListResponse { items: rooms }))
 }
 
 /// Create new room for house
 ///
-/// Creates a new room for a specific house.
+/// Creates a new room for a specific house. Requires the owner or admin role.
 #[utoipa::path(
     get,
     path = "/houses/{id}/rooms",
     responses(
         (status = 201, description = "Room created", body = Room),
         (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing rooms", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
     security(
@@ -70,8 +80,17 @@ pub async fn create_room<R, H, A>(
 where
     R: RoomsServiceTrait,
     H: HouseServiceTrait,
-    A: crate::services::access_control_service::AccessControlServiceTrait,
+    A: AccessControlServiceTrait,
 {
+    state
+        .access_control_service
+        .can(
+            house_access.user_id,
+            house_access.house_id,
+            Action::ManageRooms,
+        )
+        .await?;
+
     let rooms = state
         .room_service
         .create_house_room(house_access.house_id, payload)
@@ -80,14 +99,67 @@ where
     Ok((StatusCode::CREATED, Json(rooms)))
 }
 
+/// Update room endpoint
+///
+/// Edits a room of a specific house. Only the given fields change. Requires the
+/// owner or admin role.
+#[utoipa::path(
+    patch,
+    path = "/houses/{house_id}/rooms/{room_id}",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("room_id" = i64, Path, description = "Room ID")
+    ),
+    request_body = UpdateRoom,
+    responses(
+        (status = 200, description = "Room updated", body = Room),
+        (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing rooms", body = String),
+        (status = 404, description = "Room not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "rooms"
+)]
+pub async fn update_room<R, H, A>(
+    State(state): State<RoomsRouterState<R, H, A>>,
+    HouseAccess { house_id, user_id }: HouseAccess,
+    Path((_, room_id)): Path<(i64, i64)>,
+    ValidatedJson(payload): ValidatedJson<UpdateRoom>,
+) -> Result<Json<Room>>
+where
+    R: RoomsServiceTrait,
+    H: HouseServiceTrait,
+    A: AccessControlServiceTrait,
+{
+    state
+        .access_control_service
+        .can(user_id, house_id, Action::ManageRooms)
+        .await?;
+
+    let room = state.room_service.get_room(room_id).await?;
+
+    if room.house_id != house_id {
+        return Err(AppError::AuthenticationError("Access denied".to_string()));
+    }
+
+    let room = state.room_service.update_room(room_id, payload).await?;
+
+    Ok(Json(room))
+}
+
 /// Delete room from house
 ///
-/// Deletes a room from a specific house.
+/// Deletes a room from a specific house. Requires the owner or admin role.
 #[utoipa::path(
     get,
     path = "/houses/{id}/rooms/{id}",
     responses(
         (status = 204, description = "Room deleted", body = ()),
+        (status = 403, description = "Forbidden - Role does not allow managing rooms", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
     security(
@@ -97,17 +169,19 @@ where
 )]
 pub async fn delete_room<R, H, A>(
     State(state): State<RoomsRouterState<R, H, A>>,
-    HouseAccess {
-        house_id,
-        user_id: _,
-    }: HouseAccess,
-    Path(room_id): Path<i64>,
+    HouseAccess { house_id, user_id }: HouseAccess,
+    Path((_, room_id)): Path<(i64, i64)>,
 ) -> Result<StatusCode>
 where
     R: RoomsServiceTrait,
     H: HouseServiceTrait,
-    A: crate::services::access_control_service::AccessControlServiceTrait,
+    A: AccessControlServiceTrait,
 {
+    state
+        .access_control_service
+        .can(user_id, house_id, Action::ManageRooms)
+        .await?;
+
     let room = state.room_service.get_room(room_id).await?;
 
     if room.house_id != house_id {
@@ -135,11 +209,24 @@ mod tests {
     use chrono::Utc;
     use mockall::predicate::*;
 
+    fn access_control_allowing() -> MockAccessControlServiceTrait {
+        let mut mock_access_control_service = MockAccessControlServiceTrait::new();
+        mock_access_control_service
+            .expect_can()
+            .with(eq(1i64), eq(1i64), eq(Action::ManageRooms))
+            .returning(|_, _, _| Ok(()));
+        mock_access_control_service
+    }
+
     #[tokio::test]
     async fn test_get_house_rooms_success() {
         let mut mock_room_service = MockRoomsServiceTrait::new();
         let mock_house_service = MockHouseServiceTrait::new();
-        let mock_access_control_service = MockAccessControlServiceTrait::new();
+        let mut mock_access_control_service = MockAccessControlServiceTrait::new();
+        mock_access_control_service
+            .expect_visible_rooms()
+            .with(eq(1i64), eq(1i64))
+            .returning(|_, _| Ok(None));
 
         let now = Utc::now();
         let room = Room {
@@ -147,6 +234,7 @@ mod tests {
             house_id: 1,
             name: "Living Room".to_string(),
             room_type: "living_room".to_string(),
+            zone_id: None,
             created_at: now,
             updated_at: now,
         };
@@ -177,16 +265,61 @@ mod tests {
         assert_eq!(rooms.items[0].name, "Living Room");
     }
 
+    #[tokio::test]
+    async fn test_get_house_rooms_limited_to_rooms() {
+        let mut mock_room_service = MockRoomsServiceTrait::new();
+        let mut mock_access_control_service = MockAccessControlServiceTrait::new();
+        mock_access_control_service
+            .expect_visible_rooms()
+            .with(eq(1i64), eq(1i64))
+            .returning(|_, _| Ok(Some(vec![2])));
+
+        let now = Utc::now();
+        mock_room_service
+            .expect_get_house_rooms()
+            .with(eq(1i64))
+            .returning(move |_| {
+                Ok([(1, "Living Room"), (2, "Bedroom")]
+                    .into_iter()
+                    .map(|(id, name)| Room {
+                        id,
+                        house_id: 1,
+                        name: name.to_string(),
+                        room_type: "bedroom".to_string(),
+                        zone_id: None,
+                        created_at: now,
+                        updated_at: now,
+                    })
+                    .collect())
+            });
+
+        let state = RoomsRouterState {
+            room_service: mock_room_service,
+            house_service: MockHouseServiceTrait::new(),
+            access_control_service: mock_access_control_service,
+        };
+
+        let house_access = HouseAccess {
+            house_id: 1,
+            user_id: 1,
+        };
+
+        let Json(rooms) = get_house_rooms(State(state), house_access).await.unwrap();
+        assert_eq!(rooms.items.len(), 1);
+        assert_eq!(rooms.items[0].name, "Bedroom");
+    }
+
     #[tokio::test]
     async fn test_create_room_success() {
         let mut mock_room_service = MockRoomsServiceTrait::new();
         let mock_house_service = MockHouseServiceTrait::new();
-        let mock_access_control_service = MockAccessControlServiceTrait::new();
+        let mock_access_control_service = access_control_allowing();
 
         let now = Utc::now();
         let new_room = NewRoom {
             name: "Bedroom".to_string(),
             room_type: "bedroom".to_string(),
+            zone_id: None,
         };
 
         let room = Room {
@@ -194,6 +327,7 @@ mod tests {
             house_id: 1,
             name: "Bedroom".to_string(),
             room_type: "bedroom".to_string(),
+            zone_id: None,
             created_at: now,
             updated_at: now,
         };
@@ -227,7 +361,7 @@ mod tests {
     async fn test_delete_room_success() {
         let mut mock_room_service = MockRoomsServiceTrait::new();
         let mock_house_service = MockHouseServiceTrait::new();
-        let mock_access_control_service = MockAccessControlServiceTrait::new();
+        let mock_access_control_service = access_control_allowing();
         let now = Utc::now();
 
         let room = Room {
@@ -235,6 +369,7 @@ mod tests {
             house_id: 1,
             name: "Living Room".to_string(),
             room_type: "living_room".to_string(),
+            zone_id: None,
             created_at: now,
             updated_at: now,
         };
@@ -262,7 +397,7 @@ mod tests {
             user_id: 1,
         };
 
-        let result = delete_room(State(state), house_access, Path(1)).await;
+        let result = delete_room(State(state), house_access, Path((1, 1))).await;
 
         assert!(result.is_ok());
     }
@@ -271,7 +406,7 @@ mod tests {
     async fn test_delete_room_not_found() {
         let mut mock_room_service = MockRoomsServiceTrait::new();
         let mock_house_service = MockHouseServiceTrait::new();
-        let mock_access_control_service = MockAccessControlServiceTrait::new();
+        let mock_access_control_service = access_control_allowing();
 
         mock_room_service
             .expect_get_room()
@@ -290,7 +425,7 @@ mod tests {
             user_id: 1,
         };
 
-        let result = delete_room(State(state), house_access, Path(999)).await;
+        let result = delete_room(State(state), house_access, Path((1, 999))).await;
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -303,7 +438,7 @@ mod tests {
     async fn test_delete_room_access_denied() {
         let mut mock_room_service = MockRoomsServiceTrait::new();
         let mock_house_service = MockHouseServiceTrait::new();
-        let mock_access_control_service = MockAccessControlServiceTrait::new();
+        let mock_access_control_service = access_control_allowing();
         let now = Utc::now();
 
         let room = Room {
@@ -311,6 +446,7 @@ mod tests {
             house_id: 2, // This room belongs to another house
             name: "Living Room".to_string(),
             room_type: "living_room".to_string(),
+            zone_id: None,
             created_at: now,
             updated_at: now,
         };
@@ -332,7 +468,7 @@ mod tests {
             user_id: 1,
         };
 
-        let result = delete_room(State(state), house_access, Path(1)).await;
+        let result = delete_room(State(state), house_access, Path((1, 1))).await;
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -340,4 +476,146 @@ mod tests {
             _ => panic!("Expected AuthenticationError"),
         }
     }
+
+    #[tokio::test]
+    async fn test_create_room_requires_manage_rooms() {
+        let mut mock_room_service = MockRoomsServiceTrait::new();
+        let mut mock_access_control_service = MockAccessControlServiceTrait::new();
+
+        mock_access_control_service
+            .expect_can()
+            .with(eq(1i64), eq(1i64), eq(Action::ManageRooms))
+            .times(1)
+            .returning(|_, _, _| {
+                Err(AppError::AuthorizationError(
+                    "The member role does not allow this action".to_string(),
+                ))
+            });
+        mock_room_service.expect_create_house_room().never();
+
+        let state = RoomsRouterState {
+            room_service: mock_room_service,
+            house_service: MockHouseServiceTrait::new(),
+            access_control_service: mock_access_control_service,
+        };
+
+        let house_access = HouseAccess {
+            house_id: 1,
+            user_id: 1,
+        };
+        let new_room = NewRoom {
+            name: "Bedroom".to_string(),
+            room_type: "bedroom".to_string(),
+            zone_id: None,
+        };
+
+        let result = create_room(State(state), house_access, ValidatedJson(new_room)).await;
+
+        assert!(matches!(result, Err(AppError::AuthorizationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_room_success() {
+        let mut mock_room_service = MockRoomsServiceTrait::new();
+        let now = Utc::now();
+        let room = Room {
+            id: 1,
+            house_id: 1,
+            name: "Living Room".to_string(),
+            room_type: "living_room".to_string(),
+            zone_id: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let update = UpdateRoom {
+            name: Some("Lounge".to_string()),
+            room_type: None,
+            zone_id: None,
+        };
+
+        let existing_room = room.clone();
+        mock_room_service
+            .expect_get_room()
+            .with(eq(1i64))
+            .times(1)
+            .returning(move |_| Ok(existing_room.clone()));
+        mock_room_service
+            .expect_update_room()
+            .with(eq(1i64), eq(update.clone()))
+            .times(1)
+            .returning(move |_, update| {
+                Ok(Room {
+                    name: update.name.unwrap(),
+                    ..room.clone()
+                })
+            });
+
+        let state = RoomsRouterState {
+            room_service: mock_room_service,
+            house_service: MockHouseServiceTrait::new(),
+            access_control_service: access_control_allowing(),
+        };
+
+        let house_access = HouseAccess {
+            house_id: 1,
+            user_id: 1,
+        };
+
+        let Json(room) = update_room(
+            State(state),
+            house_access,
+            Path((1, 1)),
+            ValidatedJson(update),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(room.name, "Lounge");
+        assert_eq!(room.room_type, "living_room");
+    }
+
+    #[tokio::test]
+    async fn test_update_room_of_another_house() {
+        let mut mock_room_service = MockRoomsServiceTrait::new();
+        let now = Utc::now();
+
+        mock_room_service.expect_get_room().returning(move |_| {
+            Ok(Room {
+                id: 1,
+                house_id: 2,
+                name: "Living Room".to_string(),
+                room_type: "living_room".to_string(),
+                zone_id: None,
+                created_at: now,
+                updated_at: now,
+            })
+        });
+        mock_room_service.expect_update_room().never();
+
+        let state = RoomsRouterState {
+            room_service: mock_room_service,
+            house_service: MockHouseServiceTrait::new(),
+            access_control_service: access_control_allowing(),
+        };
+
+        let house_access = HouseAccess {
+            house_id: 1,
+            user_id: 1,
+        };
+        let update = UpdateRoom {
+            name: Some("Lounge".to_string()),
+            room_type: None,
+            zone_id: None,
+        };
+
+        let result = update_room(
+            State(state),
+            house_access,
+            Path((1, 1)),
+            ValidatedJson(update),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::AuthenticationError(_))));
+    }
 }
@@ -1,8 +1,25 @@
-use axum::{extract::State, Extension, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
 
 use crate::{
-    errors::Result, models::users::User, routes::users::UserRouterState,
-    services::user_service::UserServiceTrait,
+    errors::{Result, ValidationErrorResponse},
+    middlewares::validator::ValidatedJson,
+    models::{
+        auth_events::{AuthEvent, SecurityEventsQuery},
+        common::ListResponse,
+        sessions::{ClientInfo, PublicSession, SessionId},
+        two_factor::{RecoveryCodes, TotpCodeRequest, TotpEnrollment},
+        users::{ChangePasswordRequest, DeleteAccountRequest, UpdateProfileRequest, User},
+    },
+    routes::users::UserRouterState,
+    services::{
+        account::AccountServiceTrait, auth_events::AuthEventsServiceTrait,
+        sessions::SessionsServiceTrait, two_factor::TwoFactorServiceTrait,
+        user_service::UserServiceTrait,
+    },
 };
 
 /// Get user profile by ID endpoint
@@ -31,6 +48,335 @@ pub async fn get_user_profile(
     Ok(Json(profile))
 }
 
+/// Update user profile endpoint
+///
+/// Changes the name and phone number of the current user. Omitted fields keep
+/// their current value.
+#[utoipa::path(
+    patch,
+    path = "/profile",
+    request_body = UpdateProfileRequest,
+    responses(
+        (status = 200, description = "Profile updated", body = User),
+        (status = 400, description = "Bad Request - Invalid input or phone already in use", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn update_user_profile(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    ValidatedJson(payload): ValidatedJson<UpdateProfileRequest>,
+) -> Result<Json<User>> {
+    let profile = state.user_service.update_profile(user_id, payload).await?;
+
+    Ok(Json(profile))
+}
+
+/// Change password endpoint
+///
+/// Sets a new password for the current user. Requires the current password and
+/// signs out all other sessions.
+#[utoipa::path(
+    post,
+    path = "/profile/password",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 204, description = "Password changed"),
+        (status = 400, description = "Bad Request - Invalid input or wrong current password", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn change_password(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    session_id: Option<Extension<SessionId>>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<ChangePasswordRequest>,
+) -> Result<StatusCode> {
+    let current_session_id = session_id.map(|Extension(SessionId(id))| id);
+    state
+        .account_service
+        .change_password(
+            user_id,
+            &payload.current_password,
+            &payload.new_password,
+            current_session_id,
+            client,
+        )
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete account endpoint
+///
+/// Deletes the current user after confirming the password. Houses no other user
+/// has access to are deleted with the account; shared houses stay with their
+/// remaining members.
+#[utoipa::path(
+    delete,
+    path = "/profile",
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 400, description = "Bad Request - Invalid input or wrong password", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn delete_account(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    ValidatedJson(payload): ValidatedJson<DeleteAccountRequest>,
+) -> Result<StatusCode> {
+    state
+        .account_service
+        .delete_account(user_id, &payload.password)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List active sessions endpoint
+///
+/// Lists the current user's active login sessions with the client they were used from.
+#[utoipa::path(
+    get,
+    path = "/profile/sessions",
+    responses(
+        (status = 200, description = "Active sessions", body = ListResponse<PublicSession>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot manage sessions", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn get_sessions(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    session_id: Option<Extension<SessionId>>,
+) -> Result<Json<ListResponse<PublicSession>>> {
+    let current_session_id = session_id.map(|Extension(SessionId(id))| id);
+    let sessions = state
+        .sessions_service
+        .get_sessions(user_id, current_session_id)
+        .await?;
+
+    Ok(Json(ListResponse { items: sessions }))
+}
+
+/// Revoke session endpoint
+///
+/// Revokes one of the current user's sessions. Its refresh token and access tokens
+/// stop working immediately.
+#[utoipa::path(
+    delete,
+    path = "/profile/sessions/{id}",
+    params(
+        ("id" = i64, Path, description = "Session ID")
+    ),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot manage sessions", body = String),
+        (status = 404, description = "Session not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn revoke_session(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(session_id): Path<i64>,
+    client: ClientInfo,
+) -> Result<StatusCode> {
+    state
+        .sessions_service
+        .revoke_session(user_id, session_id, client)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List security events endpoint
+///
+/// Lists the authentication events of the current user, newest first: logins and
+/// failed login attempts, token creation, use and revocation, and password changes.
+#[utoipa::path(
+    get,
+    path = "/profile/security-events",
+    params(SecurityEventsQuery),
+    responses(
+        (status = 200, description = "Security events", body = ListResponse<AuthEvent>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn get_security_events(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    Query(query): Query<SecurityEventsQuery>,
+) -> Result<Json<ListResponse<AuthEvent>>> {
+    let events = state
+        .auth_events_service
+        .list_for_user(user_id, query)
+        .await?;
+
+    Ok(Json(ListResponse { items: events }))
+}
+
+/// Resend email verification endpoint
+///
+/// Sends a new verification link to the current user's email address. Links sent
+/// earlier stop working.
+#[utoipa::path(
+    post,
+    path = "/profile/email/verification",
+    responses(
+        (status = 202, description = "Verification email sent"),
+        (status = 400, description = "Email address is already verified", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn resend_email_verification(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+) -> Result<StatusCode> {
+    state
+        .account_service
+        .resend_email_verification(user_id)
+        .await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Enroll TOTP endpoint
+///
+/// Generates a new TOTP secret for the current user. Two-factor authentication is
+/// enabled once the secret is confirmed with a code from the authenticator app.
+#[utoipa::path(
+    post,
+    path = "/profile/2fa/totp",
+    responses(
+        (status = 200, description = "Secret generated", body = TotpEnrollment),
+        (status = 400, description = "Two-factor authentication is already enabled", body = String),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn enroll_totp(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+) -> Result<Json<TotpEnrollment>> {
+    let enrollment = state.two_factor_service.enroll(user_id).await?;
+
+    Ok(Json(enrollment))
+}
+
+/// Confirm TOTP endpoint
+///
+/// Enables two-factor authentication with a code for the enrolled secret and
+/// returns single-use recovery codes.
+#[utoipa::path(
+    post,
+    path = "/profile/2fa/totp/confirm",
+    request_body = TotpCodeRequest,
+    responses(
+        (status = 200, description = "Two-factor authentication enabled", body = RecoveryCodes),
+        (status = 400, description = "Bad Request - Invalid code or no secret enrolled", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn confirm_totp(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    ValidatedJson(payload): ValidatedJson<TotpCodeRequest>,
+) -> Result<Json<RecoveryCodes>> {
+    let recovery_codes = state
+        .two_factor_service
+        .confirm(user_id, payload.code.trim())
+        .await?;
+
+    Ok(Json(recovery_codes))
+}
+
+/// Disable TOTP endpoint
+///
+/// Turns off two-factor authentication. Requires a current code or a recovery code.
+#[utoipa::path(
+    post,
+    path = "/profile/2fa/totp/disable",
+    request_body = TotpCodeRequest,
+    responses(
+        (status = 204, description = "Two-factor authentication disabled"),
+        (status = 400, description = "Bad Request - Invalid code or not enabled", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - API tokens cannot be used for this operation", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn disable_totp(
+    State(state): State<UserRouterState>,
+    Extension(user_id): Extension<i64>,
+    ValidatedJson(payload): ValidatedJson<TotpCodeRequest>,
+) -> Result<StatusCode> {
+    state
+        .two_factor_service
+        .disable(user_id, payload.code.trim())
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -38,9 +384,18 @@ mod tests {
     use super::*;
     use crate::{
         models::users::{User, UserRole},
-        repositories::user_repository::MockUserRepositoryTrait,
+        repositories::{
+            account_tokens_repository::MockAccountTokensRepositoryTrait,
+            auth_events_repository::MockAuthEventsRepositoryTrait,
+            sessions_repository::MockSessionsRepositoryTrait,
+            two_factor_repository::MockTwoFactorRepositoryTrait,
+            user_repository::MockUserRepositoryTrait,
+        },
         routes::users::UserRouterState,
-        services::user_service::UserService,
+        services::{
+            account::AccountService, auth_events::AuthEventsService, mailer::MockMailer,
+            sessions::SessionsService, two_factor::TwoFactorService, user_service::UserService,
+        },
     };
     use axum::extract::State;
     use chrono::Utc;
@@ -60,6 +415,8 @@ mod tests {
             created_at: now,
             updated_at: now,
             last_login_at: Some(now),
+            email_verified_at: None,
+            disabled_at: None,
         };
 
         mock_repo
@@ -69,7 +426,31 @@ mod tests {
             .returning(move |_| Ok(user_entity.clone()));
 
         let user_service = UserService::new(Arc::new(mock_repo));
-        let state = UserRouterState { user_service };
+        let auth_events_service =
+            AuthEventsService::new(Arc::new(MockAuthEventsRepositoryTrait::new()));
+        let sessions_service = SessionsService::new(
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(auth_events_service.clone()),
+        );
+        let account_service = AccountService::new(
+            crate::tests::create_test_config(),
+            Arc::new(MockUserRepositoryTrait::new()),
+            Arc::new(MockAccountTokensRepositoryTrait::new()),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(MockMailer::new()),
+            Arc::new(auth_events_service.clone()),
+        );
+        let two_factor_service = TwoFactorService::new(
+            Arc::new(MockUserRepositoryTrait::new()),
+            Arc::new(MockTwoFactorRepositoryTrait::new()),
+        );
+        let state = UserRouterState {
+            user_service,
+            sessions_service,
+            account_service,
+            two_factor_service,
+            auth_events_service,
+        };
 
         let result = get_user_profile(State(state), Extension(1)).await;
 
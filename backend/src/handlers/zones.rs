@@ -0,0 +1,159 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::{
+    errors::{Result, ValidationErrorResponse},
+    middlewares::validator::ValidatedJson,
+    models::{
+        common::ListResponse,
+        zones::{NewZone, UpdateZone, Zone},
+    },
+    routes::zones::ZonesRouterState,
+};
+
+/// Get house zones endpoint
+///
+/// Lists the zones of a house, e.g. its floors.
+#[utoipa::path(
+    get,
+    path = "/houses/{house_id}/zones",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    responses(
+        (status = 200, description = "Zones found", body = ListResponse<Zone>),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "zones"
+)]
+pub async fn get_house_zones(
+    State(state): State<ZonesRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+) -> Result<Json<ListResponse<Zone>>> {
+    let zones = state
+        .zones_service
+        .get_house_zones(user_id, house_id)
+        .await?;
+
+    Ok(Json(ListResponse { items: zones }))
+}
+
+/// Create zone endpoint
+///
+/// Creates a zone in a house. Rooms are put in it with their `zone_id`. Requires
+/// the owner or admin role.
+#[utoipa::path(
+    post,
+    path = "/houses/{house_id}/zones",
+    params(
+        ("house_id" = i64, Path, description = "House ID")
+    ),
+    request_body = NewZone,
+    responses(
+        (status = 201, description = "Zone created", body = Zone),
+        (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing rooms", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "zones"
+)]
+pub async fn create_zone(
+    State(state): State<ZonesRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path(house_id): Path<i64>,
+    ValidatedJson(payload): ValidatedJson<NewZone>,
+) -> Result<(StatusCode, Json<Zone>)> {
+    let zone = state
+        .zones_service
+        .create_zone(user_id, house_id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(zone)))
+}
+
+/// Update zone endpoint
+///
+/// Edits a zone of a house. Only the given fields change. Requires the owner or
+/// admin role.
+#[utoipa::path(
+    patch,
+    path = "/houses/{house_id}/zones/{zone_id}",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("zone_id" = i64, Path, description = "Zone ID")
+    ),
+    request_body = UpdateZone,
+    responses(
+        (status = 200, description = "Zone updated", body = Zone),
+        (status = 400, description = "Bad Request - Invalid input", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing rooms", body = String),
+        (status = 404, description = "Zone not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "zones"
+)]
+pub async fn update_zone(
+    State(state): State<ZonesRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, zone_id)): Path<(i64, i64)>,
+    ValidatedJson(payload): ValidatedJson<UpdateZone>,
+) -> Result<Json<Zone>> {
+    let zone = state
+        .zones_service
+        .update_zone(user_id, house_id, zone_id, payload)
+        .await?;
+
+    Ok(Json(zone))
+}
+
+/// Delete zone endpoint
+///
+/// Deletes a zone of a house. Its rooms and their devices are kept, without a
+/// zone. Requires the owner or admin role.
+#[utoipa::path(
+    delete,
+    path = "/houses/{house_id}/zones/{zone_id}",
+    params(
+        ("house_id" = i64, Path, description = "House ID"),
+        ("zone_id" = i64, Path, description = "Zone ID")
+    ),
+    responses(
+        (status = 204, description = "Zone deleted"),
+        (status = 401, description = "Unauthorized", body = String),
+        (status = 403, description = "Forbidden - Role does not allow managing rooms", body = String),
+        (status = 404, description = "Zone not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "zones"
+)]
+pub async fn delete_zone(
+    State(state): State<ZonesRouterState>,
+    Extension(user_id): Extension<i64>,
+    Path((house_id, zone_id)): Path<(i64, i64)>,
+) -> Result<StatusCode> {
+    state
+        .zones_service
+        .delete_zone(user_id, house_id, zone_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
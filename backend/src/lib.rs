@@ -3,7 +3,9 @@
 //! This library provides the core functionality for the Smart Home management system,
 //! including user authentication, device management, and API endpoints.
 
-use axum::{http::HeaderValue, middleware, Router};
+use std::{sync::Arc, time::Duration};
+
+use axum::{http::HeaderValue, middleware, Extension, Router};
 use sqlx::postgres::PgPoolOptions;
 use tower_http::cors::CorsLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -24,6 +26,8 @@ pub mod tests;
 
 use config::Config;
 use db::Database;
+use middlewares::auth::TrustedProxies;
+use services::jwt_keys::JwtKeys;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
@@ -32,14 +36,79 @@ use utoipa_swagger_ui::SwaggerUi;
 pub struct AppState {
     pub db: Database,
     pub config: Config,
+    pub jwt_keys: Arc<JwtKeys>,
 }
 
 impl AppState {
+    /// Panics if the JWT signing or verification keys cannot be loaded.
     pub fn new(db: Database, config: Config) -> Self {
-        Self { db, config }
+        let jwt_keys =
+            Arc::new(JwtKeys::from_config(&config).expect("Invalid JWT key configuration"));
+
+        Self {
+            db,
+            config,
+            jwt_keys,
+        }
     }
 }
 
+/// How often memberships whose access has ended are removed
+const EXPIRED_MEMBERS_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often overdue device commands are expired
+const DEVICE_COMMANDS_EXPIRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often devices that have not been heard from are taken offline
+const OFFLINE_DEVICES_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Spawn the periodic background jobs
+pub fn spawn_background_tasks(app_state: AppState) {
+    let house_members_service =
+        routes::house_members::HouseMembersRouterState::new(app_state.clone())
+            .house_members_service;
+    let device_commands_service =
+        routes::device_commands::DeviceCommandsRouterState::new(app_state.clone())
+            .device_commands_service;
+    let device_service = routes::devices::DeviceRouterState::new(app_state).device_service;
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXPIRED_MEMBERS_CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match house_members_service.remove_expired_members().await {
+                Ok(0) => {}
+                Ok(removed) => tracing::info!("Removed {} expired house members", removed),
+                Err(e) => tracing::error!("Failed to remove expired house members: {}", e),
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DEVICE_COMMANDS_EXPIRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            match device_commands_service.expire_commands().await {
+                Ok(0) => {}
+                Ok(expired) => tracing::info!("Expired {} device commands", expired),
+                Err(e) => tracing::error!("Failed to expire device commands: {}", e),
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(OFFLINE_DEVICES_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match device_service.mark_offline_devices().await {
+                Ok(0) => {}
+                Ok(offline) => tracing::info!("{} devices went offline", offline),
+                Err(e) => tracing::error!("Failed to take devices offline: {}", e),
+            }
+        }
+    });
+}
+
 /// Initialize tracing subscriber for logging
 pub fn init_tracing() {
     tracing_subscriber::registry()
@@ -103,15 +172,45 @@ pub fn create_app(app_state: AppState) -> Router {
     let protected_routes = Router::new()
         .nest("/profile", routes::users::users_router(app_state.clone()))
         .nest("/tokens", routes::api_tokens::api_tokens_router(app_state.clone()))
+        .nest("/admin", routes::admin::admin_router(app_state.clone()))
         .nest("/houses", routes::houses::houses_router(app_state.clone()))
         .nest(
             "/houses/{house_id}/rooms",
             routes::rooms::rooms_router(app_state.clone()),
         )
+        .nest(
+            "/houses/{house_id}",
+            routes::house_members::house_members_router(app_state.clone()),
+        )
+        .nest(
+            "/invitations",
+            routes::house_members::invitations_router(app_state.clone()),
+        )
+        .nest(
+            "/houses/{house_id}/transfers",
+            routes::house_transfers::house_transfers_router(app_state.clone()),
+        )
+        .nest(
+            "/transfers",
+            routes::house_transfers::transfers_router(app_state.clone()),
+        )
         .nest(
             "/devices",
             routes::devices::devices_router(app_state.clone()),
         )
+        .nest(
+            "/devices/{device_id}/state",
+            routes::device_state::device_state_router(app_state.clone()),
+        )
+        .nest(
+            "/devices/{device_id}/commands",
+            routes::device_commands::device_commands_router(app_state.clone()),
+        )
+        .nest(
+            "/houses/{house_id}/commands",
+            routes::device_commands::house_commands_router(app_state.clone()),
+        )
+        .nest("/device-types", routes::devices::device_types_router())
         .nest(
             "/houses/{house_id}/devices",
             routes::devices::house_devices_router(app_state.clone()),
@@ -120,6 +219,14 @@ pub fn create_app(app_state: AppState) -> Router {
             "/houses/{house_id}/rooms/{room_id}/devices",
             routes::devices::room_devices_router(app_state.clone()),
         )
+        .nest(
+            "/houses/{house_id}/zones",
+            routes::zones::zones_router(app_state.clone()),
+        )
+        .nest(
+            "/houses/{house_id}/zones/{zone_id}/devices",
+            routes::devices::zone_devices_router(app_state.clone()),
+        )
         .merge(routes::device_metrics::device_metrics_routes(
             app_state.clone(),
         ))
@@ -144,6 +251,9 @@ pub fn create_app(app_state: AppState) -> Router {
         .merge(protected_routes)
         // Health check endpoint
         .route("/health", axum::routing::get(health_check))
+        .layer(Extension(TrustedProxies(Arc::new(
+            app_state.config.trusted_proxies.clone(),
+        ))))
         .layer(cors)
 }
 
@@ -165,6 +275,7 @@ pub async fn health_check() -> &'static str {
 #[cfg(test)]
 mod lib_tests {
     use super::*;
+    use crate::config::JwtAlgorithm;
 
     #[tokio::test]
     async fn test_health_check() {
@@ -182,8 +293,18 @@ mod lib_tests {
             db_user: "test".to_string(),
             db_pass: "test".to_string(),
             jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
             jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
             frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
         };
 
         // This would require a real database connection, so we just test the config
@@ -3,8 +3,9 @@
 use anyhow::Context;
 use smart_home_backend::{
     config::Config, create_app, create_database_pool, db::Database, init_tracing, run_migrations,
-    AppState,
+    spawn_background_tasks, AppState,
 };
+use std::net::SocketAddr;
 use tracing::info;
 
 #[tokio::main]
@@ -34,6 +35,9 @@ async fn main() -> anyhow::Result<()> {
     // Create application state
     let app_state = AppState::new(Database::new(pool), config.clone());
 
+    // Start background jobs
+    spawn_background_tasks(app_state.clone());
+
     // Create application router
     let app = create_app(app_state);
 
@@ -51,7 +55,12 @@ async fn main() -> anyhow::Result<()> {
         listener_address
     );
 
-    axum::serve(listener, app).await.context("Server error")?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .context("Server error")?;
 
     Ok(())
 }
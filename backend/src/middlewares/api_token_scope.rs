@@ -0,0 +1,146 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    errors::AppError,
+    models::api_tokens::{ApiTokenGrant, ApiTokenScope},
+    services::access_control_service::{AccessControlServiceTrait, AccessTarget},
+};
+use axum::{
+    body::Body,
+    extract::{Path, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+
+/// Scopes an API token needs to use the routes of a router
+#[derive(Clone)]
+pub struct RequiredScopes {
+    read: ApiTokenScope,
+    write: ApiTokenScope,
+    access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+}
+
+impl RequiredScopes {
+    pub fn new(
+        read: ApiTokenScope,
+        write: ApiTokenScope,
+        access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            read,
+            write,
+            access_control_service,
+        }
+    }
+
+    fn for_method(&self, method: &Method) -> ApiTokenScope {
+        if method == Method::GET || method == Method::HEAD {
+            self.read
+        } else {
+            self.write
+        }
+    }
+}
+
+/// Enforces API token scopes on the routes of a router
+///
+/// This middleware:
+/// 1. Lets requests authenticated with a JWT through unchanged
+/// 2. Requires the `read` scope for GET/HEAD requests and the `write` scope otherwise
+/// 3. For tokens restricted to a house, resolves the house of the resource addressed
+///    by the path (`house_id`, `room_id` or `device_id`) and rejects other houses
+///
+/// Routes that take their target from the request body check the house
+/// restriction in the handler.
+pub async fn api_token_scope_middleware(
+    State(required): State<RequiredScopes>,
+    params: Option<Path<HashMap<String, i64>>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(grant) = req.extensions().get::<ApiTokenGrant>().cloned() else {
+        return Ok(next.run(req).await);
+    };
+
+    grant.require(required.for_method(req.method()))?;
+
+    if let Some(target) = params.and_then(|Path(params)| path_target(&params)) {
+        required
+            .access_control_service
+            .validate_token_house(&grant, target)
+            .await?;
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Rejects requests authenticated with an API token
+///
+/// Used for routes that manage credentials, so a leaked token can never be
+/// used to mint or revoke tokens.
+pub async fn reject_api_tokens(req: Request<Body>, next: Next) -> Result<Response, AppError> {
+    if req.extensions().get::<ApiTokenGrant>().is_some() {
+        return Err(AppError::AuthorizationError(
+            "API tokens cannot be used for this operation".to_string(),
+        ));
+    }
+
+    Ok(next.run(req).await)
+}
+
+fn path_target(params: &HashMap<String, i64>) -> Option<AccessTarget> {
+    if let Some(house_id) = params.get("house_id") {
+        Some(AccessTarget::House(*house_id))
+    } else if let Some(room_id) = params.get("room_id") {
+        Some(AccessTarget::Room(*room_id))
+    } else {
+        params
+            .get("device_id")
+            .map(|device_id| AccessTarget::Device(*device_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_target_prefers_house() {
+        let params = HashMap::from([("house_id".to_string(), 1), ("room_id".to_string(), 2)]);
+        assert_eq!(path_target(&params), Some(AccessTarget::House(1)));
+    }
+
+    #[test]
+    fn test_path_target_device() {
+        let params = HashMap::from([("device_id".to_string(), 3)]);
+        assert_eq!(path_target(&params), Some(AccessTarget::Device(3)));
+    }
+
+    #[test]
+    fn test_path_target_none() {
+        let params = HashMap::from([("id".to_string(), 4)]);
+        assert_eq!(path_target(&params), None);
+    }
+
+    #[test]
+    fn test_required_scope_for_method() {
+        let required = RequiredScopes::new(
+            ApiTokenScope::DevicesRead,
+            ApiTokenScope::DevicesWrite,
+            Arc::new(crate::services::access_control_service::MockAccessControlServiceTrait::new()),
+        );
+        assert_eq!(
+            required.for_method(&Method::GET),
+            ApiTokenScope::DevicesRead
+        );
+        assert_eq!(
+            required.for_method(&Method::DELETE),
+            ApiTokenScope::DevicesWrite
+        );
+        assert_eq!(
+            required.for_method(&Method::PATCH),
+            ApiTokenScope::DevicesWrite
+        );
+    }
+}
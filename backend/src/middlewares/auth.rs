@@ -1,19 +1,48 @@
-use crate::{errors::AppError, models::auth::Claims, repositories::UserRepositoryTrait, AppState};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use crate::{
+    errors::AppError,
+    models::{
+        api_tokens::{ApiToken, ApiTokenGrant},
+        auth::Claims,
+        auth_events::{AuthEventType, NewAuthEvent},
+        sessions::{ClientInfo, SessionId},
+        users::UserRole,
+    },
+    repositories::{
+        user_houses_repository::UserHousesRepository, ApiTokensRepository, AuthEventsRepository,
+        SessionsRepository, SessionsRepositoryTrait, UserRepositoryTrait,
+    },
+    services::{
+        access_control_service::AccessControlService,
+        api_tokens::{ApiTokensService, API_TOKEN_PREFIX},
+        auth_events::{AuthEventsService, AuthEventsServiceTrait},
+    },
+    AppState,
+};
 use axum::{
     body::Body,
-    extract::{Request, State},
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{header::USER_AGENT, request::Parts, Extensions, HeaderMap},
     middleware::Next,
     response::Response,
 };
-use jsonwebtoken::{decode, DecodingKey, Validation};
 
-/// Authentication middleware that validates JWT tokens
+/// Authentication middleware that validates JWT tokens and API tokens
 ///
 /// This middleware:
 /// 1. Extracts the Bearer token from the Authorization header
-/// 2. Validates the JWT token
-/// 3. Verifies the user still exists in the database and fetches the user object
-/// 4. Adds the `User` object to request extensions for use in handlers
+/// 2. Validates the token: values starting with `sh_` are treated as API tokens
+///    and resolved through the `api_tokens` table, everything else as a JWT whose
+///    session (`sid` claim) must still be active
+/// 3. Verifies the user still exists in the database and fetches the user object,
+///    rejecting accounts disabled by an admin
+/// 4. Adds the user id and [`UserRole`] to request extensions for use in handlers,
+///    along with the [`ApiTokenGrant`] of an API token or the [`SessionId`] of a JWT
 pub async fn auth_middleware(
     State(state): State<AppState>,
     mut req: Request<Body>,
@@ -35,33 +64,35 @@ pub async fn auth_middleware(
         return Err(AppError::AuthenticationError("Empty token".to_string()));
     }
 
-    // Decode and validate JWT token
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(state.config.jwt_secret.as_ref()),
-        &Validation::default(),
-    )
-    .map_err(|e| match e.kind() {
-        jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
-            AppError::AuthenticationError("Token has expired".to_string())
-        }
-        jsonwebtoken::errors::ErrorKind::InvalidToken => {
-            AppError::AuthenticationError("Invalid token format".to_string())
-        }
-        jsonwebtoken::errors::ErrorKind::InvalidSignature => {
-            AppError::AuthenticationError("Invalid token signature".to_string())
-        }
-        _ => AppError::AuthenticationError("Token validation failed".to_string()),
-    })?;
-
-    let user_id = token_data.claims.sub;
+    let (user_id, grant, session_id) = if token.starts_with(API_TOKEN_PREFIX) {
+        let client = client_info(req.headers(), req.extensions());
+        let api_token = authenticate_api_token(&state, token, client).await?;
+        (
+            api_token.user_id,
+            Some(ApiTokenGrant::from(&api_token)),
+            None,
+        )
+    } else {
+        let claims = authenticate_jwt(&state, token).await?;
+        (claims.sub, None, Some(SessionId(claims.sid)))
+    };
 
     // Verify that the user still exists in the database
     let user_repository = crate::repositories::UserRepository::new(state.db.pool.clone());
 
     match user_repository.get_user_by_id(user_id).await {
+        Ok(user) if user.disabled_at.is_some() => Err(AppError::AuthorizationError(
+            "Account is disabled".to_string(),
+        )),
         Ok(user) => {
             req.extensions_mut().insert(user.id);
+            req.extensions_mut().insert(user.role);
+            if let Some(grant) = grant {
+                req.extensions_mut().insert(grant);
+            }
+            if let Some(session_id) = session_id {
+                req.extensions_mut().insert(session_id);
+            }
             Ok(next.run(req).await)
         }
         Err(crate::errors::AppError::NotFound(_)) => Err(AppError::AuthenticationError(
@@ -73,6 +104,164 @@ pub async fn auth_middleware(
     }
 }
 
+/// Decodes and validates a JWT and checks that its session is still active
+async fn authenticate_jwt(state: &AppState, token: &str) -> Result<Claims, AppError> {
+    let token_data = state
+        .jwt_keys
+        .decode::<Claims>(token)
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
+                AppError::AuthenticationError("Token has expired".to_string())
+            }
+            jsonwebtoken::errors::ErrorKind::InvalidToken => {
+                AppError::AuthenticationError("Invalid token format".to_string())
+            }
+            jsonwebtoken::errors::ErrorKind::InvalidSignature => {
+                AppError::AuthenticationError("Invalid token signature".to_string())
+            }
+            _ => AppError::AuthenticationError("Token validation failed".to_string()),
+        })?;
+
+    let claims = token_data.claims;
+    let sessions_repository = SessionsRepository::new(state.db.pool.clone());
+
+    if !sessions_repository
+        .is_active(claims.sid, claims.sub)
+        .await?
+    {
+        return Err(AppError::AuthenticationError(
+            "Session has been revoked".to_string(),
+        ));
+    }
+
+    Ok(claims)
+}
+
+/// Resolves the owner of an API token through the `api_tokens` table and
+/// records the use in the authentication audit log
+async fn authenticate_api_token(
+    state: &AppState,
+    token: &str,
+    client: ClientInfo,
+) -> Result<ApiToken, AppError> {
+    let tokens_repository = Arc::new(ApiTokensRepository::new(state.db.pool.clone()));
+    let user_houses_repository = Arc::new(UserHousesRepository::new(state.db.pool.clone()));
+    let access_control_service = Arc::new(AccessControlService::new(user_houses_repository));
+    let auth_events_service = Arc::new(AuthEventsService::new(Arc::new(
+        AuthEventsRepository::new(state.db.pool.clone()),
+    )));
+    let tokens_service = ApiTokensService::new(
+        state.config.clone(),
+        tokens_repository,
+        access_control_service,
+        auth_events_service.clone(),
+    );
+
+    let api_token = tokens_service
+        .authenticate(token, client.ip.clone())
+        .await?;
+    auth_events_service
+        .record(
+            NewAuthEvent::new(AuthEventType::TokenUsed, Some(api_token.user_id), &client)
+                .with_details(format!("Used API token {}", api_token.id)),
+        )
+        .await;
+
+    Ok(api_token)
+}
+
+/// Reverse proxies whose `X-Forwarded-For` is trusted, see `Config::trusted_proxies`
+#[derive(Debug, Clone)]
+pub struct TrustedProxies(pub Arc<Vec<IpAddr>>);
+
+/// Client address of a request
+///
+/// The peer address of the connection, unless the peer is a trusted proxy. Then
+/// `X-Forwarded-For` is walked from the right and the first hop that is not a
+/// trusted proxy is the client; entries left of it are set by the client and
+/// ignored.
+pub fn client_ip(headers: &HeaderMap, extensions: &Extensions) -> Option<String> {
+    let mut client = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())?;
+    let Some(TrustedProxies(trusted)) = extensions.get::<TrustedProxies>() else {
+        return Some(client.to_string());
+    };
+
+    let forwarded: Vec<&str> = headers
+        .get_all("X-Forwarded-For")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .collect();
+    for hop in forwarded.into_iter().rev() {
+        if !trusted.contains(&client) {
+            break;
+        }
+        match hop.trim().parse() {
+            Ok(ip) => client = ip,
+            Err(_) => break,
+        }
+    }
+
+    Some(client.to_string())
+}
+
+fn client_info(headers: &HeaderMap, extensions: &Extensions) -> ClientInfo {
+    ClientInfo {
+        user_agent: headers
+            .get(USER_AGENT)
+            .and_then(|h| h.to_str().ok())
+            .map(|user_agent| user_agent.to_string()),
+        ip: client_ip(headers, extensions),
+    }
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(client_info(&parts.headers, &parts.extensions))
+    }
+}
+
+/// Extractor for handlers only admins may use. Holds the admin's user id.
+///
+/// Requires a session of a user with [`UserRole::Admin`]; API tokens are
+/// rejected even if they belong to an admin.
+pub struct RequireAdmin(pub i64);
+
+impl<S> FromRequestParts<S> for RequireAdmin
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if parts.extensions.get::<ApiTokenGrant>().is_some() {
+            return Err(AppError::AuthorizationError(
+                "API tokens cannot be used for this operation".to_string(),
+            ));
+        }
+
+        let user_id = parts
+            .extensions
+            .get::<i64>()
+            .copied()
+            .ok_or_else(|| AppError::AuthenticationError("Not authenticated".to_string()))?;
+
+        match parts.extensions.get::<UserRole>() {
+            Some(UserRole::Admin) => Ok(RequireAdmin(user_id)),
+            _ => Err(AppError::AuthorizationError(
+                "Admin access required".to_string(),
+            )),
+        }
+    }
+}
+
 /// Extract User from request extensions
 ///
 /// This function should be called from handlers that are protected by auth middleware
@@ -1,2 +1,3 @@
+pub mod api_token_scope;
 pub mod auth;
 pub mod validator;
@@ -1,9 +1,23 @@
+pub mod account_tokens;
+pub mod admin;
 pub mod api_tokens;
 pub mod auth;
+pub mod auth_events;
 pub mod common;
+pub mod device_commands;
 pub mod device_metrics;
+pub mod device_state;
+pub mod device_types;
 pub mod devices;
+pub mod house_invitations;
+pub mod house_transfers;
 pub mod houses;
+pub mod jwks;
+pub mod login_attempts;
+pub mod mail;
 pub mod rooms;
+pub mod sessions;
+pub mod two_factor;
 pub mod user_houses;
 pub mod users;
+pub mod zones;
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+/// What a single-use account token may be exchanged for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountTokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl AccountTokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountTokenPurpose::PasswordReset => "password_reset",
+            AccountTokenPurpose::EmailVerification => "email_verification",
+        }
+    }
+}
+
+impl std::fmt::Display for AccountTokenPurpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A password reset or email verification token sent to the user by email.
+#[derive(Debug, FromRow, Clone)]
+pub struct AccountToken {
+    pub id: i64,
+    pub user_id: i64,
+    pub purpose: String,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
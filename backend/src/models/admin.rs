@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::users::UserRole;
+
+pub const DEFAULT_USERS_PAGE_SIZE: i64 = 50;
+pub const MAX_USERS_PAGE_SIZE: i64 = 200;
+
+/// Filters for listing users. All filters are optional and combined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams, Default)]
+pub struct AdminUserFilters {
+    /// Case-insensitive match on name, email or phone.
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub disabled: Option<bool>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateUserRoleRequest {
+    pub role: UserRole,
+}
+
+/// System-wide row counts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SystemStats {
+    pub users: i64,
+    pub disabled_users: i64,
+    pub houses: i64,
+    pub rooms: i64,
+    pub devices: i64,
+    pub device_metrics: i64,
+}
@@ -4,15 +4,81 @@ use sqlx::FromRow;
 use utoipa::ToSchema;
 use validator::Validate;
 
+use crate::errors::AppError;
+
+/// Permission that can be granted to an API token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ApiTokenScope {
+    #[serde(rename = "houses:read")]
+    HousesRead,
+    /// Full access to houses, rooms, devices and metrics.
+    #[serde(rename = "houses:admin")]
+    HousesAdmin,
+    #[serde(rename = "devices:read")]
+    DevicesRead,
+    #[serde(rename = "devices:write")]
+    DevicesWrite,
+    #[serde(rename = "metrics:read")]
+    MetricsRead,
+    #[serde(rename = "metrics:write")]
+    MetricsWrite,
+}
+
+impl ApiTokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiTokenScope::HousesRead => "houses:read",
+            ApiTokenScope::HousesAdmin => "houses:admin",
+            ApiTokenScope::DevicesRead => "devices:read",
+            ApiTokenScope::DevicesWrite => "devices:write",
+            ApiTokenScope::MetricsRead => "metrics:read",
+            ApiTokenScope::MetricsWrite => "metrics:write",
+        }
+    }
+}
+
+impl std::fmt::Display for ApiTokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ApiTokenScope {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "houses:read" => Ok(ApiTokenScope::HousesRead),
+            "houses:admin" => Ok(ApiTokenScope::HousesAdmin),
+            "devices:read" => Ok(ApiTokenScope::DevicesRead),
+            "devices:write" => Ok(ApiTokenScope::DevicesWrite),
+            "metrics:read" => Ok(ApiTokenScope::MetricsRead),
+            "metrics:write" => Ok(ApiTokenScope::MetricsWrite),
+            _ => Err(AppError::BadRequest(format!(
+                "Unknown API token scope: {}",
+                value
+            ))),
+        }
+    }
+}
+
 /// Represents an API token in the database.
 #[derive(Debug, FromRow, Serialize, Deserialize, ToSchema, Clone)]
 pub struct ApiToken {
     pub id: i64,
     pub user_id: i64,
     pub name: String,
+    /// Indexed lookup key embedded in the token. `None` for legacy tokens.
+    pub public_id: Option<String>,
     #[serde(skip_serializing)] // Never expose the hash
     pub token_hash: String,
     pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub scopes: Vec<String>,
+    /// House the token is restricted to, if any.
+    pub house_id: Option<i64>,
 }
 
 /// Publicly safe information about an API token.
@@ -21,6 +87,16 @@ pub struct PublicApiToken {
     pub id: i64,
     pub name: String,
     pub created_at: DateTime<Utc>,
+    /// Legacy tokens predate the `sh_<public_id>_<secret>` format and can no
+    /// longer authenticate. They should be deleted and reissued.
+    pub legacy: bool,
+    /// Tokens without an expiry stay valid until they are deleted.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// `None` if the token has never been used.
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub scopes: Vec<String>,
+    pub house_id: Option<i64>,
 }
 
 /// The response when creating a new API token, including the plaintext token.
@@ -30,6 +106,9 @@ pub struct NewApiToken {
     pub id: i64,
     pub name: String,
     pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub scopes: Vec<String>,
+    pub house_id: Option<i64>,
     /// The plaintext token. This is only provided on creation.
     pub token: String,
 }
@@ -40,6 +119,54 @@ pub struct CreateApiToken {
     #[validate(length(min = 2, message = "Name must be at least 2 characters"))]
     #[serde(default)]
     pub name: String,
+    /// Optional point in time after which the token is rejected.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Permissions granted to the token. At least one is required.
+    #[serde(default)]
+    pub scopes: Option<Vec<ApiTokenScope>>,
+    /// Restricts the token to a single house the user has access to.
+    #[serde(default)]
+    pub house_id: Option<i64>,
+}
+
+/// Scopes and house restriction of the API token that authenticated a request.
+///
+/// Inserted into the request extensions by the auth middleware. Requests
+/// authenticated with a JWT carry no grant and are not restricted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiTokenGrant {
+    pub scopes: Vec<ApiTokenScope>,
+    pub house_id: Option<i64>,
+}
+
+impl ApiTokenGrant {
+    /// `houses:admin` implies every other scope.
+    pub fn allows(&self, scope: ApiTokenScope) -> bool {
+        self.scopes
+            .iter()
+            .any(|s| *s == scope || *s == ApiTokenScope::HousesAdmin)
+    }
+
+    pub fn require(&self, scope: ApiTokenScope) -> Result<(), AppError> {
+        if !self.allows(scope) {
+            return Err(AppError::AuthorizationError(format!(
+                "API token is missing the '{}' scope",
+                scope
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl From<&ApiToken> for ApiTokenGrant {
+    fn from(token: &ApiToken) -> Self {
+        Self {
+            // Unknown scopes are ignored rather than granting anything.
+            scopes: token.scopes.iter().filter_map(|s| s.parse().ok()).collect(),
+            house_id: token.house_id,
+        }
+    }
 }
 
 impl From<ApiToken> for PublicApiToken {
@@ -48,6 +175,52 @@ impl From<ApiToken> for PublicApiToken {
             id: token.id,
             name: token.name,
             created_at: token.created_at,
+            legacy: token.public_id.is_none(),
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            last_used_ip: token.last_used_ip,
+            scopes: token.scopes,
+            house_id: token.house_id,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_token_scope_round_trip() {
+        for scope in [
+            ApiTokenScope::HousesRead,
+            ApiTokenScope::HousesAdmin,
+            ApiTokenScope::DevicesRead,
+            ApiTokenScope::DevicesWrite,
+            ApiTokenScope::MetricsRead,
+            ApiTokenScope::MetricsWrite,
+        ] {
+            assert_eq!(scope.to_string().parse::<ApiTokenScope>().unwrap(), scope);
+        }
+        assert!("houses:delete".parse::<ApiTokenScope>().is_err());
+    }
+
+    #[test]
+    fn test_api_token_grant_allows() {
+        let grant = ApiTokenGrant {
+            scopes: vec![ApiTokenScope::MetricsWrite],
+            house_id: Some(1),
+        };
+        assert!(grant.allows(ApiTokenScope::MetricsWrite));
+        assert!(!grant.allows(ApiTokenScope::MetricsRead));
+        assert!(matches!(
+            grant.require(ApiTokenScope::HousesAdmin),
+            Err(AppError::AuthorizationError(_))
+        ));
+
+        let admin = ApiTokenGrant {
+            scopes: vec![ApiTokenScope::HousesAdmin],
+            house_id: None,
+        };
+        assert!(admin.allows(ApiTokenScope::DevicesWrite));
+    }
+}
@@ -7,6 +7,7 @@ use validator::Validate;
 pub struct Claims {
     pub sub: i64,   // User ID
     pub exp: usize, // Expiration time
+    pub sid: i64,   // Session ID
 }
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
@@ -18,14 +19,25 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-use crate::models::users::User;
+use crate::models::{two_factor::TwoFactorChallenge, users::User};
 
 #[derive(Debug, Serialize, ToSchema, Deserialize, Clone)]
 pub struct AuthResponse {
     pub token: String,
+    /// Opaque token for `POST /auth/refresh`. Only returned once.
+    pub refresh_token: String,
     pub user: User,
 }
 
+/// Result of a password login: the tokens, or a challenge when the account
+/// requires a second factor.
+#[derive(Debug, Serialize, ToSchema, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    TwoFactorRequired(TwoFactorChallenge),
+}
+
 #[derive(Debug, FromRow, Clone)]
 pub struct PasswordHash {
     pub id: i64,
@@ -62,3 +74,33 @@ pub struct RegisterUser {
     #[serde(default)]
     pub password: String,
 }
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "Must be a valid email address"))]
+    #[serde(default)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordRequest {
+    /// Token from the password reset email.
+    #[validate(length(min = 1, message = "Token is required"))]
+    #[serde(default)]
+    pub token: String,
+    #[validate(length(
+        min = 6,
+        max = 25,
+        message = "Password must be between 6 and 25 characters"
+    ))]
+    #[serde(default)]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct VerifyEmailRequest {
+    /// Token from the verification email.
+    #[validate(length(min = 1, message = "Token is required"))]
+    #[serde(default)]
+    pub token: String,
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Database, Decode, Postgres};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::sessions::ClientInfo;
+
+pub const DEFAULT_AUTH_EVENTS_PAGE_SIZE: i64 = 50;
+pub const MAX_AUTH_EVENTS_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventType {
+    LoginSucceeded,
+    LoginFailed,
+    Signup,
+    /// An API token was created.
+    TokenCreated,
+    /// A request was authenticated with an API token.
+    TokenUsed,
+    /// An API token was deleted or a session was signed out.
+    TokenRevoked,
+    PasswordChanged,
+}
+
+impl AuthEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthEventType::LoginSucceeded => "login_succeeded",
+            AuthEventType::LoginFailed => "login_failed",
+            AuthEventType::Signup => "signup",
+            AuthEventType::TokenCreated => "token_created",
+            AuthEventType::TokenUsed => "token_used",
+            AuthEventType::TokenRevoked => "token_revoked",
+            AuthEventType::PasswordChanged => "password_changed",
+        }
+    }
+}
+
+impl std::fmt::Display for AuthEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for AuthEventType {
+    fn decode(
+        value: <Postgres as Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(match s {
+            "login_succeeded" => AuthEventType::LoginSucceeded,
+            "login_failed" => AuthEventType::LoginFailed,
+            "signup" => AuthEventType::Signup,
+            "token_created" => AuthEventType::TokenCreated,
+            "token_used" => AuthEventType::TokenUsed,
+            "token_revoked" => AuthEventType::TokenRevoked,
+            "password_changed" => AuthEventType::PasswordChanged,
+            _ => return Err(format!("Unknown auth event type: {}", s).into()),
+        })
+    }
+}
+
+/// An entry of the authentication audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AuthEvent {
+    pub id: i64,
+    /// `None` for attempts that don't match an account, or once the account
+    /// has been deleted.
+    pub user_id: Option<i64>,
+    pub event_type: AuthEventType,
+    /// Email address given for logins and signups.
+    pub email: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// What happened, e.g. why a login failed or which token was used.
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewAuthEvent {
+    pub event_type: AuthEventType,
+    pub user_id: Option<i64>,
+    pub email: Option<String>,
+    pub client: ClientInfo,
+    pub details: Option<String>,
+}
+
+impl NewAuthEvent {
+    pub fn new(event_type: AuthEventType, user_id: Option<i64>, client: &ClientInfo) -> Self {
+        Self {
+            event_type,
+            user_id,
+            email: None,
+            client: client.clone(),
+            details: None,
+        }
+    }
+
+    pub fn with_email(mut self, email: &str) -> Self {
+        self.email = Some(email.trim().to_lowercase());
+        self
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+}
+
+/// Filters for the admin query. All filters are optional and combined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams, Default)]
+pub struct AuthEventFilters {
+    pub user_id: Option<i64>,
+    pub event_type: Option<AuthEventType>,
+    /// Case-insensitive match on the email address given.
+    pub email: Option<String>,
+    pub ip: Option<String>,
+    /// Only events at or after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Only events before this time.
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Filters for a user's own security events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams, Default)]
+pub struct SecurityEventsQuery {
+    pub event_type: Option<AuthEventType>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Database, Decode, FromRow, Postgres};
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+pub const DEFAULT_COMMAND_TIMEOUT_SECONDS: i32 = 30;
+pub const DEFAULT_COMMAND_MAX_ATTEMPTS: i32 = 3;
+pub const DEFAULT_COMMAND_TTL_SECONDS: i64 = 3600;
+
+/// What a command asks the device to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandKind {
+    /// Set a capability to a value, e.g. `on_off` to `1` to turn a light on
+    Set,
+    Reboot,
+}
+
+impl CommandKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandKind::Set => "set",
+            CommandKind::Reboot => "reboot",
+        }
+    }
+}
+
+impl std::fmt::Display for CommandKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for CommandKind {
+    fn decode(
+        value: <Postgres as Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(match s {
+            "set" => CommandKind::Set,
+            "reboot" => CommandKind::Reboot,
+            _ => return Err(format!("Unknown command: {}", s).into()),
+        })
+    }
+}
+
+/// Where a command is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandStatus {
+    /// Waiting for a gateway to fetch it
+    Pending,
+    /// Fetched by a gateway, waiting for the device to acknowledge it
+    Delivered,
+    Acknowledged,
+    /// Reported as failed, or not acknowledged after all attempts
+    Failed,
+    /// Not completed in time, or cancelled
+    Expired,
+}
+
+impl CommandStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandStatus::Pending => "pending",
+            CommandStatus::Delivered => "delivered",
+            CommandStatus::Acknowledged => "acknowledged",
+            CommandStatus::Failed => "failed",
+            CommandStatus::Expired => "expired",
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(self, CommandStatus::Pending | CommandStatus::Delivered)
+    }
+}
+
+impl std::fmt::Display for CommandStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for CommandStatus {
+    fn decode(
+        value: <Postgres as Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(match s {
+            "pending" => CommandStatus::Pending,
+            "delivered" => CommandStatus::Delivered,
+            "acknowledged" => CommandStatus::Acknowledged,
+            "failed" => CommandStatus::Failed,
+            "expired" => CommandStatus::Expired,
+            _ => return Err(format!("Unknown command status: {}", s).into()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct DeviceCommand {
+    pub id: i64,
+    pub device_id: i64,
+    pub command: CommandKind,
+    pub capability: Option<String>,
+    pub value: Option<f64>,
+    pub status: CommandStatus,
+    /// How many times the command has been delivered
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// Seconds the device has to acknowledge a delivery
+    pub timeout_seconds: i32,
+    pub expires_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Why the command failed
+    pub error: Option<String>,
+    /// `None` once the user has been deleted
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn default_timeout_seconds() -> i32 {
+    DEFAULT_COMMAND_TIMEOUT_SECONDS
+}
+
+fn default_max_attempts() -> i32 {
+    DEFAULT_COMMAND_MAX_ATTEMPTS
+}
+
+fn default_ttl_seconds() -> i64 {
+    DEFAULT_COMMAND_TTL_SECONDS
+}
+
+/// The request body for sending a command to a device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_command_arguments"))]
+pub struct NewDeviceCommand {
+    pub command: CommandKind,
+    /// Capability to set, only for `set`
+    pub capability: Option<String>,
+    /// Value to set, only for `set`
+    pub value: Option<f64>,
+    /// Seconds the device has to acknowledge a delivery, 30 by default
+    #[validate(range(min = 1, max = 3600, message = "Timeout must be 1 to 3600 seconds"))]
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: i32,
+    /// How many times the command is delivered at most, 3 by default
+    #[validate(range(min = 1, max = 10, message = "Attempts must be 1 to 10"))]
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: i32,
+    /// Seconds until the command expires, an hour by default
+    #[validate(range(min = 1, max = 86400, message = "TTL must be 1 to 86400 seconds"))]
+    #[serde(default = "default_ttl_seconds")]
+    pub ttl_seconds: i64,
+}
+
+fn validate_command_arguments(command: &NewDeviceCommand) -> Result<(), ValidationError> {
+    let has_arguments = command.capability.is_some() && command.value.is_some();
+    let has_any_argument = command.capability.is_some() || command.value.is_some();
+    let valid = match command.command {
+        CommandKind::Set => has_arguments,
+        CommandKind::Reboot => !has_any_argument,
+    };
+
+    if !valid {
+        return Err(ValidationError::new("command").with_message(
+            "set takes a capability and a value, other commands take neither".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The request body for reporting that a device could not run a command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+pub struct FailDeviceCommand {
+    #[validate(length(min = 1, max = 500, message = "Error must be 1 to 500 characters"))]
+    #[serde(default)]
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams, Default)]
+pub struct DeviceCommandFilters {
+    pub status: Option<CommandStatus>,
+}
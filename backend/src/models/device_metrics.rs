@@ -20,6 +20,9 @@ pub struct AggregatedDeviceMetric {
     pub metric_type: String,
     pub metric_value: f64,
     pub unit: String,
+    /// Start of the day or week the value is aggregated over, `None` unless
+    /// bucketed
+    pub bucket_start: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
@@ -46,6 +49,24 @@ pub enum Aggregation {
     Max,
 }
 
+/// Calendar period aggregates are grouped by. Days and weeks start at midnight
+/// in the time zone of the house, and weeks on Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricBucket {
+    Day,
+    Week,
+}
+
+impl MetricBucket {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricBucket::Day => "day",
+            MetricBucket::Week => "week",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams)]
 pub struct DeviceMetricAgregation {
     pub metric_type: String,
@@ -59,4 +80,6 @@ pub struct DeviceMetricFilters {
     pub unit: Option<String>,
     pub metric_type: Option<String>,
     pub aggregate: Option<Vec<DeviceMetricAgregation>>,
+    /// Aggregate per day or week instead of over the whole period
+    pub bucket: Option<MetricBucket>,
 }
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// Values of the capabilities of a device, keyed by capability, e.g.
+/// `{"on_off": 1, "brightness": 40}`.
+pub type StateValues = Map<String, Value>;
+
+/// What a device last reported and what its users want it to be.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DeviceState {
+    pub device_id: i64,
+    #[schema(value_type = Object)]
+    pub desired: StateValues,
+    #[schema(value_type = Object)]
+    pub reported: StateValues,
+    /// Bumped on every change, `0` until the state is first written
+    pub version: i64,
+    pub desired_updated_at: Option<DateTime<Utc>>,
+    pub reported_updated_at: Option<DateTime<Utc>>,
+}
+
+impl DeviceState {
+    pub fn empty(device_id: i64) -> Self {
+        Self {
+            device_id,
+            desired: StateValues::new(),
+            reported: StateValues::new(),
+            version: 0,
+            desired_updated_at: None,
+            reported_updated_at: None,
+        }
+    }
+
+    /// Desired values the device has not reported yet.
+    pub fn delta(&self) -> StateValues {
+        self.desired
+            .iter()
+            .filter(|(key, desired)| match self.reported.get(*key) {
+                Some(reported) => reported.as_f64() != desired.as_f64(),
+                None => true,
+            })
+            .map(|(key, desired)| (key.clone(), desired.clone()))
+            .collect()
+    }
+}
+
+/// The request body for changing the state of a device. The values are merged
+/// into the current ones, and `null` removes a value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UpdateDeviceState {
+    /// Values to set on the device, only for capabilities that can be set
+    #[schema(value_type = Option<Object>)]
+    pub desired: Option<StateValues>,
+    /// Values the device has applied or measured
+    #[schema(value_type = Option<Object>)]
+    pub reported: Option<StateValues>,
+    /// Version the change is based on. The update fails if the state has
+    /// changed since.
+    pub version: Option<i64>,
+}
+
+/// Desired values a gateway still has to apply to a device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DeviceStateDelta {
+    pub device_id: i64,
+    pub version: i64,
+    #[schema(value_type = Object)]
+    pub delta: StateValues,
+}
+
+impl From<DeviceState> for DeviceStateDelta {
+    fn from(state: DeviceState) -> Self {
+        Self {
+            device_id: state.device_id,
+            version: state.version,
+            delta: state.delta(),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use validator::ValidationError;
+
+/// Offline timeout of devices whose type is not in the registry
+pub const DEFAULT_OFFLINE_AFTER_SECONDS: i64 = 600;
+
+/// Something a device can do or measure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CapabilityKind {
+    /// Switched on and off, `1` or `0`
+    OnOff,
+    /// Light level
+    Brightness,
+    /// White light from warm to cold
+    ColorTemperature,
+    /// Target temperature
+    Setpoint,
+    TemperatureSensor,
+    HumiditySensor,
+    /// Power drawn right now
+    PowerMeter,
+    /// Energy used in total
+    EnergyMeter,
+    /// Motion detected, `1` or `0`
+    MotionSensor,
+    /// Door or window open, `1` or `0`
+    ContactSensor,
+}
+
+impl CapabilityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CapabilityKind::OnOff => "on_off",
+            CapabilityKind::Brightness => "brightness",
+            CapabilityKind::ColorTemperature => "color_temperature",
+            CapabilityKind::Setpoint => "setpoint",
+            CapabilityKind::TemperatureSensor => "temperature_sensor",
+            CapabilityKind::HumiditySensor => "humidity_sensor",
+            CapabilityKind::PowerMeter => "power_meter",
+            CapabilityKind::EnergyMeter => "energy_meter",
+            CapabilityKind::MotionSensor => "motion_sensor",
+            CapabilityKind::ContactSensor => "contact_sensor",
+        }
+    }
+}
+
+/// A capability of a device type with the values it takes.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Capability {
+    pub kind: CapabilityKind,
+    /// Whether the value can be set, or is only reported by the device
+    pub writable: bool,
+    pub min: f64,
+    pub max: f64,
+    /// `None` for switches and binary sensors
+    pub unit: Option<&'static str>,
+}
+
+impl Capability {
+    const fn switch(kind: CapabilityKind, writable: bool) -> Self {
+        Self {
+            kind,
+            writable,
+            min: 0.0,
+            max: 1.0,
+            unit: None,
+        }
+    }
+
+    const fn control(kind: CapabilityKind, min: f64, max: f64, unit: &'static str) -> Self {
+        Self {
+            kind,
+            writable: true,
+            min,
+            max,
+            unit: Some(unit),
+        }
+    }
+
+    const fn sensor(kind: CapabilityKind, min: f64, max: f64, unit: &'static str) -> Self {
+        Self {
+            kind,
+            writable: false,
+            min,
+            max,
+            unit: Some(unit),
+        }
+    }
+
+    pub fn allows(&self, value: f64) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/// A kind of device clients can create, e.g. `dimmable_light`.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct DeviceType {
+    pub key: &'static str,
+    pub name: &'static str,
+    /// Seconds without heartbeats or metrics after which devices of the type
+    /// are offline
+    pub offline_after_seconds: i64,
+    #[schema(value_type = Vec<Capability>)]
+    pub capabilities: &'static [Capability],
+}
+
+impl DeviceType {
+    /// The registered type with the key. Devices created before the registry
+    /// may have other types, which have no capabilities.
+    pub fn find(key: &str) -> Option<&'static DeviceType> {
+        DEVICE_TYPES
+            .iter()
+            .find(|device_type| device_type.key == key)
+    }
+
+    pub fn capability(&self, kind: CapabilityKind) -> Option<&'static Capability> {
+        self.capabilities
+            .iter()
+            .find(|capability| capability.kind == kind)
+    }
+
+    /// The capability with the key, e.g. `brightness`.
+    pub fn capability_by_key(&self, key: &str) -> Option<&'static Capability> {
+        self.capabilities
+            .iter()
+            .find(|capability| capability.kind.as_str() == key)
+    }
+}
+
+/// The device types clients can create.
+pub static DEVICE_TYPES: &[DeviceType] = {
+    use CapabilityKind::*;
+
+    &[
+        DeviceType {
+            key: "light",
+            name: "Light",
+            offline_after_seconds: 300,
+            capabilities: &[Capability::switch(OnOff, true)],
+        },
+        DeviceType {
+            key: "dimmable_light",
+            name: "Dimmable light",
+            offline_after_seconds: 300,
+            capabilities: &[
+                Capability::switch(OnOff, true),
+                Capability::control(Brightness, 0.0, 100.0, "%"),
+            ],
+        },
+        DeviceType {
+            key: "tunable_light",
+            name: "Tunable white light",
+            offline_after_seconds: 300,
+            capabilities: &[
+                Capability::switch(OnOff, true),
+                Capability::control(Brightness, 0.0, 100.0, "%"),
+                Capability::control(ColorTemperature, 2000.0, 6500.0, "K"),
+            ],
+        },
+        DeviceType {
+            key: "smart_plug",
+            name: "Smart plug",
+            offline_after_seconds: 300,
+            capabilities: &[
+                Capability::switch(OnOff, true),
+                Capability::sensor(PowerMeter, 0.0, 3680.0, "W"),
+                Capability::sensor(EnergyMeter, 0.0, 1_000_000.0, "kWh"),
+            ],
+        },
+        DeviceType {
+            key: "thermostat",
+            name: "Thermostat",
+            offline_after_seconds: 600,
+            capabilities: &[
+                Capability::control(Setpoint, 5.0, 30.0, "°C"),
+                Capability::sensor(TemperatureSensor, -40.0, 60.0, "°C"),
+            ],
+        },
+        DeviceType {
+            key: "temperature_sensor",
+            name: "Temperature sensor",
+            offline_after_seconds: 1800,
+            capabilities: &[Capability::sensor(TemperatureSensor, -40.0, 85.0, "°C")],
+        },
+        DeviceType {
+            key: "climate_sensor",
+            name: "Temperature and humidity sensor",
+            offline_after_seconds: 1800,
+            capabilities: &[
+                Capability::sensor(TemperatureSensor, -40.0, 85.0, "°C"),
+                Capability::sensor(HumiditySensor, 0.0, 100.0, "%"),
+            ],
+        },
+        DeviceType {
+            key: "energy_meter",
+            name: "Energy meter",
+            offline_after_seconds: 300,
+            capabilities: &[
+                Capability::sensor(PowerMeter, 0.0, 100_000.0, "W"),
+                Capability::sensor(EnergyMeter, 0.0, 10_000_000.0, "kWh"),
+            ],
+        },
+        DeviceType {
+            key: "motion_sensor",
+            name: "Motion sensor",
+            offline_after_seconds: 3600,
+            capabilities: &[Capability::switch(MotionSensor, false)],
+        },
+        DeviceType {
+            key: "contact_sensor",
+            name: "Door and window sensor",
+            offline_after_seconds: 3600,
+            capabilities: &[Capability::switch(ContactSensor, false)],
+        },
+    ]
+};
+
+pub fn validate_device_type(key: &str) -> Result<(), ValidationError> {
+    if DeviceType::find(key).is_none() {
+        return Err(ValidationError::new("device_type")
+            .with_message("Unknown device type, see GET /device-types".into()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_type_keys_are_unique() {
+        for (i, device_type) in DEVICE_TYPES.iter().enumerate() {
+            assert!(DEVICE_TYPES[i + 1..]
+                .iter()
+                .all(|other| other.key != device_type.key));
+            assert!(!device_type.capabilities.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_find_device_type() {
+        let thermostat = DeviceType::find("thermostat").unwrap();
+        let setpoint = thermostat.capability(CapabilityKind::Setpoint).unwrap();
+        assert!(setpoint.writable);
+        assert!(setpoint.allows(21.5));
+        assert!(!setpoint.allows(45.0));
+        assert!(thermostat.capability(CapabilityKind::OnOff).is_none());
+
+        assert!(DeviceType::find("Thermostat").is_none());
+        assert!(validate_device_type("flux_capacitor").is_err());
+    }
+}
@@ -1,25 +1,96 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
-use utoipa::ToSchema;
+use sqlx::{Database, Decode, FromRow, Postgres};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
+use crate::models::device_types::{validate_device_type, Capability, DeviceType};
+
+/// Whether a device has been heard from recently
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceStatus {
+    Online,
+    /// Not heard from within the offline timeout of its type, or never
+    #[default]
+    Offline,
+}
+
+impl DeviceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeviceStatus::Online => "online",
+            DeviceStatus::Offline => "offline",
+        }
+    }
+}
+
+impl std::fmt::Display for DeviceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for DeviceStatus {
+    fn decode(
+        value: <Postgres as Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(match s {
+            "online" => DeviceStatus::Online,
+            "offline" => DeviceStatus::Offline,
+            _ => return Err(format!("Unknown device status: {}", s).into()),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Device {
     pub id: i64,
     pub name: String,
     pub device_type: String,
     pub room_id: i64,
+    pub status: DeviceStatus,
+    /// Last heartbeat or metric of the device
+    pub last_seen_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, IntoParams, Default)]
+pub struct DeviceFilters {
+    pub status: Option<DeviceStatus>,
+}
+
+/// A device with the capabilities of its type.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct DeviceDetails {
+    #[serde(flatten)]
+    pub device: Device,
+    /// Empty for devices whose type is not in the registry
+    pub capabilities: Vec<Capability>,
+}
+
+impl From<Device> for DeviceDetails {
+    fn from(device: Device) -> Self {
+        let capabilities = DeviceType::find(&device.device_type)
+            .map(|device_type| device_type.capabilities.to_vec())
+            .unwrap_or_default();
+
+        Self {
+            device,
+            capabilities,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateDevice {
     #[validate(length(min = 1, message = "Name cannot be empty"))]
     #[serde(default)]
     pub name: String,
-    #[validate(length(min = 1, message = "Device type cannot be empty"))]
+    /// Key of a type from `GET /device-types`
+    #[validate(custom(function = "validate_device_type"))]
     #[serde(default)]
     pub device_type: String,
     #[serde(default)]
@@ -27,9 +98,44 @@ pub struct CreateDevice {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+/// The request body for editing a device. Devices are moved to another room
+/// with `MoveDevice`; unknown fields such as `room_id` are rejected rather
+/// than ignored.
+#[serde(deny_unknown_fields)]
 pub struct UpdateDevice {
     #[validate(length(min = 1, message = "Name cannot be empty"))]
     pub name: Option<String>,
+    #[validate(custom(function = "validate_device_type"))]
     pub device_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+pub struct MoveDevice {
+    /// Room to move the device to. It may be in another house.
+    #[validate(range(min = 1, message = "Room is required"))]
+    #[serde(default)]
+    pub room_id: i64,
+}
+
+/// A stay of a device in a room.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct DeviceLocation {
+    pub id: i64,
+    pub device_id: i64,
+    /// `None` once the room has been deleted
     pub room_id: Option<i64>,
+    /// User who moved the device here, `None` when it was placed on creation
+    pub moved_by: Option<i64>,
+    pub placed_at: DateTime<Utc>,
+    /// `None` while the device is still in the room
+    pub removed_at: Option<DateTime<Utc>>,
+}
+
+/// A device coming online or going offline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ConnectivityEvent {
+    pub id: i64,
+    pub device_id: i64,
+    pub status: DeviceStatus,
+    pub occurred_at: DateTime<Utc>,
 }
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::models::user_houses::{HouseRole, MemberAccess};
+
+pub const DEFAULT_INVITATION_EXPIRES_IN_HOURS: i64 = 72;
+
+/// An invitation to join a house. Invitations can be accepted once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HouseInvitation {
+    pub id: i64,
+    pub house_id: i64,
+    /// `None` once the inviting user has been deleted.
+    pub invited_by: Option<i64>,
+    /// Only the user with this email address can accept. `None` for open codes
+    /// anyone with the code can accept.
+    pub email: Option<String>,
+    /// Role the invited user gets in the house.
+    pub role: HouseRole,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_by: Option<i64>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub declined_at: Option<DateTime<Utc>>,
+    /// Access limits the invited user gets in the house.
+    #[serde(flatten)]
+    pub access: MemberAccess,
+}
+
+/// An invitation addressed to the current user, with the house it's for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ReceivedHouseInvitation {
+    pub id: i64,
+    pub house_id: i64,
+    pub house_name: String,
+    pub role: HouseRole,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub access: MemberAccess,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewHouseInvitation {
+    pub house_id: i64,
+    pub invited_by: i64,
+    pub email: Option<String>,
+    pub role: HouseRole,
+    pub code_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub access: MemberAccess,
+}
+
+/// The request body for inviting someone to a house.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateHouseInvitation {
+    /// Email address of the invited user. The invitation is sent there and
+    /// only that user can accept it. Without an email the invitation is an
+    /// open code for anyone it's shared with.
+    #[validate(email(message = "Invalid email format"))]
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Role the invited user gets. Defaults to `member`.
+    #[serde(default)]
+    pub role: Option<HouseRole>,
+    /// Hours until the invitation expires. Defaults to 72, at most 720.
+    #[validate(range(min = 1, max = 720, message = "Must be between 1 and 720 hours"))]
+    #[serde(default)]
+    pub expires_in_hours: Option<i64>,
+    /// Access limits of the invited user. Owners can't be limited, and admins
+    /// can't be limited to rooms.
+    #[validate(nested)]
+    #[serde(flatten)]
+    pub access: MemberAccess,
+}
+
+/// The response when creating an invitation. The code is only shown once.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatedHouseInvitation {
+    #[serde(flatten)]
+    pub invitation: HouseInvitation,
+    /// Code to accept the invitation with.
+    pub code: String,
+    /// Link to the frontend page that accepts the invitation.
+    pub link: String,
+}
+
+/// The request body for accepting an invitation with its code.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AcceptHouseInvitation {
+    #[validate(length(min = 1, message = "Code is required"))]
+    #[serde(default)]
+    pub code: String,
+}
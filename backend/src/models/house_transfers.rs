@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::models::user_houses::HouseRole;
+
+pub const TRANSFER_EXPIRES_IN_HOURS: i64 = 72;
+
+/// An owner nominating a member to take over the house. Transfers are kept
+/// once accepted, declined or cancelled as the audit trail of the house.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HouseTransfer {
+    pub id: i64,
+    pub house_id: i64,
+    /// Owner handing the house over. `None` once the user has been deleted.
+    pub from_user_id: Option<i64>,
+    /// Nominated member. `None` once the user has been deleted.
+    pub to_user_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub declined_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    /// Role the previous owner got once the transfer was accepted, the one the
+    /// nominee had before.
+    pub previous_owner_role: Option<HouseRole>,
+}
+
+/// A pending transfer to the current user, with the house it's for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ReceivedHouseTransfer {
+    pub id: i64,
+    pub house_id: i64,
+    pub house_name: String,
+    pub from_user_id: Option<i64>,
+    pub from_first_name: Option<String>,
+    pub from_last_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The request body for handing a house over to another member.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateHouseTransfer {
+    /// ID of the member who takes over the house.
+    #[validate(range(min = 1, message = "Nominee is required"))]
+    #[serde(default)]
+    pub to_user_id: i64,
+}
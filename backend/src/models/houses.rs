@@ -1,7 +1,49 @@
 use serde::{Deserialize, Serialize};
-use sqlx::prelude::FromRow;
+use sqlx::{prelude::FromRow, Database, Decode, Postgres};
 use utoipa::ToSchema;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Units measurements of a house are shown in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    /// Celsius, metres, kilograms
+    #[default]
+    Metric,
+    /// Fahrenheit, feet, pounds
+    Imperial,
+}
+
+impl UnitSystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+        }
+    }
+}
+
+impl std::fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for UnitSystem {
+    fn decode(
+        value: <Postgres as Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(match s {
+            "metric" => UnitSystem::Metric,
+            "imperial" => UnitSystem::Imperial,
+            _ => return Err(format!("Unknown unit system: {}", s).into()),
+        })
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, Clone)]
 pub struct House {
@@ -10,11 +52,56 @@ pub struct House {
     pub address: String,
     pub r#type: String,
     pub description: String,
+    /// IANA time zone, e.g. `Europe/Berlin`. Days and weeks of metrics are
+    /// bucketed in it, and member access schedules are in its local time.
+    pub timezone: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub unit_system: UnitSystem,
+    /// ISO 4217 currency code, e.g. `EUR`
+    pub currency: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+fn default_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
+}
+
+fn validate_timezone(timezone: &str) -> Result<(), ValidationError> {
+    if timezone.parse::<chrono_tz::Tz>().is_err() {
+        return Err(ValidationError::new("timezone")
+            .with_message("Timezone must be an IANA time zone, e.g. Europe/Berlin".into()));
+    }
+
+    Ok(())
+}
+
+fn validate_currency(currency: &str) -> Result<(), ValidationError> {
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(ValidationError::new("currency")
+            .with_message("Currency must be an ISO 4217 code, e.g. EUR".into()));
+    }
+
+    Ok(())
+}
+
+/// A house is either located with both coordinates or not at all.
+fn validate_location(latitude: Option<f64>, longitude: Option<f64>) -> Result<(), ValidationError> {
+    if latitude.is_some() != longitude.is_some() {
+        return Err(ValidationError::new("location")
+            .with_message("Latitude and longitude must be given together".into()));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate, PartialEq, Clone)]
+#[validate(schema(function = "validate_new_house_location"))]
 pub struct NewHouse {
     #[validate(length(min = 3, message = "Name must be at least 3 characters long"))]
     #[serde(default)]
@@ -27,4 +114,52 @@ pub struct NewHouse {
     pub r#type: String,
     #[serde(default)]
     pub description: String,
+    /// IANA time zone, `UTC` by default
+    #[validate(custom(function = "validate_timezone"))]
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    #[validate(range(min = -90.0, max = 90.0, message = "Latitude must be between -90 and 90"))]
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0, message = "Longitude must be between -180 and 180"))]
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub unit_system: UnitSystem,
+    /// ISO 4217 currency code, `USD` by default
+    #[validate(custom(function = "validate_currency"))]
+    #[serde(default = "default_currency")]
+    pub currency: String,
+}
+
+fn validate_new_house_location(house: &NewHouse) -> Result<(), ValidationError> {
+    validate_location(house.latitude, house.longitude)
+}
+
+/// The request body for editing a house. Only the given fields change.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate, PartialEq, Clone)]
+#[validate(schema(function = "validate_update_house_location"))]
+pub struct UpdateHouse {
+    #[validate(length(min = 3, message = "Name must be at least 3 characters long"))]
+    pub name: Option<String>,
+    #[validate(length(min = 3, message = "Address must be at least 3 characters long"))]
+    pub address: Option<String>,
+    #[validate(length(min = 3, message = "Type must be at least 3 characters long"))]
+    pub r#type: Option<String>,
+    pub description: Option<String>,
+    /// Member access schedules are in local time, so this moves their windows
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+    /// Moves the house. Latitude and longitude are changed together.
+    #[validate(range(min = -90.0, max = 90.0, message = "Latitude must be between -90 and 90"))]
+    pub latitude: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0, message = "Longitude must be between -180 and 180"))]
+    pub longitude: Option<f64>,
+    pub unit_system: Option<UnitSystem>,
+    #[validate(custom(function = "validate_currency"))]
+    pub currency: Option<String>,
+}
+
+fn validate_update_house_location(house: &UpdateHouse) -> Result<(), ValidationError> {
+    validate_location(house.latitude, house.longitude)
 }
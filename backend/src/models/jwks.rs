@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Public key access tokens can be verified with (RFC 7517).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct JsonWebKey {
+    /// `RSA` or `OKP`.
+    pub kty: String,
+    /// Matches the `kid` header of tokens signed with this key.
+    pub kid: String,
+    /// `RS256` or `EdDSA`.
+    pub alg: String,
+    #[serde(rename = "use")]
+    pub use_: String,
+    /// RSA modulus, base64url encoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    /// RSA public exponent, base64url encoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// Curve of an `OKP` key, `Ed25519`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    /// Ed25519 public key, base64url encoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JsonWebKeySet {
+    pub keys: Vec<JsonWebKey>,
+}
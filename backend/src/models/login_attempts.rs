@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+/// What failed attempts are counted against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttemptScope {
+    /// Normalized email address, whether or not an account exists for it.
+    Account,
+    Ip,
+}
+
+impl AttemptScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttemptScope::Account => "account",
+            AttemptScope::Ip => "ip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrottleKey {
+    pub scope: AttemptScope,
+    pub key: String,
+}
+
+impl ThrottleKey {
+    pub fn account(email: &str) -> Self {
+        Self {
+            scope: AttemptScope::Account,
+            key: email.trim().to_lowercase(),
+        }
+    }
+
+    pub fn ip(ip: &str) -> Self {
+        Self {
+            scope: AttemptScope::Ip,
+            key: ip.to_string(),
+        }
+    }
+}
+
+/// Failed attempt counter of one account or IP address.
+#[derive(Debug, FromRow, Clone)]
+pub struct LoginAttempt {
+    pub scope: String,
+    pub key: String,
+    pub failed_count: i32,
+    pub last_failed_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,7 @@
+/// A plain text email handed to a `Mailer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::prelude::FromRow;
 use utoipa::ToSchema;
 use validator::Validate;
@@ -9,6 +9,8 @@ pub struct Room {
     pub house_id: i64,
     pub name: String,
     pub room_type: String,
+    /// Zone of the house the room is in, if any.
+    pub zone_id: Option<i64>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -21,4 +23,30 @@ pub struct NewRoom {
     #[validate(length(min = 3, message = "Room type must be at least 3 characters"))]
     #[serde(default)]
     pub room_type: String,
+    /// Zone of the same house to put the room in.
+    #[serde(default)]
+    pub zone_id: Option<i64>,
+}
+
+/// The request body for editing a room. Only the given fields change.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate, Clone, PartialEq)]
+pub struct UpdateRoom {
+    #[validate(length(min = 3, message = "Name must be at least 3 characters"))]
+    pub name: Option<String>,
+    #[validate(length(min = 3, message = "Room type must be at least 3 characters"))]
+    pub room_type: Option<String>,
+    /// Zone of the same house to move the room to, `null` to take it out of its
+    /// zone.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[schema(value_type = Option<i64>)]
+    pub zone_id: Option<Option<i64>>,
+}
+
+/// Tells a field set to `null` (`Some(None)`) apart from a missing one (`None`).
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
 }
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// A login session backing a refresh token.
+#[derive(Debug, FromRow, Serialize, Deserialize, ToSchema, Clone)]
+pub struct Session {
+    pub id: i64,
+    pub user_id: i64,
+    #[serde(skip_serializing)] // Never expose the hash
+    pub refresh_token_hash: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Session information shown to its owner.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PublicSession {
+    pub id: i64,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session the request was made with.
+    pub current: bool,
+}
+
+/// Id of the session the request's access token belongs to.
+///
+/// Inserted into the request extensions by the auth middleware for JWT
+/// requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionId(pub i64);
+
+/// Details about the client a session is created for.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct RefreshTokenRequest {
+    #[validate(length(min = 1, message = "Refresh token is required"))]
+    #[serde(default)]
+    pub refresh_token: String,
+}
+
+/// A fresh access token together with the rotated refresh token.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TokenPair {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+impl Session {
+    pub fn into_public(self, current_session_id: Option<i64>) -> PublicSession {
+        PublicSession {
+            current: current_session_id == Some(self.id),
+            id: self.id,
+            user_agent: self.user_agent,
+            ip: self.ip,
+            created_at: self.created_at,
+            last_used_at: self.last_used_at,
+            expires_at: self.expires_at,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// TOTP secret of a user. Two-factor authentication is enabled once the
+/// secret has been confirmed with a valid code.
+#[derive(Debug, FromRow, Clone)]
+pub struct UserTotp {
+    pub user_id: i64,
+    pub secret: String,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// Last accepted time step, codes from this step or earlier are rejected.
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TotpEnrollment {
+    /// Base32 encoded secret for manual entry.
+    pub secret: String,
+    /// `otpauth://` URI for authenticator apps, usually shown as a QR code.
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TotpCodeRequest {
+    /// Code from the authenticator app, or a recovery code.
+    #[validate(length(min = 1, message = "Code is required"))]
+    #[serde(default)]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecoveryCodes {
+    /// Single-use codes for logging in without the authenticator app. Only
+    /// returned once.
+    pub recovery_codes: Vec<String>,
+}
+
+/// Returned by `POST /auth/login` instead of the tokens when the account has
+/// two-factor authentication enabled.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct TwoFactorChallenge {
+    pub two_factor_required: bool,
+    /// Short-lived token for `POST /auth/login/2fa`.
+    pub challenge_token: String,
+    /// Lifetime of the challenge token in seconds.
+    pub expires_in: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorChallengeClaims {
+    pub sub: i64,   // User ID
+    pub exp: usize, // Expiration time
+    pub purpose: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TwoFactorLoginRequest {
+    #[validate(length(min = 1, message = "Challenge token is required"))]
+    #[serde(default)]
+    pub challenge_token: String,
+    /// Code from the authenticator app, or a recovery code.
+    #[validate(length(min = 1, message = "Code is required"))]
+    #[serde(default)]
+    pub code: String,
+}
@@ -1,9 +1,268 @@
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use sqlx::prelude::FromRow;
+use sqlx::{prelude::FromRow, Database, Decode, Postgres};
 use utoipa::ToSchema;
+use validator::Validate;
+
+/// Role of a user in a house
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HouseRole {
+    /// Full control, including deleting the house
+    Owner,
+    /// Manages rooms, devices and members
+    Admin,
+    /// Uses and configures devices
+    Member,
+    /// Read-only access
+    Guest,
+}
+
+impl std::fmt::Display for HouseRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HouseRole::Owner => write!(f, "owner"),
+            HouseRole::Admin => write!(f, "admin"),
+            HouseRole::Member => write!(f, "member"),
+            HouseRole::Guest => write!(f, "guest"),
+        }
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for HouseRole {
+    fn decode(
+        value: <Postgres as Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(match s {
+            "owner" => HouseRole::Owner,
+            "admin" => HouseRole::Admin,
+            "member" => HouseRole::Member,
+            "guest" => HouseRole::Guest,
+            _ => return Err(format!("Unknown house role: {}", s).into()),
+        })
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, Clone)]
 pub struct UserHouse {
     pub user_id: i64,
     pub house_id: i64,
+    pub role: HouseRole,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(weekday: chrono::Weekday) -> Self {
+        match weekday {
+            chrono::Weekday::Mon => Weekday::Monday,
+            chrono::Weekday::Tue => Weekday::Tuesday,
+            chrono::Weekday::Wed => Weekday::Wednesday,
+            chrono::Weekday::Thu => Weekday::Thursday,
+            chrono::Weekday::Fri => Weekday::Friday,
+            chrono::Weekday::Sat => Weekday::Saturday,
+            chrono::Weekday::Sun => Weekday::Sunday,
+        }
+    }
+}
+
+/// A weekly time window in the local time of the house, so it follows daylight
+/// saving time. A window ending before it starts runs past midnight, e.g.
+/// 22:00 to 06:00.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AccessWindow {
+    /// Days the window starts on
+    #[validate(length(min = 1, message = "At least one day is required"))]
+    pub days: Vec<Weekday>,
+    #[schema(value_type = String, example = "09:00:00")]
+    pub start: NaiveTime,
+    #[schema(value_type = String, example = "13:00:00")]
+    pub end: NaiveTime,
+}
+
+impl AccessWindow {
+    /// Whether the window contains `at`, given in the timezone of the house.
+    pub fn contains<Z: TimeZone>(&self, at: &DateTime<Z>) -> bool {
+        let time = at.time();
+        let today = Weekday::from(at.weekday());
+        if self.start <= self.end {
+            return self.days.contains(&today) && self.start <= time && time < self.end;
+        }
+
+        // The part after midnight belongs to the window of the day before
+        let yesterday = Weekday::from(at.weekday().pred());
+        (self.days.contains(&today) && time >= self.start)
+            || (self.days.contains(&yesterday) && time < self.end)
+    }
+}
+
+/// Limits of a membership: when it gives access, e.g. for cleaners or holiday
+/// renters, and to which rooms. Members without limits have access to the
+/// whole house at any time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct MemberAccess {
+    /// Access starts at this time
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    /// Access ends at this time, and the membership is removed shortly after
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Weekly windows access is limited to. `None` for any time of the week
+    #[validate(length(min = 1, message = "At least one window is required"), nested)]
+    #[serde(default)]
+    pub schedule: Option<Vec<AccessWindow>>,
+    /// Rooms the member can see and use. `None` for the whole house
+    #[validate(length(min = 1, message = "At least one room is required"))]
+    #[serde(default)]
+    pub room_ids: Option<Vec<i64>>,
+}
+
+impl MemberAccess {
+    pub fn is_limited(&self) -> bool {
+        self.valid_from.is_some()
+            || self.valid_until.is_some()
+            || self.schedule.is_some()
+            || self.room_ids.is_some()
+    }
+
+    pub fn allows_room(&self, room_id: i64) -> bool {
+        self.room_ids
+            .as_ref()
+            .is_none_or(|room_ids| room_ids.contains(&room_id))
+    }
+
+    /// Whether the limits allow access at `at`, given in the timezone of the
+    /// house so the schedule is evaluated in its local time.
+    pub fn allows<Z: TimeZone>(&self, at: &DateTime<Z>) -> bool {
+        if self.valid_from.is_some_and(|valid_from| *at < valid_from)
+            || self
+                .valid_until
+                .is_some_and(|valid_until| *at >= valid_until)
+        {
+            return false;
+        }
+
+        match &self.schedule {
+            Some(windows) => windows.iter().any(|window| window.contains(at)),
+            None => true,
+        }
+    }
+}
+
+/// Role and access limits of a user in a house.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HouseMembership {
+    pub role: HouseRole,
+    pub access: MemberAccess,
+    /// Timezone of the house, the schedule of `access` is in its local time
+    pub timezone: Tz,
+}
+
+/// A user with access to a house.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HouseMember {
+    pub user_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub role: HouseRole,
+    pub joined_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub access: MemberAccess,
+}
+
+/// A time-boxed membership removed after its access ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiredMember {
+    pub house_id: i64,
+    pub user_id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub valid_until: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_access_window_contains() {
+        // 2026-10-19 is a Monday
+        let monday_morning = Utc.with_ymd_and_hms(2026, 10, 19, 9, 30, 0).unwrap();
+        let window = AccessWindow {
+            days: vec![Weekday::Monday],
+            start: time(9, 0),
+            end: time(13, 0),
+        };
+
+        assert!(window.contains(&monday_morning));
+        assert!(!window.contains(&(monday_morning + chrono::Duration::hours(4))));
+        assert!(!window.contains(&(monday_morning + chrono::Duration::days(1))));
+    }
+
+    #[test]
+    fn test_access_window_past_midnight() {
+        let window = AccessWindow {
+            days: vec![Weekday::Sunday],
+            start: time(22, 0),
+            end: time(6, 0),
+        };
+
+        assert!(window.contains(&Utc.with_ymd_and_hms(2026, 10, 18, 23, 0, 0).unwrap()));
+        assert!(window.contains(&Utc.with_ymd_and_hms(2026, 10, 19, 5, 0, 0).unwrap()));
+        assert!(!window.contains(&Utc.with_ymd_and_hms(2026, 10, 19, 23, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_access_window_follows_daylight_saving_time() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let window = AccessWindow {
+            days: vec![Weekday::Monday],
+            start: time(9, 0),
+            end: time(13, 0),
+        };
+        let at = |day, hour| {
+            Utc.with_ymd_and_hms(2026, 10, day, hour, 30, 0)
+                .unwrap()
+                .with_timezone(&berlin)
+        };
+
+        // 09:30 in Berlin is 07:30 UTC in summer time and 08:30 UTC after it
+        // ends on 2026-10-25
+        assert!(window.contains(&at(19, 7)));
+        assert!(window.contains(&at(26, 8)));
+        assert!(!window.contains(&at(26, 7)));
+    }
+
+    #[test]
+    fn test_member_access_allows() {
+        let now = Utc::now();
+        let access = MemberAccess {
+            valid_from: Some(now - chrono::Duration::days(1)),
+            valid_until: Some(now + chrono::Duration::days(1)),
+            ..Default::default()
+        };
+
+        assert!(MemberAccess::default().allows(&now));
+        assert!(access.allows(&now));
+        assert!(!access.allows(&(now - chrono::Duration::days(2))));
+        assert!(!access.allows(&(now + chrono::Duration::days(1))));
+    }
 }
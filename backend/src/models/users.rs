@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Database, Decode, FromRow, Postgres};
 use utoipa::ToSchema;
+use validator::Validate;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
@@ -52,6 +53,49 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login_at: Option<DateTime<Utc>>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// Set while an admin has disabled the account.
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+/// Profile fields to change. Omitted fields keep their current value.
+#[derive(Debug, Clone, PartialEq, Deserialize, Validate, ToSchema)]
+pub struct UpdateProfileRequest {
+    #[validate(length(
+        min = 3,
+        max = 25,
+        message = "First name must be between 3 and 25 characters"
+    ))]
+    pub first_name: Option<String>,
+    #[validate(length(
+        min = 3,
+        max = 25,
+        message = "Last name must be between 3 and 25 characters"
+    ))]
+    pub last_name: Option<String>,
+    #[validate(length(min = 10, max = 15, message = "Invalid phone number"))]
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    #[serde(default)]
+    pub current_password: String,
+    #[validate(length(
+        min = 6,
+        max = 25,
+        message = "Password must be between 6 and 25 characters"
+    ))]
+    #[serde(default)]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct DeleteAccountRequest {
+    #[validate(length(min = 1, message = "Password is required"))]
+    #[serde(default)]
+    pub password: String,
 }
 
 #[cfg(test)]
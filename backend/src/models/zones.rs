@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// A group of rooms in a house, e.g. a floor, a wing or the garden.
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, Clone)]
+pub struct Zone {
+    pub id: i64,
+    pub house_id: i64,
+    pub name: String,
+    pub zone_type: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate, Clone, PartialEq)]
+pub struct NewZone {
+    #[validate(length(min = 3, message = "Name must be at least 3 characters"))]
+    #[serde(default)]
+    pub name: String,
+    /// What the zone is, e.g. `floor`, `wing` or `outdoor`.
+    #[validate(length(min = 3, message = "Zone type must be at least 3 characters"))]
+    #[serde(default)]
+    pub zone_type: String,
+}
+
+/// The request body for editing a zone. Only the given fields change.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate, Clone, PartialEq)]
+pub struct UpdateZone {
+    #[validate(length(min = 3, message = "Name must be at least 3 characters"))]
+    pub name: Option<String>,
+    #[validate(length(min = 3, message = "Zone type must be at least 3 characters"))]
+    pub zone_type: Option<String>,
+}
@@ -1,6 +1,6 @@
 use crate::{
-    errors::Result,
-    models::api_tokens::{ApiToken},
+    errors::{AppError, Result},
+    models::api_tokens::{ApiToken, CreateApiToken},
 };
 use async_trait::async_trait;
 use mockall::automock;
@@ -9,8 +9,17 @@ use sqlx::PgPool;
 #[automock]
 #[async_trait]
 pub trait ApiTokensRepositoryTrait {
-    async fn create(&self, user_id: i64, name: &str, token_hash: &str) -> Result<ApiToken>;
+    async fn create(
+        &self,
+        user_id: i64,
+        public_id: &str,
+        token_hash: &str,
+        token_data: &CreateApiToken,
+    ) -> Result<ApiToken>;
     async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<ApiToken>>;
+    async fn find_by_public_id(&self, public_id: &str) -> Result<Option<ApiToken>>;
+    async fn delete(&self, id: i64, user_id: i64) -> Result<()>;
+    async fn record_usage(&self, id: i64, ip: Option<String>) -> Result<()>;
 }
 
 #[derive(Clone)]
@@ -26,17 +35,35 @@ impl ApiTokensRepository {
 
 #[async_trait]
 impl ApiTokensRepositoryTrait for ApiTokensRepository {
-    async fn create(&self, user_id: i64, name: &str, token_hash: &str) -> Result<ApiToken> {
+    async fn create(
+        &self,
+        user_id: i64,
+        public_id: &str,
+        token_hash: &str,
+        token_data: &CreateApiToken,
+    ) -> Result<ApiToken> {
+        let scopes: Vec<String> = token_data
+            .scopes
+            .iter()
+            .flatten()
+            .map(|scope| scope.to_string())
+            .collect();
+
         let token = sqlx::query_as!(
             ApiToken,
             r#"
-            INSERT INTO api_tokens (user_id, name, token_hash)
-            VALUES ($1, $2, $3)
-            RETURNING id, user_id, name, token_hash, created_at
+            INSERT INTO api_tokens (user_id, name, public_id, token_hash, expires_at, scopes, house_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, user_id, name, public_id, token_hash, created_at, expires_at,
+                      last_used_at, last_used_ip, scopes, house_id
             "#,
             user_id,
-            name,
-            token_hash
+            token_data.name,
+            public_id,
+            token_hash,
+            token_data.expires_at,
+            &scopes,
+            token_data.house_id
         )
         .fetch_one(&self.pool)
         .await?;
@@ -48,7 +75,8 @@ impl ApiTokensRepositoryTrait for ApiTokensRepository {
         let tokens = sqlx::query_as!(
             ApiToken,
             r#"
-            SELECT id, user_id, name, token_hash, created_at
+            SELECT id, user_id, name, public_id, token_hash, created_at, expires_at,
+                   last_used_at, last_used_ip, scopes, house_id
             FROM api_tokens
             WHERE user_id = $1
             ORDER BY created_at DESC
@@ -60,12 +88,70 @@ impl ApiTokensRepositoryTrait for ApiTokensRepository {
 
         Ok(tokens)
     }
+
+    async fn find_by_public_id(&self, public_id: &str) -> Result<Option<ApiToken>> {
+        let token = sqlx::query_as!(
+            ApiToken,
+            r#"
+            SELECT id, user_id, name, public_id, token_hash, created_at, expires_at,
+                   last_used_at, last_used_ip, scopes, house_id
+            FROM api_tokens
+            WHERE public_id = $1
+            "#,
+            public_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn delete(&self, id: i64, user_id: i64) -> Result<()> {
+        let rows_affected = sqlx::query!(
+            r#"
+            DELETE FROM api_tokens
+            WHERE id = $1 AND user_id = $2
+            "#,
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(AppError::NotFound(format!(
+                "API token with id {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn record_usage(&self, id: i64, ip: Option<String>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE api_tokens
+            SET last_used_at = NOW(), last_used_ip = $2
+            WHERE id = $1
+            "#,
+            id,
+            ip
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::api_tokens::ApiTokenScope;
     use sqlx::PgPool;
+    use uuid::Uuid;
 
     // Helper function to create a test pool (in-memory or real, depending on setup)
     async fn create_test_pool() -> PgPool {
@@ -82,42 +168,74 @@ mod tests {
         pool
     }
 
+    // Helper function to insert a user the tokens can belong to
+    async fn create_test_user(pool: &PgPool) -> i64 {
+        let email = format!("api_token_repo_{}@example.com", Uuid::new_v4());
+        let phone = format!("+{}", &Uuid::new_v4().as_u128().to_string()[..12]);
+
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (first_name, last_name, email, password_hash, phone)
+            VALUES ('Test', 'User', $1, 'hash', $2)
+            RETURNING id
+            "#,
+            email,
+            phone
+        )
+        .fetch_one(pool)
+        .await
+        .expect("Failed to create test user")
+    }
+
+    fn token_data(name: &str) -> CreateApiToken {
+        CreateApiToken {
+            name: name.to_string(),
+            expires_at: None,
+            scopes: Some(vec![ApiTokenScope::HousesAdmin]),
+            house_id: None,
+        }
+    }
+
     #[sqlx::test]
     async fn test_create_api_token() {
         let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
         let repo = ApiTokensRepository::new(pool);
 
-        let user_id = 1;
         let name = "test_token".to_string();
+        let public_id = "publicid0001".to_string();
         let token_hash = "hashed_token".to_string();
 
         let created_token = repo
-            .create(user_id, &name, &token_hash)
+            .create(user_id, &public_id, &token_hash, &token_data(&name))
             .await
             .expect("Failed to create API token");
 
         assert_eq!(created_token.user_id, user_id);
         assert_eq!(created_token.name, name);
+        assert_eq!(created_token.public_id, Some(public_id));
         assert_eq!(created_token.token_hash, token_hash);
+        assert_eq!(created_token.scopes, vec!["houses:admin".to_string()]);
+        assert_eq!(created_token.house_id, None);
         assert!(!created_token.id.to_string().is_empty());
     }
 
     #[sqlx::test]
     async fn test_find_by_user_id() {
         let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
         let repo = ApiTokensRepository::new(pool);
 
-        let user_id = 2; // Use a different user ID for this test
         let name1 = "token1".to_string();
         let token_hash1 = "hash1".to_string();
         let name2 = "token2".to_string();
         let token_hash2 = "hash2".to_string();
 
         // Create some tokens for the user
-        repo.create(user_id, &name1, &token_hash1)
+        repo.create(user_id, "publicid0002", &token_hash1, &token_data(&name1))
             .await
             .expect("Failed to create token1");
-        repo.create(user_id, &name2, &token_hash2)
+        repo.create(user_id, "publicid0003", &token_hash2, &token_data(&name2))
             .await
             .expect("Failed to create token2");
 
@@ -130,4 +248,77 @@ mod tests {
         assert_eq!(tokens[0].name, name2); // Ordered by created_at DESC
         assert_eq!(tokens[1].name, name1);
     }
+
+    #[sqlx::test]
+    async fn test_find_by_public_id() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let repo = ApiTokensRepository::new(pool);
+
+        repo.create(user_id, "publicid0004", "hash4", &token_data("token"))
+            .await
+            .expect("Failed to create token");
+
+        let found = repo
+            .find_by_public_id("publicid0004")
+            .await
+            .expect("Failed to find token by public ID");
+        assert_eq!(found.map(|t| t.user_id), Some(user_id));
+
+        let missing = repo
+            .find_by_public_id("doesnotexist")
+            .await
+            .expect("Failed to query missing token");
+        assert!(missing.is_none());
+    }
+
+    #[sqlx::test]
+    async fn test_delete() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let other_user_id = create_test_user(&pool).await;
+        let repo = ApiTokensRepository::new(pool);
+
+        let token = repo
+            .create(user_id, "publicid0005", "hash5", &token_data("token"))
+            .await
+            .expect("Failed to create token");
+
+        let result = repo.delete(token.id, other_user_id).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        repo.delete(token.id, user_id)
+            .await
+            .expect("Failed to delete token");
+        assert!(repo
+            .find_by_public_id("publicid0005")
+            .await
+            .expect("Failed to query token")
+            .is_none());
+    }
+
+    #[sqlx::test]
+    async fn test_record_usage() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+        let repo = ApiTokensRepository::new(pool);
+
+        let token = repo
+            .create(user_id, "publicid0006", "hash6", &token_data("token"))
+            .await
+            .expect("Failed to create token");
+        assert!(token.last_used_at.is_none());
+
+        repo.record_usage(token.id, Some("10.0.0.1".to_string()))
+            .await
+            .expect("Failed to record usage");
+
+        let used = repo
+            .find_by_public_id("publicid0006")
+            .await
+            .expect("Failed to query token")
+            .unwrap();
+        assert!(used.last_used_at.is_some());
+        assert_eq!(used.last_used_ip.as_deref(), Some("10.0.0.1"));
+    }
 }
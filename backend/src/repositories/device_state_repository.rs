@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use mockall::automock;
+use sqlx::{types::Json, PgPool};
+
+use crate::{
+    errors::{AppError, Result},
+    models::device_state::{DeviceState, StateValues},
+};
+
+#[automock]
+#[async_trait]
+pub trait DeviceStateRepositoryTrait {
+    /// `None` until the state of the device is first written.
+    async fn get_state(&self, device_id: i64) -> Result<Option<DeviceState>>;
+    /// Merges the values into the state, removing those set to `null`, and
+    /// bumps its version. Fails with a conflict if `expected_version` is given
+    /// and the state has another version.
+    async fn update_state(
+        &self,
+        device_id: i64,
+        desired: Option<StateValues>,
+        reported: Option<StateValues>,
+        expected_version: Option<i64>,
+    ) -> Result<DeviceState>;
+}
+
+#[derive(Clone)]
+pub struct DeviceStateRepository {
+    pool: PgPool,
+}
+
+impl DeviceStateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DeviceStateRepositoryTrait for DeviceStateRepository {
+    async fn get_state(&self, device_id: i64) -> Result<Option<DeviceState>> {
+        let result = sqlx::query!(
+            r#"
+            SELECT device_id, desired as "desired: Json<StateValues>",
+                reported as "reported: Json<StateValues>", version,
+                desired_updated_at, reported_updated_at
+            FROM device_states
+            WHERE device_id = $1
+            "#,
+            device_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result.map(|row| DeviceState {
+            device_id: row.device_id,
+            desired: row.desired.0,
+            reported: row.reported.0,
+            version: row.version,
+            desired_updated_at: row.desired_updated_at,
+            reported_updated_at: row.reported_updated_at,
+        }))
+    }
+
+    async fn update_state(
+        &self,
+        device_id: i64,
+        desired: Option<StateValues>,
+        reported: Option<StateValues>,
+        expected_version: Option<i64>,
+    ) -> Result<DeviceState> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO device_states (device_id)
+            VALUES ($1)
+            ON CONFLICT (device_id) DO NOTHING
+            "#,
+            device_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let version = sqlx::query_scalar!(
+            "SELECT version FROM device_states WHERE device_id = $1 FOR UPDATE",
+            device_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if expected_version.is_some_and(|expected| expected != version) {
+            return Err(AppError::Conflict(format!(
+                "Device state has changed, its version is now {}",
+                version
+            )));
+        }
+
+        let row = sqlx::query!(
+            r#"
+            UPDATE device_states
+            SET
+                desired = CASE WHEN $2::jsonb IS NULL THEN desired
+                    ELSE jsonb_strip_nulls(desired || $2) END,
+                reported = CASE WHEN $3::jsonb IS NULL THEN reported
+                    ELSE jsonb_strip_nulls(reported || $3) END,
+                desired_updated_at = CASE WHEN $2::jsonb IS NULL THEN desired_updated_at
+                    ELSE NOW() END,
+                reported_updated_at = CASE WHEN $3::jsonb IS NULL THEN reported_updated_at
+                    ELSE NOW() END,
+                version = version + 1
+            WHERE device_id = $1
+            RETURNING device_id, desired as "desired: Json<StateValues>",
+                reported as "reported: Json<StateValues>", version,
+                desired_updated_at, reported_updated_at
+            "#,
+            device_id,
+            desired.map(Json) as Option<Json<StateValues>>,
+            reported.map(Json) as Option<Json<StateValues>>
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(DeviceState {
+            device_id: row.device_id,
+            desired: row.desired.0,
+            reported: row.reported.0,
+            version: row.version,
+            desired_updated_at: row.desired_updated_at,
+            reported_updated_at: row.reported_updated_at,
+        })
+    }
+}
@@ -4,7 +4,7 @@ use sqlx::PgPool;
 
 use crate::{
     errors::{AppError, Result},
-    models::houses::{House, NewHouse},
+    models::houses::{House, NewHouse, UnitSystem, UpdateHouse},
 };
 
 #[automock]
@@ -13,6 +13,7 @@ pub trait HouseRepositoryTrait {
     async fn create_house(&self, house: NewHouse) -> Result<House>;
     async fn get_house_by_id(&self, id: i64) -> Result<House>;
     async fn get_user_houses(&self, user_id: i64) -> Result<Vec<House>>;
+    async fn update_house(&self, id: i64, house: UpdateHouse) -> Result<House>;
     async fn delete_house(&self, id: i64) -> Result<()>;
     async fn find_house_by_address(&self, address: String) -> Result<Option<House>>;
 }
@@ -34,12 +35,23 @@ impl HouseRepositoryTrait for HouseRepository {
         let result = sqlx::query_as!(
             House,
             r#"
-            INSERT INTO houses (name, address)
-            VALUES ($1, $2)
-            RETURNING id, name, address, created_at, updated_at, type, description
+            INSERT INTO houses (
+                name, address, type, description, timezone, latitude, longitude,
+                unit_system, currency
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, name, address, created_at, updated_at, type, description, timezone,
+                latitude, longitude, unit_system as "unit_system: UnitSystem", currency
             "#,
             house.name,
-            house.address
+            house.address,
+            house.r#type,
+            house.description,
+            house.timezone,
+            house.latitude,
+            house.longitude,
+            house.unit_system.as_str(),
+            house.currency
         )
         .fetch_one(&self.pool)
         .await?;
@@ -51,7 +63,9 @@ impl HouseRepositoryTrait for HouseRepository {
         let result = sqlx::query_as!(
             House,
             r#"
-            SELECT id, name, address, created_at, updated_at, type, description FROM houses
+            SELECT id, name, address, created_at, updated_at, type, description, timezone,
+                latitude, longitude, unit_system as "unit_system: UnitSystem", currency
+            FROM houses
             WHERE id = ($1)
             "#,
             id
@@ -66,7 +80,8 @@ impl HouseRepositoryTrait for HouseRepository {
         let result = sqlx::query_as!(
             House,
             r#"
-            SELECT id, name, address, created_at, updated_at, type, description
+            SELECT id, name, address, created_at, updated_at, type, description, timezone,
+                latitude, longitude, unit_system as "unit_system: UnitSystem", currency
             FROM houses h
             JOIN user_houses uh ON h.id = uh.house_id
             WHERE uh.user_id = ($1)
@@ -79,6 +94,46 @@ impl HouseRepositoryTrait for HouseRepository {
         Ok(result)
     }
 
+    async fn update_house(&self, id: i64, house: UpdateHouse) -> Result<House> {
+        let result = sqlx::query_as!(
+            House,
+            r#"
+            UPDATE houses
+            SET
+                name = COALESCE($1, name),
+                address = COALESCE($2, address),
+                type = COALESCE($3, type),
+                description = COALESCE($4, description),
+                timezone = COALESCE($5, timezone),
+                latitude = COALESCE($6, latitude),
+                longitude = COALESCE($7, longitude),
+                unit_system = COALESCE($8, unit_system),
+                currency = COALESCE($9, currency)
+            WHERE id = $10
+            RETURNING id, name, address, created_at, updated_at, type, description, timezone,
+                latitude, longitude, unit_system as "unit_system: UnitSystem", currency
+            "#,
+            house.name,
+            house.address,
+            house.r#type,
+            house.description,
+            house.timezone,
+            house.latitude,
+            house.longitude,
+            house.unit_system.map(|unit_system| unit_system.as_str()),
+            house.currency,
+            id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => AppError::NotFound("House not found".to_string()),
+            _ => AppError::DatabaseError(e),
+        })?;
+
+        Ok(result)
+    }
+
     async fn delete_house(&self, id: i64) -> Result<()> {
         let rows_affected = sqlx::query!("DELETE FROM houses WHERE id = $1", id)
             .execute(&self.pool)
@@ -96,7 +151,8 @@ impl HouseRepositoryTrait for HouseRepository {
         let result = sqlx::query_as!(
             House,
             r#"
-            SELECT id, name, address, created_at, updated_at, type, description
+            SELECT id, name, address, created_at, updated_at, type, description, timezone,
+                latitude, longitude, unit_system as "unit_system: UnitSystem", currency
             FROM houses
             WHERE address = ($1)
             "#,
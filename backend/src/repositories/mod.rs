@@ -20,3 +20,41 @@ pub use device_metrics_repository::{DeviceMetricsRepository, DeviceMetricsReposi
 
 pub mod api_tokens_repository;
 pub use api_tokens_repository::{ApiTokensRepository, ApiTokensRepositoryTrait};
+
+pub mod sessions_repository;
+pub use sessions_repository::{SessionsRepository, SessionsRepositoryTrait};
+
+pub mod account_tokens_repository;
+pub use account_tokens_repository::{AccountTokensRepository, AccountTokensRepositoryTrait};
+
+pub mod outbox_repository;
+pub use outbox_repository::{OutboxRepository, OutboxRepositoryTrait};
+
+pub mod two_factor_repository;
+pub use two_factor_repository::{TwoFactorRepository, TwoFactorRepositoryTrait};
+
+pub mod login_attempts_repository;
+pub use login_attempts_repository::{LoginAttemptsRepository, LoginAttemptsRepositoryTrait};
+
+pub mod stats_repository;
+pub use stats_repository::{StatsRepository, StatsRepositoryTrait};
+
+pub mod auth_events_repository;
+pub use auth_events_repository::{AuthEventsRepository, AuthEventsRepositoryTrait};
+
+pub mod house_invitations_repository;
+pub use house_invitations_repository::{
+    HouseInvitationsRepository, HouseInvitationsRepositoryTrait,
+};
+
+pub mod house_transfers_repository;
+pub use house_transfers_repository::{HouseTransfersRepository, HouseTransfersRepositoryTrait};
+
+pub mod zones_repository;
+pub use zones_repository::{ZonesRepository, ZonesRepositoryTrait};
+
+pub mod device_state_repository;
+pub use device_state_repository::{DeviceStateRepository, DeviceStateRepositoryTrait};
+
+pub mod device_commands_repository;
+pub use device_commands_repository::{DeviceCommandsRepository, DeviceCommandsRepositoryTrait};
@@ -0,0 +1,40 @@
+use crate::{errors::Result, models::mail::EmailMessage};
+use async_trait::async_trait;
+use mockall::automock;
+use sqlx::PgPool;
+
+#[automock]
+#[async_trait]
+pub trait OutboxRepositoryTrait {
+    async fn create(&self, message: &EmailMessage) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct OutboxRepository {
+    pool: PgPool,
+}
+
+impl OutboxRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OutboxRepositoryTrait for OutboxRepository {
+    async fn create(&self, message: &EmailMessage) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO outbox (recipient, subject, body)
+            VALUES ($1, $2, $3)
+            "#,
+            message.to,
+            message.subject,
+            message.body
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use sqlx::PgPool;
 
 use crate::{
     errors::{AppError, Result},
-    models::rooms::{NewRoom, Room},
+    models::rooms::{NewRoom, Room, UpdateRoom},
 };
 
 #[automock]
@@ -12,6 +12,7 @@ use crate::{
 pub trait RoomsRepositoryTrait {
     async fn get_house_rooms(&self, house_id: i64) -> Result<Vec<Room>>;
     async fn create_house_room(&self, house_id: i64, room: NewRoom) -> Result<Room>;
+    async fn update_room(&self, room_id: i64, room: UpdateRoom) -> Result<Room>;
     async fn delete_room(&self, room_id: i64) -> Result<()>;
     async fn get_room(&self, room_id: i64) -> Result<Room>;
 }
@@ -33,7 +34,7 @@ impl RoomsRepositoryTrait for RoomsRepository {
         let result = sqlx::query_as!(
             Room,
             r#"
-            SELECT id, house_id, name, room_type, created_at, updated_at
+            SELECT id, house_id, name, room_type, zone_id, created_at, updated_at
             FROM rooms
             WHERE house_id = ($1)
             "#,
@@ -49,13 +50,14 @@ impl RoomsRepositoryTrait for RoomsRepository {
         let result = sqlx::query_as!(
             Room,
             r#"
-            INSERT INTO rooms (house_id, name, room_type)
-            VALUES ($1, $2, $3)
-            RETURNING id, house_id, name, room_type, created_at, updated_at
+            INSERT INTO rooms (house_id, name, room_type, zone_id)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, house_id, name, room_type, zone_id, created_at, updated_at
             "#,
             house_id,
             new_room.name,
-            new_room.room_type
+            new_room.room_type,
+            new_room.zone_id
         )
         .fetch_one(&self.pool)
         .await?;
@@ -63,6 +65,34 @@ impl RoomsRepositoryTrait for RoomsRepository {
         Ok(result)
     }
 
+    async fn update_room(&self, room_id: i64, room: UpdateRoom) -> Result<Room> {
+        let result = sqlx::query_as!(
+            Room,
+            r#"
+            UPDATE rooms
+            SET
+                name = COALESCE($1, name),
+                room_type = COALESCE($2, room_type),
+                zone_id = CASE WHEN $3 THEN $4 ELSE zone_id END
+            WHERE id = $5
+            RETURNING id, house_id, name, room_type, zone_id, created_at, updated_at
+            "#,
+            room.name,
+            room.room_type,
+            room.zone_id.is_some(),
+            room.zone_id.flatten(),
+            room_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => AppError::NotFound("Room not found".to_string()),
+            _ => AppError::DatabaseError(e),
+        })?;
+
+        Ok(result)
+    }
+
     async fn delete_room(&self, room_id: i64) -> Result<()> {
         let rows_affected = sqlx::query!("DELETE FROM rooms WHERE id = $1", room_id)
             .execute(&self.pool)
@@ -80,7 +110,7 @@ impl RoomsRepositoryTrait for RoomsRepository {
         let result = sqlx::query_as!(
             Room,
             r#"
-            SELECT id, house_id, name, room_type, created_at, updated_at
+            SELECT id, house_id, name, room_type, zone_id, created_at, updated_at
             FROM rooms
             WHERE id = ($1)
             "#,
@@ -1,19 +1,50 @@
 use async_trait::async_trait;
+use chrono_tz::Tz;
 use mockall::automock;
-use sqlx::PgPool;
+use sqlx::{types::Json, PgPool};
 
 use crate::{
-    errors::Result,
-    models::{houses::House, user_houses::UserHouse},
+    errors::{AppError, Result},
+    models::{
+        houses::{House, UnitSystem},
+        user_houses::{
+            AccessWindow, ExpiredMember, HouseMember, HouseMembership, HouseRole, MemberAccess,
+            UserHouse,
+        },
+    },
 };
 
 #[automock]
 #[async_trait]
 pub trait UserHousesRepositoryTrait {
-    async fn add_house_to_user(&self, user_id: i64, house_id: i64) -> Result<UserHouse>;
-    async fn user_has_access_to_house(&self, house_id: i64, user_id: i64) -> Result<bool>;
+    async fn add_house_to_user(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        role: HouseRole,
+    ) -> Result<UserHouse>;
+    /// Role of the user in the house, `None` if they are not a member.
+    async fn get_user_role(&self, house_id: i64, user_id: i64) -> Result<Option<HouseRole>>;
+    /// Role and access limits of the user in the house, `None` if they are not
+    /// a member.
+    async fn get_membership(&self, house_id: i64, user_id: i64) -> Result<Option<HouseMembership>>;
+    async fn update_member_access(
+        &self,
+        house_id: i64,
+        user_id: i64,
+        access: &MemberAccess,
+    ) -> Result<()>;
+    /// Removes the memberships whose access has ended. Owners are never
+    /// removed.
+    async fn remove_expired_members(&self) -> Result<Vec<ExpiredMember>>;
     async fn get_house_by_device_id(&self, device_id: i64) -> Result<House>;
+    /// Members of the house, owners first.
+    async fn list_members(&self, house_id: i64) -> Result<Vec<HouseMember>>;
+    /// Removes the user from the house. Fails if they are its last owner, so a
+    /// house always keeps at least one owner.
+    async fn remove_member(&self, house_id: i64, user_id: i64) -> Result<()>;
     async fn get_house_by_room_id(&self, room_id: i64) -> Result<House>;
+    async fn get_room_id_by_device_id(&self, device_id: i64) -> Result<i64>;
 }
 
 #[derive(Clone)]
@@ -29,16 +60,22 @@ impl UserHousesRepository {
 
 #[async_trait]
 impl UserHousesRepositoryTrait for UserHousesRepository {
-    async fn add_house_to_user(&self, user_id: i64, house_id: i64) -> Result<UserHouse> {
+    async fn add_house_to_user(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        role: HouseRole,
+    ) -> Result<UserHouse> {
         let result = sqlx::query_as!(
             UserHouse,
             r#"
-            INSERT INTO user_houses (user_id, house_id)
-            VALUES ($1, $2)
-            RETURNING user_id, house_id
+            INSERT INTO user_houses (user_id, house_id, role)
+            VALUES ($1, $2, $3::TEXT::house_role)
+            RETURNING user_id, house_id, role as "role: HouseRole"
             "#,
             user_id,
-            house_id
+            house_id,
+            role.to_string()
         )
         .fetch_one(&self.pool)
         .await?;
@@ -46,11 +83,10 @@ impl UserHousesRepositoryTrait for UserHousesRepository {
         Ok(result)
     }
 
-    async fn user_has_access_to_house(&self, house_id: i64, user_id: i64) -> Result<bool> {
-        let result = sqlx::query_as!(
-            UserHouse,
+    async fn get_user_role(&self, house_id: i64, user_id: i64) -> Result<Option<HouseRole>> {
+        let role = sqlx::query!(
             r#"
-            SELECT * FROM user_houses
+            SELECT role as "role: HouseRole" FROM user_houses
             WHERE user_id = $1 AND house_id = $2
             "#,
             user_id,
@@ -58,16 +94,170 @@ impl UserHousesRepositoryTrait for UserHousesRepository {
         )
         .fetch_optional(&self.pool)
         .await?
-        .is_some();
+        .map(|row| row.role);
 
-        Ok(result)
+        Ok(role)
+    }
+
+    async fn get_membership(&self, house_id: i64, user_id: i64) -> Result<Option<HouseMembership>> {
+        let membership = sqlx::query!(
+            r#"
+            SELECT uh.role as "role: HouseRole", uh.valid_from, uh.valid_until,
+                   uh.schedule as "schedule: Json<Vec<AccessWindow>>", uh.room_ids, h.timezone
+            FROM user_houses uh
+            JOIN houses h ON h.id = uh.house_id
+            WHERE uh.user_id = $1 AND uh.house_id = $2
+            "#,
+            user_id,
+            house_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|row| HouseMembership {
+            role: row.role,
+            access: MemberAccess {
+                valid_from: row.valid_from,
+                valid_until: row.valid_until,
+                schedule: row.schedule.map(|schedule| schedule.0),
+                room_ids: row.room_ids,
+            },
+            // Timezones are validated when a house is saved
+            timezone: row.timezone.parse().unwrap_or(Tz::UTC),
+        });
+
+        Ok(membership)
+    }
+
+    async fn update_member_access(
+        &self,
+        house_id: i64,
+        user_id: i64,
+        access: &MemberAccess,
+    ) -> Result<()> {
+        let rows_affected = sqlx::query!(
+            r#"
+            UPDATE user_houses
+            SET valid_from = $3, valid_until = $4, schedule = $5, room_ids = $6
+            WHERE house_id = $1 AND user_id = $2
+            "#,
+            house_id,
+            user_id,
+            access.valid_from,
+            access.valid_until,
+            access.schedule.as_ref().map(Json) as _,
+            access.room_ids.as_deref()
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(AppError::NotFound("Member not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn remove_expired_members(&self) -> Result<Vec<ExpiredMember>> {
+        let members = sqlx::query_as!(
+            ExpiredMember,
+            r#"
+            DELETE FROM user_houses uh
+            USING users u
+            WHERE u.id = uh.user_id AND uh.valid_until <= NOW() AND uh.role <> 'owner'
+            RETURNING uh.house_id, uh.user_id, u.first_name, u.last_name, uh.valid_until as "valid_until!"
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(members)
+    }
+
+    async fn list_members(&self, house_id: i64) -> Result<Vec<HouseMember>> {
+        let members = sqlx::query!(
+            r#"
+            SELECT u.id as user_id, u.first_name, u.last_name, u.email, uh.role as "role: HouseRole", uh.joined_at,
+                uh.valid_from, uh.valid_until, uh.schedule as "schedule: Json<Vec<AccessWindow>>", uh.room_ids
+            FROM user_houses uh
+            JOIN users u ON u.id = uh.user_id
+            WHERE uh.house_id = $1
+            ORDER BY uh.role, uh.joined_at, u.id
+            "#,
+            house_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| HouseMember {
+            user_id: row.user_id,
+            first_name: row.first_name,
+            last_name: row.last_name,
+            email: row.email,
+            role: row.role,
+            joined_at: row.joined_at,
+            access: MemberAccess {
+                valid_from: row.valid_from,
+                valid_until: row.valid_until,
+                schedule: row.schedule.map(|schedule| schedule.0),
+                room_ids: row.room_ids,
+            },
+        })
+        .collect();
+
+        Ok(members)
+    }
+
+    async fn remove_member(&self, house_id: i64, user_id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        // Locking the owners keeps concurrent removals from leaving the house
+        // without one
+        let owners = sqlx::query_scalar!(
+            r#"
+            SELECT user_id FROM user_houses
+            WHERE house_id = $1 AND role = 'owner'
+            FOR UPDATE
+            "#,
+            house_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if owners == [user_id] {
+            return Err(AppError::BadRequest(
+                "A house must keep at least one owner".to_string(),
+            ));
+        }
+
+        let rows_affected = sqlx::query!(
+            r#"
+            DELETE FROM user_houses
+            WHERE house_id = $1 AND user_id = $2
+            "#,
+            house_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(AppError::NotFound("Member not found".to_string()));
+        }
+
+        tx.commit().await?;
+
+        Ok(())
     }
 
     async fn get_house_by_device_id(&self, device_id: i64) -> Result<House> {
         let house = sqlx::query_as!(
             House,
             r#"
-            SELECT h.*
+            SELECT h.id, h.name, h.address, h.type, h.description, h.timezone, h.latitude,
+                h.longitude, h.unit_system as "unit_system: UnitSystem", h.currency,
+                h.created_at, h.updated_at
             FROM houses h
             JOIN rooms r ON h.id = r.house_id
             JOIN devices d ON r.id = d.room_id
@@ -75,8 +265,9 @@ impl UserHousesRepositoryTrait for UserHousesRepository {
             "#,
             device_id
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Device not found".to_string()))?;
 
         Ok(house)
     }
@@ -85,16 +276,28 @@ impl UserHousesRepositoryTrait for UserHousesRepository {
         let house = sqlx::query_as!(
             House,
             r#"
-            SELECT h.*
+            SELECT h.id, h.name, h.address, h.type, h.description, h.timezone, h.latitude,
+                h.longitude, h.unit_system as "unit_system: UnitSystem", h.currency,
+                h.created_at, h.updated_at
             FROM houses h
             JOIN rooms r ON h.id = r.house_id
             WHERE r.id = $1
             "#,
             room_id
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Room not found".to_string()))?;
 
         Ok(house)
     }
+
+    async fn get_room_id_by_device_id(&self, device_id: i64) -> Result<i64> {
+        let room_id = sqlx::query_scalar!("SELECT room_id FROM devices WHERE id = $1", device_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Device not found".to_string()))?;
+
+        Ok(room_id)
+    }
 }
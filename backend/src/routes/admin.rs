@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use crate::{
+    handlers::admin::{
+        disable_user, enable_user, force_logout, get_system_stats, list_auth_events, list_users,
+        update_user_role,
+    },
+    repositories::{AuthEventsRepository, SessionsRepository, StatsRepository, UserRepository},
+    services::{admin::AdminService, auth_events::AuthEventsService},
+    AppState,
+};
+use axum::{
+    routing::{get, post, put},
+    Router,
+};
+
+#[derive(Clone)]
+pub struct AdminRouterState {
+    pub admin_service: AdminService,
+    pub auth_events_service: AuthEventsService,
+}
+
+impl AdminRouterState {
+    pub fn new(app_state: AppState) -> Self {
+        let user_repository = Arc::new(UserRepository::new(app_state.db.pool.clone()));
+        let sessions_repository = Arc::new(SessionsRepository::new(app_state.db.pool.clone()));
+        let stats_repository = Arc::new(StatsRepository::new(app_state.db.pool.clone()));
+        let admin_service =
+            AdminService::new(user_repository, sessions_repository, stats_repository);
+        let auth_events_service = AuthEventsService::new(Arc::new(AuthEventsRepository::new(
+            app_state.db.pool.clone(),
+        )));
+
+        Self {
+            admin_service,
+            auth_events_service,
+        }
+    }
+}
+
+/// Admin routes. Every handler requires [`crate::middlewares::auth::RequireAdmin`].
+pub fn admin_router(app_state: AppState) -> Router {
+    let router_state = AdminRouterState::new(app_state);
+
+    Router::new()
+        .route("/users", get(list_users))
+        .route("/users/{id}/role", put(update_user_role))
+        .route("/users/{id}/disable", post(disable_user))
+        .route("/users/{id}/enable", post(enable_user))
+        .route("/users/{id}/logout", post(force_logout))
+        .route("/stats", get(get_system_stats))
+        .route("/auth-events", get(list_auth_events))
+        .with_state(router_state)
+}
@@ -1,10 +1,20 @@
 use crate::{
-    handlers::api_tokens::{create_api_token, get_api_tokens},
-    repositories::ApiTokensRepository,
-    services::api_tokens::ApiTokensService,
+    handlers::api_tokens::{create_api_token, delete_api_token, get_api_tokens},
+    middlewares::api_token_scope::reject_api_tokens,
+    repositories::{
+        user_houses_repository::UserHousesRepository, ApiTokensRepository, AuthEventsRepository,
+    },
+    services::{
+        access_control_service::AccessControlService, api_tokens::ApiTokensService,
+        auth_events::AuthEventsService,
+    },
     AppState,
 };
-use axum::{routing::post, Router};
+use axum::{
+    middleware,
+    routing::{delete, post},
+    Router,
+};
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -15,7 +25,16 @@ pub struct ApiTokensRouterState {
 impl ApiTokensRouterState {
     pub fn new(app_state: AppState) -> Self {
         let tokens_repository = Arc::new(ApiTokensRepository::new(app_state.db.pool.clone()));
-        let tokens_service = ApiTokensService::new(tokens_repository);
+        let user_houses_repository = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
+        let access_control_service = Arc::new(AccessControlService::new(user_houses_repository));
+        let tokens_service = ApiTokensService::new(
+            app_state.config.clone(),
+            tokens_repository,
+            access_control_service,
+            Arc::new(AuthEventsService::new(Arc::new(AuthEventsRepository::new(
+                app_state.db.pool.clone(),
+            )))),
+        );
 
         Self { tokens_service }
     }
@@ -26,5 +45,7 @@ pub fn api_tokens_router(app_state: AppState) -> Router {
 
     Router::new()
         .route("/", post(create_api_token).get(get_api_tokens))
+        .route("/{id}", delete(delete_api_token))
+        .route_layer(middleware::from_fn(reject_api_tokens))
         .with_state(router_state)
 }
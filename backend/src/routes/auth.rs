@@ -1,26 +1,67 @@
 use std::sync::Arc;
 
 use crate::{
-    handlers::auth::{login, register},
-    repositories::UserRepository,
-    services::auth::AuthService,
+    handlers::auth::{
+        forgot_password, jwks, login, login_two_factor, logout, refresh, register, reset_password,
+        verify_email,
+    },
+    repositories::{
+        AccountTokensRepository, AuthEventsRepository, LoginAttemptsRepository, SessionsRepository,
+        TwoFactorRepository, UserRepository,
+    },
+    services::{
+        account::AccountService, auth::AuthService, auth_events::AuthEventsService,
+        login_throttle::LoginThrottleService, mailer, two_factor::TwoFactorService,
+    },
     AppState,
 };
-use axum::{routing::post, Router};
+use axum::{
+    routing::{get, post},
+    Router,
+};
 
 #[derive(Clone)]
 pub struct AuthRouterState {
     pub auth_service: AuthService,
+    pub account_service: AccountService,
     pub app_state: AppState,
 }
 
 impl AuthRouterState {
     pub fn new(app_state: AppState) -> Self {
         let user_repository = Arc::new(UserRepository::new(app_state.db.pool.clone()));
-        let auth_service = AuthService::new(app_state.config.clone(), user_repository);
+        let sessions_repository = Arc::new(SessionsRepository::new(app_state.db.pool.clone()));
+        let auth_events_service = Arc::new(AuthEventsService::new(Arc::new(
+            AuthEventsRepository::new(app_state.db.pool.clone()),
+        )));
+        let account_tokens_repository =
+            Arc::new(AccountTokensRepository::new(app_state.db.pool.clone()));
+        let account_service = AccountService::new(
+            app_state.config.clone(),
+            user_repository.clone(),
+            account_tokens_repository,
+            sessions_repository.clone(),
+            mailer::from_app_state(&app_state),
+            auth_events_service.clone(),
+        );
+        let two_factor_repository = Arc::new(TwoFactorRepository::new(app_state.db.pool.clone()));
+        let two_factor_service =
+            TwoFactorService::new(user_repository.clone(), two_factor_repository);
+        let login_attempts_repository =
+            Arc::new(LoginAttemptsRepository::new(app_state.db.pool.clone()));
+        let auth_service = AuthService::new(
+            app_state.config.clone(),
+            user_repository,
+            sessions_repository,
+            Arc::new(two_factor_service),
+            Arc::new(LoginThrottleService::new(login_attempts_repository)),
+            auth_events_service,
+            app_state.jwt_keys.clone(),
+        );
 
         Self {
             auth_service,
+            account_service,
             app_state,
         }
     }
@@ -31,14 +72,21 @@ pub fn auth_router(app_state: AppState) -> Router {
 
     Router::new()
         .route("/auth/login", post(login))
+        .route("/auth/login/2fa", post(login_two_factor))
         .route("/auth/signup", post(register))
+        .route("/auth/refresh", post(refresh))
+        .route("/auth/logout", post(logout))
+        .route("/auth/password/forgot", post(forgot_password))
+        .route("/auth/password/reset", post(reset_password))
+        .route("/auth/email/verify", post(verify_email))
+        .route("/.well-known/jwks.json", get(jwks))
         .with_state(auth_router_state)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
+    use crate::config::{Config, JwtAlgorithm};
 
     fn create_test_config() -> Config {
         Config {
@@ -49,8 +97,18 @@ mod tests {
             db_user: "test".to_string(),
             db_pass: "test".to_string(),
             jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
             jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
             frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
         }
     }
 
@@ -66,10 +124,7 @@ mod tests {
 
         // Skip the actual test if we can't connect (which is expected in CI)
         if let Ok(pool) = pool {
-            let app_state = AppState {
-                db: crate::db::Database::new(pool),
-                config,
-            };
+            let app_state = AppState::new(crate::db::Database::new(pool), config);
 
             let router = auth_router(app_state);
 
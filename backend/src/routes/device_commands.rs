@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+
+use crate::{
+    handlers::device_commands::{
+        acknowledge_device_command, create_device_command, expire_device_command,
+        fail_device_command, fetch_device_commands, fetch_house_commands, get_device_commands,
+    },
+    middlewares::api_token_scope::{api_token_scope_middleware, RequiredScopes},
+    models::api_tokens::ApiTokenScope,
+    repositories::{
+        device_commands_repository::DeviceCommandsRepository,
+        user_houses_repository::UserHousesRepository, DeviceRepository,
+    },
+    services::{
+        access_control_service::{AccessControlService, AccessControlServiceTrait},
+        device_commands::{DeviceCommandsService, DeviceCommandsServiceTrait},
+    },
+    AppState,
+};
+
+#[derive(Clone)]
+pub struct DeviceCommandsRouterState {
+    pub device_commands_service: Arc<dyn DeviceCommandsServiceTrait + Send + Sync>,
+    pub access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+}
+
+impl DeviceCommandsRouterState {
+    pub fn new(app_state: AppState) -> Self {
+        let device_commands_repository =
+            Arc::new(DeviceCommandsRepository::new(app_state.db.pool.clone()));
+        let device_repository = Arc::new(DeviceRepository::new(app_state.db.pool.clone()));
+        let user_houses_repo = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
+        let access_control_service = Arc::new(AccessControlService::new(user_houses_repo));
+        let device_commands_service = Arc::new(DeviceCommandsService::new(
+            device_commands_repository,
+            device_repository,
+            access_control_service.clone(),
+        ));
+
+        Self {
+            device_commands_service,
+            access_control_service,
+        }
+    }
+
+    fn required_scopes(&self) -> RequiredScopes {
+        RequiredScopes::new(
+            ApiTokenScope::DevicesRead,
+            ApiTokenScope::DevicesWrite,
+            self.access_control_service.clone(),
+        )
+    }
+}
+
+pub fn device_commands_router(app_state: AppState) -> Router {
+    let device_commands_router_state = DeviceCommandsRouterState::new(app_state);
+
+    Router::new()
+        .route("/", post(create_device_command))
+        .route("/", get(get_device_commands))
+        .route("/fetch", post(fetch_device_commands))
+        .route("/{command_id}/ack", post(acknowledge_device_command))
+        .route("/{command_id}/fail", post(fail_device_command))
+        .route("/{command_id}/expire", post(expire_device_command))
+        .route_layer(middleware::from_fn_with_state(
+            device_commands_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
+        .with_state(Arc::new(device_commands_router_state))
+}
+
+pub fn house_commands_router(app_state: AppState) -> Router {
+    let device_commands_router_state = DeviceCommandsRouterState::new(app_state);
+
+    Router::new()
+        .route("/fetch", post(fetch_house_commands))
+        .route_layer(middleware::from_fn_with_state(
+            device_commands_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
+        .with_state(Arc::new(device_commands_router_state))
+}
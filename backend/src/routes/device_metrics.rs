@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
@@ -8,10 +9,14 @@ use axum::{
 use crate::{
     handlers::device_metrics::{
         create_metric, get_metrics, get_metrics_for_house, get_metrics_for_room,
+        get_metrics_for_zone,
     },
+    middlewares::api_token_scope::{api_token_scope_middleware, RequiredScopes},
+    models::api_tokens::ApiTokenScope,
     repositories::{
         device_metrics_repository::DeviceMetricsRepository,
-        user_houses_repository::UserHousesRepository,
+        user_houses_repository::UserHousesRepository, zones_repository::ZonesRepository,
+        DeviceRepository,
     },
     services::{
         access_control_service::{AccessControlService, AccessControlServiceTrait},
@@ -32,8 +37,12 @@ impl DeviceMetricsRouterState {
             Arc::new(DeviceMetricsRepository::new(app_state.db.pool.clone()));
         let user_houses_repo = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
         let access_control_service = Arc::new(AccessControlService::new(user_houses_repo));
+        let device_repository = Arc::new(DeviceRepository::new(app_state.db.pool.clone()));
+        let zones_repository = Arc::new(ZonesRepository::new(app_state.db.pool.clone()));
         let device_metrics_service = Arc::new(DeviceMetricsService::new(
             device_metrics_repository,
+            device_repository,
+            zones_repository,
             access_control_service.clone(),
         ));
 
@@ -42,6 +51,14 @@ impl DeviceMetricsRouterState {
             access_control_service,
         }
     }
+
+    fn required_scopes(&self) -> RequiredScopes {
+        RequiredScopes::new(
+            ApiTokenScope::MetricsRead,
+            ApiTokenScope::MetricsWrite,
+            self.access_control_service.clone(),
+        )
+    }
 }
 
 pub fn device_metrics_router(app_state: AppState) -> Router {
@@ -50,6 +67,10 @@ pub fn device_metrics_router(app_state: AppState) -> Router {
     Router::new()
         .route("/", post(create_metric))
         .route("/", get(get_metrics))
+        .route_layer(middleware::from_fn_with_state(
+            device_metrics_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
         .with_state(Arc::new(device_metrics_router_state))
 }
 
@@ -58,5 +79,13 @@ pub fn device_metrics_routes(app_state: AppState) -> Router {
     Router::new()
         .route("/houses/{house_id}/metrics", get(get_metrics_for_house))
         .route("/rooms/{room_id}/metrics", get(get_metrics_for_room))
+        .route(
+            "/houses/{house_id}/zones/{zone_id}/metrics",
+            get(get_metrics_for_zone),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            device_metrics_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
         .with_state(device_metrics_router_state)
 }
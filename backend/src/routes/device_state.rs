@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware,
+    routing::{get, patch},
+    Router,
+};
+
+use crate::{
+    handlers::device_state::{get_device_state, get_device_state_delta, update_device_state},
+    middlewares::api_token_scope::{api_token_scope_middleware, RequiredScopes},
+    models::api_tokens::ApiTokenScope,
+    repositories::{
+        device_state_repository::DeviceStateRepository,
+        user_houses_repository::UserHousesRepository, DeviceRepository,
+    },
+    services::{
+        access_control_service::{AccessControlService, AccessControlServiceTrait},
+        device_state::{DeviceStateService, DeviceStateServiceTrait},
+    },
+    AppState,
+};
+
+#[derive(Clone)]
+pub struct DeviceStateRouterState {
+    pub device_state_service: Arc<dyn DeviceStateServiceTrait + Send + Sync>,
+    pub access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+}
+
+impl DeviceStateRouterState {
+    pub fn new(app_state: AppState) -> Self {
+        let device_state_repository =
+            Arc::new(DeviceStateRepository::new(app_state.db.pool.clone()));
+        let device_repository = Arc::new(DeviceRepository::new(app_state.db.pool.clone()));
+        let user_houses_repo = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
+        let access_control_service = Arc::new(AccessControlService::new(user_houses_repo));
+        let device_state_service = Arc::new(DeviceStateService::new(
+            device_state_repository,
+            device_repository,
+            access_control_service.clone(),
+        ));
+
+        Self {
+            device_state_service,
+            access_control_service,
+        }
+    }
+
+    fn required_scopes(&self) -> RequiredScopes {
+        RequiredScopes::new(
+            ApiTokenScope::DevicesRead,
+            ApiTokenScope::DevicesWrite,
+            self.access_control_service.clone(),
+        )
+    }
+}
+
+pub fn device_state_router(app_state: AppState) -> Router {
+    let device_state_router_state = DeviceStateRouterState::new(app_state);
+
+    Router::new()
+        .route("/", get(get_device_state))
+        .route("/", patch(update_device_state))
+        .route("/delta", get(get_device_state_delta))
+        .route_layer(middleware::from_fn_with_state(
+            device_state_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
+        .with_state(Arc::new(device_state_router_state))
+}
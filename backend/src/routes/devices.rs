@@ -3,6 +3,7 @@ use std::sync::Arc;
 use axum::{
     extract::{FromRequestParts, Path},
     http::request::Parts,
+    middleware,
     routing::{delete, get, patch, post},
     Router,
 };
@@ -10,17 +11,23 @@ use axum::{
 use crate::{
     errors::AppError,
     handlers::devices::{
-        create_device, delete_device, get_device_by_id, get_devices_by_house_id,
-        get_devices_by_room_id, update_device,
+        create_device, delete_device, get_device_by_id, get_device_connectivity_events,
+        get_device_location_history, get_devices_by_house_id, get_devices_by_room_id,
+        get_devices_by_zone_id, list_device_types, move_device, record_device_heartbeat,
+        update_device,
     },
+    middlewares::api_token_scope::{api_token_scope_middleware, RequiredScopes},
+    models::api_tokens::ApiTokenScope,
     repositories::{
         rooms_repository::RoomsRepository, user_houses_repository::UserHousesRepository,
+        zones_repository::ZonesRepository,
     },
     routes::rooms::HouseAccess,
     services::{
         access_control_service::{AccessControlService, AccessControlServiceTrait},
         device::DeviceServiceTrait,
         rooms::{RoomsService, RoomsServiceTrait},
+        zones::{ZonesService, ZonesServiceTrait},
     },
     AppState,
 };
@@ -29,6 +36,7 @@ use crate::{
 pub struct DeviceRouterState {
     pub device_service: Arc<dyn DeviceServiceTrait + Send + Sync>,
     pub room_service: Arc<dyn RoomsServiceTrait + Send + Sync>,
+    pub zone_service: Arc<dyn ZonesServiceTrait + Send + Sync>,
     pub app_state: AppState,
     pub access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
 }
@@ -44,15 +52,42 @@ impl DeviceRouterState {
         let user_houses_repo = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
         let access_control_service = Arc::new(AccessControlService::new(user_houses_repo));
         let rooms_repository = Arc::new(RoomsRepository::new(app_state.db.pool.clone()));
-        let room_service = Arc::new(RoomsService::new(rooms_repository));
+        let zones_repository = Arc::new(ZonesRepository::new(app_state.db.pool.clone()));
+        let room_service = Arc::new(RoomsService::new(
+            rooms_repository,
+            zones_repository.clone(),
+        ));
+        let zone_service = Arc::new(ZonesService::new(
+            zones_repository,
+            access_control_service.clone(),
+        ));
 
         Self {
             device_service,
             room_service,
+            zone_service,
             app_state,
             access_control_service,
         }
     }
+
+    fn required_scopes(&self) -> RequiredScopes {
+        RequiredScopes::new(
+            ApiTokenScope::DevicesRead,
+            ApiTokenScope::DevicesWrite,
+            self.access_control_service.clone(),
+        )
+    }
+
+    /// Heartbeats are reported by gateways along with metrics, so they take the
+    /// metrics scopes.
+    fn heartbeat_scopes(&self) -> RequiredScopes {
+        RequiredScopes::new(
+            ApiTokenScope::MetricsRead,
+            ApiTokenScope::MetricsWrite,
+            self.access_control_service.clone(),
+        )
+    }
 }
 
 impl FromRequestParts<Arc<DeviceRouterState>> for HouseAccess {
@@ -84,11 +119,29 @@ impl FromRequestParts<Arc<DeviceRouterState>> for HouseAccess {
 pub fn devices_router(app_state: AppState) -> Router {
     let device_router_state = DeviceRouterState::new(app_state);
 
+    let heartbeat_routes = Router::new()
+        .route("/{device_id}/heartbeat", post(record_device_heartbeat))
+        .route_layer(middleware::from_fn_with_state(
+            device_router_state.heartbeat_scopes(),
+            api_token_scope_middleware,
+        ));
+
     Router::new()
         .route("/", post(create_device))
         .route("/{device_id}", get(get_device_by_id))
         .route("/{device_id}", patch(update_device))
         .route("/{device_id}", delete(delete_device))
+        .route("/{device_id}/move", post(move_device))
+        .route("/{device_id}/locations", get(get_device_location_history))
+        .route(
+            "/{device_id}/connectivity",
+            get(get_device_connectivity_events),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            device_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
+        .merge(heartbeat_routes)
         .with_state(Arc::new(device_router_state))
 }
 
@@ -97,6 +150,10 @@ pub fn house_devices_router(app_state: AppState) -> Router {
 
     Router::new()
         .route("/", get(get_devices_by_house_id))
+        .route_layer(middleware::from_fn_with_state(
+            device_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
         .with_state(Arc::new(device_router_state))
 }
 
@@ -105,5 +162,25 @@ pub fn room_devices_router(app_state: AppState) -> Router {
 
     Router::new()
         .route("/", get(get_devices_by_room_id))
+        .route_layer(middleware::from_fn_with_state(
+            device_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
         .with_state(Arc::new(device_router_state))
 }
+
+pub fn zone_devices_router(app_state: AppState) -> Router {
+    let device_router_state = DeviceRouterState::new(app_state);
+
+    Router::new()
+        .route("/", get(get_devices_by_zone_id))
+        .route_layer(middleware::from_fn_with_state(
+            device_router_state.required_scopes(),
+            api_token_scope_middleware,
+        ))
+        .with_state(Arc::new(device_router_state))
+}
+
+pub fn device_types_router() -> Router {
+    Router::new().route("/", get(list_device_types))
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
+
+use crate::{
+    handlers::house_members::{
+        accept_invitation, accept_invitation_code, create_invitation, decline_invitation,
+        leave_house, list_house_invitations, list_members, list_received_invitations,
+        remove_member, revoke_invitation, update_member_access,
+    },
+    middlewares::api_token_scope::reject_api_tokens,
+    repositories::{
+        HouseInvitationsRepository, HouseRepository, UserHousesRepository, UserRepository,
+    },
+    services::{
+        access_control_service::AccessControlService,
+        house_members::{HouseMembersService, HouseMembersServiceTrait},
+        mailer,
+    },
+    AppState,
+};
+
+#[derive(Clone)]
+pub struct HouseMembersRouterState {
+    pub house_members_service: Arc<dyn HouseMembersServiceTrait + Send + Sync>,
+}
+
+impl HouseMembersRouterState {
+    pub fn new(app_state: AppState) -> Self {
+        let user_houses_repository = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
+        let house_members_service = Arc::new(HouseMembersService::new(
+            app_state.config.clone(),
+            user_houses_repository.clone(),
+            Arc::new(HouseInvitationsRepository::new(app_state.db.pool.clone())),
+            Arc::new(HouseRepository::new(app_state.db.pool.clone())),
+            Arc::new(UserRepository::new(app_state.db.pool.clone())),
+            Arc::new(AccessControlService::new(user_houses_repository)),
+            mailer::from_app_state(&app_state),
+        ));
+
+        Self {
+            house_members_service,
+        }
+    }
+}
+
+/// Members and invitations of a house, nested under `/houses/{house_id}`.
+///
+/// Sharing a house is limited to users signed in with a session; API tokens are
+/// rejected.
+pub fn house_members_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/members", get(list_members))
+        .route("/members/{user_id}", delete(remove_member))
+        .route("/members/{user_id}/access", put(update_member_access))
+        .route("/leave", post(leave_house))
+        .route(
+            "/invitations",
+            get(list_house_invitations).post(create_invitation),
+        )
+        .route("/invitations/{id}", delete(revoke_invitation))
+        .route_layer(middleware::from_fn(reject_api_tokens))
+        .with_state(HouseMembersRouterState::new(app_state))
+}
+
+/// Invitations received by the current user.
+pub fn invitations_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_received_invitations))
+        .route("/accept", post(accept_invitation_code))
+        .route("/{id}/accept", post(accept_invitation))
+        .route("/{id}/decline", post(decline_invitation))
+        .route_layer(middleware::from_fn(reject_api_tokens))
+        .with_state(HouseMembersRouterState::new(app_state))
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+
+use crate::{
+    handlers::house_transfers::{
+        accept_transfer, cancel_transfer, create_transfer, decline_transfer,
+        list_received_transfers, list_transfers,
+    },
+    middlewares::api_token_scope::reject_api_tokens,
+    repositories::{
+        HouseRepository, HouseTransfersRepository, UserHousesRepository, UserRepository,
+    },
+    services::{
+        access_control_service::AccessControlService,
+        house_transfers::{HouseTransfersService, HouseTransfersServiceTrait},
+        mailer,
+    },
+    AppState,
+};
+
+#[derive(Clone)]
+pub struct HouseTransfersRouterState {
+    pub house_transfers_service: Arc<dyn HouseTransfersServiceTrait + Send + Sync>,
+}
+
+impl HouseTransfersRouterState {
+    pub fn new(app_state: AppState) -> Self {
+        let user_houses_repository = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
+        let house_transfers_service = Arc::new(HouseTransfersService::new(
+            Arc::new(HouseTransfersRepository::new(app_state.db.pool.clone())),
+            user_houses_repository.clone(),
+            Arc::new(HouseRepository::new(app_state.db.pool.clone())),
+            Arc::new(UserRepository::new(app_state.db.pool.clone())),
+            Arc::new(AccessControlService::new(user_houses_repository)),
+            mailer::from_app_state(&app_state),
+        ));
+
+        Self {
+            house_transfers_service,
+        }
+    }
+}
+
+/// Ownership transfers of a house, nested under `/houses/{house_id}/transfers`.
+///
+/// Like sharing a house, transfers are limited to users signed in with a
+/// session; API tokens are rejected.
+pub fn house_transfers_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_transfers).post(create_transfer))
+        .route("/{id}/cancel", post(cancel_transfer))
+        .route_layer(middleware::from_fn(reject_api_tokens))
+        .with_state(HouseTransfersRouterState::new(app_state))
+}
+
+/// Transfers to the current user.
+pub fn transfers_router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_received_transfers))
+        .route("/{id}/accept", post(accept_transfer))
+        .route("/{id}/decline", post(decline_transfer))
+        .route_layer(middleware::from_fn(reject_api_tokens))
+        .with_state(HouseTransfersRouterState::new(app_state))
+}
@@ -1,15 +1,20 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRequestParts, Path},
     http::request::Parts,
-    routing::{delete, get, post},
+    middleware,
+    routing::{delete, get, patch, post},
     Router,
 };
 
 use crate::{
     errors::AppError,
-    handlers::houses::{create_house, delete_house, get_user_house_by_id, get_user_houses},
+    handlers::houses::{
+        create_house, delete_house, get_user_house_by_id, get_user_houses, update_house,
+    },
+    middlewares::api_token_scope::{api_token_scope_middleware, RequiredScopes},
+    models::api_tokens::ApiTokenScope,
     repositories::{user_houses_repository::UserHousesRepository, HouseRepository},
     routes::rooms::HouseAccess,
     services::{
@@ -52,11 +57,9 @@ impl FromRequestParts<HousesRouterState> for HouseAccess {
             .copied()
             .ok_or_else(|| AppError::AuthorizationError("Not authenticated".to_string()))?;
 
-        let house_id = parts
-            .extensions
-            .get::<i64>()
-            .copied()
-            .ok_or_else(|| AppError::BadRequest("Invalid house id".to_string()))?;
+        let Path(house_id) = Path::<i64>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| AppError::BadRequest("Invalid house id".to_string()))?;
 
         state
             .access_control_service
@@ -69,12 +72,22 @@ impl FromRequestParts<HousesRouterState> for HouseAccess {
 
 pub fn houses_router(app_state: AppState) -> Router {
     let house_router_state = HousesRouterState::new(app_state.clone());
+    let required_scopes = RequiredScopes::new(
+        ApiTokenScope::HousesRead,
+        ApiTokenScope::HousesAdmin,
+        Arc::new(house_router_state.access_control_service.clone()),
+    );
 
     Router::new()
         .route("/", get(get_user_houses))
         .route("/", post(create_house))
-        .route("/{id}", get(get_user_house_by_id))
-        .route("/{id}", delete(delete_house))
+        .route("/{house_id}", get(get_user_house_by_id))
+        .route("/{house_id}", patch(update_house))
+        .route("/{house_id}", delete(delete_house))
+        .route_layer(middleware::from_fn_with_state(
+            required_scopes,
+            api_token_scope_middleware,
+        ))
         .with_state(house_router_state)
         .merge(crate::routes::device_metrics::device_metrics_routes(
             app_state,
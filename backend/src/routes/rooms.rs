@@ -3,16 +3,21 @@ use std::sync::Arc;
 use axum::{
     extract::{FromRequestParts, Path},
     http::request::Parts,
-    routing::{delete, get, post},
+    middleware,
+    routing::{delete, get, patch, post},
     Router,
 };
 
+use serde::Deserialize;
+
 use crate::{
     errors::AppError,
-    handlers::rooms::{create_room, delete_room, get_house_rooms},
+    handlers::rooms::{create_room, delete_room, get_house_rooms, update_room},
+    middlewares::api_token_scope::{api_token_scope_middleware, RequiredScopes},
+    models::api_tokens::ApiTokenScope,
     repositories::{
         rooms_repository::RoomsRepository, user_houses_repository::UserHousesRepository,
-        HouseRepository,
+        HouseRepository, ZonesRepository,
     },
     services::{
         access_control_service::{self, AccessControlService},
@@ -37,15 +42,23 @@ impl RoomsRouterState<RoomsService, HouseService, AccessControlService> {
             HouseService::new(house_repository.clone(), user_house_repository.clone());
         let access_control_service = AccessControlService::new(user_house_repository.clone());
         let rooms_repository = Arc::new(RoomsRepository::new(app_state.db.pool.clone()));
+        let zones_repository = Arc::new(ZonesRepository::new(app_state.db.pool.clone()));
 
         Self {
             house_service,
             access_control_service,
-            room_service: RoomsService::new(rooms_repository),
+            room_service: RoomsService::new(rooms_repository, zones_repository),
         }
     }
 }
 
+/// Path parameters of the house routes. Routes of things in a house have more
+/// parameters after `house_id`.
+#[derive(Deserialize)]
+struct HousePath {
+    house_id: i64,
+}
+
 #[derive(Debug, Clone)]
 pub struct HouseAccess {
     pub house_id: i64,
@@ -70,7 +83,7 @@ where
             .copied()
             .ok_or_else(|| AppError::AuthorizationError("Not authenticated".to_string()))?;
 
-        let Path(house_id) = Path::<i64>::from_request_parts(parts, state)
+        let Path(HousePath { house_id }) = Path::<HousePath>::from_request_parts(parts, state)
             .await
             .map_err(|_| AppError::BadRequest("Invalid house id".to_string()))?;
 
@@ -85,11 +98,21 @@ where
 
 pub fn rooms_router(app_state: AppState) -> Router {
     let rooms_router_state = RoomsRouterState::new(app_state.clone());
+    let required_scopes = RequiredScopes::new(
+        ApiTokenScope::HousesRead,
+        ApiTokenScope::HousesAdmin,
+        Arc::new(rooms_router_state.access_control_service.clone()),
+    );
 
     Router::new()
         .route("/", get(get_house_rooms))
         .route("/", post(create_room))
-        .route("/{id}", delete(delete_room))
+        .route("/{room_id}", patch(update_room))
+        .route("/{room_id}", delete(delete_room))
+        .route_layer(middleware::from_fn_with_state(
+            required_scopes,
+            api_token_scope_middleware,
+        ))
         .with_state(rooms_router_state)
         .merge(crate::routes::device_metrics::device_metrics_routes(
             app_state,
@@ -1,22 +1,69 @@
 use std::sync::Arc;
 
 use crate::{
-    handlers::users::get_user_profile, repositories::UserRepository,
-    services::user_service::UserService, AppState,
+    handlers::users::{
+        change_password, confirm_totp, delete_account, disable_totp, enroll_totp,
+        get_security_events, get_sessions, get_user_profile, resend_email_verification,
+        revoke_session, update_user_profile,
+    },
+    middlewares::api_token_scope::reject_api_tokens,
+    repositories::{
+        AccountTokensRepository, AuthEventsRepository, SessionsRepository, TwoFactorRepository,
+        UserRepository,
+    },
+    services::{
+        account::AccountService, auth_events::AuthEventsService, mailer, sessions::SessionsService,
+        two_factor::TwoFactorService, user_service::UserService,
+    },
+    AppState,
+};
+use axum::{
+    middleware,
+    routing::{delete, get, patch, post},
+    Router,
 };
-use axum::{routing::get, Router};
 
 #[derive(Clone)]
 pub struct UserRouterState {
     pub user_service: UserService,
+    pub sessions_service: SessionsService,
+    pub account_service: AccountService,
+    pub two_factor_service: TwoFactorService,
+    pub auth_events_service: AuthEventsService,
 }
 
 impl UserRouterState {
     pub fn new(app_state: AppState) -> Self {
         let user_repository = Arc::new(UserRepository::new(app_state.db.pool.clone()));
-        let user_service = UserService::new(user_repository);
-
-        Self { user_service }
+        let user_service = UserService::new(user_repository.clone());
+        let auth_events_service = AuthEventsService::new(Arc::new(AuthEventsRepository::new(
+            app_state.db.pool.clone(),
+        )));
+        let sessions_repository = Arc::new(SessionsRepository::new(app_state.db.pool.clone()));
+        let sessions_service = SessionsService::new(
+            sessions_repository.clone(),
+            Arc::new(auth_events_service.clone()),
+        );
+        let account_tokens_repository =
+            Arc::new(AccountTokensRepository::new(app_state.db.pool.clone()));
+        let account_service = AccountService::new(
+            app_state.config.clone(),
+            user_repository.clone(),
+            account_tokens_repository,
+            sessions_repository,
+            mailer::from_app_state(&app_state),
+            Arc::new(auth_events_service.clone()),
+        );
+        let two_factor_repository = Arc::new(TwoFactorRepository::new(app_state.db.pool.clone()));
+        let two_factor_service = TwoFactorService::new(user_repository, two_factor_repository);
+
+        Self {
+            user_service,
+            sessions_service,
+            account_service,
+            two_factor_service,
+            auth_events_service,
+        }
     }
 }
 
@@ -24,6 +71,16 @@ pub fn users_router(app_state: AppState) -> Router {
     let user_router_state = UserRouterState::new(app_state);
 
     Router::new()
+        .route("/sessions", get(get_sessions))
+        .route("/sessions/{id}", delete(revoke_session))
+        .route("/security-events", get(get_security_events))
+        .route("/email/verification", post(resend_email_verification))
+        .route("/2fa/totp", post(enroll_totp))
+        .route("/2fa/totp/confirm", post(confirm_totp))
+        .route("/2fa/totp/disable", post(disable_totp))
+        .route("/password", post(change_password))
+        .route("/", patch(update_user_profile).delete(delete_account))
+        .route_layer(middleware::from_fn(reject_api_tokens))
         .route("/", get(get_user_profile))
         .with_state(user_router_state)
 }
@@ -31,7 +88,7 @@ pub fn users_router(app_state: AppState) -> Router {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
+    use crate::config::{Config, JwtAlgorithm};
 
     fn create_test_config() -> Config {
         Config {
@@ -42,8 +99,18 @@ mod tests {
             db_user: "test".to_string(),
             db_pass: "test".to_string(),
             jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
             jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
             frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
         }
     }
 
@@ -59,10 +126,7 @@ mod tests {
 
         // Skip the actual test if we can't connect (which is expected in CI)
         if let Ok(pool) = pool {
-            let app_state = AppState {
-                db: crate::db::Database::new(pool),
-                config,
-            };
+            let app_state = AppState::new(crate::db::Database::new(pool), config);
 
             let router = users_router(app_state);
 
@@ -83,10 +147,7 @@ mod tests {
 
         // Skip the actual test if we can't connect (which is expected in CI)
         if let Ok(pool) = pool {
-            let app_state = AppState {
-                db: crate::db::Database::new(pool),
-                config,
-            };
+            let app_state = AppState::new(crate::db::Database::new(pool), config);
 
             let state = UserRouterState::new(app_state);
 
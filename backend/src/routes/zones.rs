@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware,
+    routing::{get, patch},
+    Router,
+};
+
+use crate::{
+    handlers::zones::{create_zone, delete_zone, get_house_zones, update_zone},
+    middlewares::api_token_scope::{api_token_scope_middleware, RequiredScopes},
+    models::api_tokens::ApiTokenScope,
+    repositories::{UserHousesRepository, ZonesRepository},
+    services::{
+        access_control_service::{AccessControlService, AccessControlServiceTrait},
+        zones::{ZonesService, ZonesServiceTrait},
+    },
+    AppState,
+};
+
+#[derive(Clone)]
+pub struct ZonesRouterState {
+    pub zones_service: Arc<dyn ZonesServiceTrait + Send + Sync>,
+    pub access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+}
+
+impl ZonesRouterState {
+    pub fn new(app_state: AppState) -> Self {
+        let user_houses_repository = Arc::new(UserHousesRepository::new(app_state.db.pool.clone()));
+        let access_control_service = Arc::new(AccessControlService::new(user_houses_repository));
+        let zones_service = Arc::new(ZonesService::new(
+            Arc::new(ZonesRepository::new(app_state.db.pool.clone())),
+            access_control_service.clone(),
+        ));
+
+        Self {
+            zones_service,
+            access_control_service,
+        }
+    }
+}
+
+/// Zones of a house, nested under `/houses/{house_id}/zones`.
+pub fn zones_router(app_state: AppState) -> Router {
+    let zones_router_state = ZonesRouterState::new(app_state);
+    let required_scopes = RequiredScopes::new(
+        ApiTokenScope::HousesRead,
+        ApiTokenScope::HousesAdmin,
+        zones_router_state.access_control_service.clone(),
+    );
+
+    Router::new()
+        .route("/", get(get_house_zones).post(create_zone))
+        .route("/{zone_id}", patch(update_zone).delete(delete_zone))
+        .route_layer(middleware::from_fn_with_state(
+            required_scopes,
+            api_token_scope_middleware,
+        ))
+        .with_state(zones_router_state)
+}
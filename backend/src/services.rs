@@ -1,8 +1,21 @@
 pub mod access_control_service;
+pub mod account;
+pub mod admin;
 pub mod api_tokens;
 pub mod auth;
+pub mod auth_events;
 pub mod device;
+pub mod device_commands;
 pub mod device_metrics;
+pub mod device_state;
 pub mod house;
+pub mod house_members;
+pub mod house_transfers;
+pub mod jwt_keys;
+pub mod login_throttle;
+pub mod mailer;
 pub mod rooms;
+pub mod sessions;
+pub mod two_factor;
 pub mod user_service;
+pub mod zones;
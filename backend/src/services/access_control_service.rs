@@ -1,20 +1,83 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use chrono::Utc;
 use mockall::automock;
 
 use crate::{
     errors::{AppError, Result},
+    models::{
+        api_tokens::ApiTokenGrant,
+        user_houses::{HouseMembership, HouseRole},
+    },
     repositories::user_houses_repository::UserHousesRepositoryTrait,
 };
 
+/// Resource a request operates on, used to resolve the house it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessTarget {
+    House(i64),
+    Room(i64),
+    Device(i64),
+}
+
+/// Operation a user performs on a house or on something in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Read the house, its rooms, devices and metrics
+    ViewHouse,
+    /// Create, update and delete devices
+    ManageDevices,
+    /// Report device metrics
+    WriteMetrics,
+    /// Create, edit and delete rooms and zones
+    ManageRooms,
+    /// Edit the house details
+    UpdateHouse,
+    /// Invite and remove members
+    ManageMembers,
+    /// Invite and remove owners
+    ManageOwners,
+    /// Hand the house over to another member
+    TransferOwnership,
+    DeleteHouse,
+}
+
+impl Action {
+    /// Permission matrix of the house roles
+    pub fn is_allowed_for(self, role: HouseRole) -> bool {
+        match self {
+            Action::ViewHouse => true,
+            Action::ManageDevices | Action::WriteMetrics => role != HouseRole::Guest,
+            Action::ManageRooms | Action::UpdateHouse | Action::ManageMembers => {
+                matches!(role, HouseRole::Owner | HouseRole::Admin)
+            }
+            Action::ManageOwners | Action::TransferOwnership | Action::DeleteHouse => {
+                role == HouseRole::Owner
+            }
+        }
+    }
+}
+
 #[automock]
 #[async_trait]
 pub trait AccessControlServiceTrait {
+    /// Checks that the user is a member of the house, whatever their role, and
+    /// that their access limits allow access right now.
     async fn validate_house_access(&self, house_id: i64, user_id: i64) -> Result<bool>;
-    async fn can_access_device(&self, user_id: i64, device_id: i64) -> Result<()>;
-    async fn can_access_room(&self, user_id: i64, room_id: i64) -> Result<()>;
-    async fn can_access_house(&self, user_id: i64, house_id: i64) -> Result<()>;
+    /// Checks that the role of the user in the house allows the action, and
+    /// that their access limits allow access right now.
+    async fn can(&self, user_id: i64, house_id: i64, action: Action) -> Result<()>;
+    /// Like `can`, and checks that the device is in a room the user may access.
+    async fn can_access_device(&self, user_id: i64, device_id: i64, action: Action) -> Result<()>;
+    /// Like `can`, and checks that the user may access the room.
+    async fn can_access_room(&self, user_id: i64, room_id: i64, action: Action) -> Result<()>;
+    /// Rooms of the house the user may see, `None` for all of them. House-wide
+    /// listings are filtered to these.
+    async fn visible_rooms(&self, user_id: i64, house_id: i64) -> Result<Option<Vec<i64>>>;
+    /// Rejects the target unless it lies in the house the API token is restricted to.
+    async fn validate_token_house(&self, grant: &ApiTokenGrant, target: AccessTarget)
+        -> Result<()>;
 }
 
 #[derive(Clone)]
@@ -26,39 +89,360 @@ impl AccessControlService {
     pub fn new(user_houses_repo: Arc<dyn UserHousesRepositoryTrait + Send + Sync>) -> Self {
         Self { user_houses_repo }
     }
+
+    async fn active_membership(&self, house_id: i64, user_id: i64) -> Result<HouseMembership> {
+        let membership = self
+            .user_houses_repo
+            .get_membership(house_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::AuthenticationError("Access denied".to_string()))?;
+
+        // Schedules are in the local time of the house
+        let now = Utc::now().with_timezone(&membership.timezone);
+        if !membership.access.allows(&now) {
+            return Err(AppError::AuthorizationError(
+                "Your access to this house is not active at this time".to_string(),
+            ));
+        }
+
+        Ok(membership)
+    }
+
+    fn check_role(membership: &HouseMembership, action: Action) -> Result<()> {
+        if !action.is_allowed_for(membership.role) {
+            return Err(AppError::AuthorizationError(format!(
+                "The {} role does not allow this action",
+                membership.role
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl AccessControlServiceTrait for AccessControlService {
     async fn validate_house_access(&self, house_id: i64, user_id: i64) -> Result<bool> {
-        let result = self
-            .user_houses_repo
-            .user_has_access_to_house(house_id, user_id)
-            .await?;
-        if !result {
-            return Err(AppError::AuthenticationError("Access denied".to_string()));
-        }
+        self.active_membership(house_id, user_id).await?;
+
+        Ok(true)
+    }
+
+    async fn can(&self, user_id: i64, house_id: i64, action: Action) -> Result<()> {
+        let membership = self.active_membership(house_id, user_id).await?;
 
-        Ok(result)
+        Self::check_role(&membership, action)
     }
 
-    async fn can_access_device(&self, user_id: i64, device_id: i64) -> Result<()> {
-        let device_house = self
+    async fn can_access_device(&self, user_id: i64, device_id: i64, action: Action) -> Result<()> {
+        let room_id = self
             .user_houses_repo
-            .get_house_by_device_id(device_id)
+            .get_room_id_by_device_id(device_id)
             .await?;
-        self.validate_house_access(device_house.id, user_id).await?;
-        Ok(())
+        self.can_access_room(user_id, room_id, action).await
     }
 
-    async fn can_access_room(&self, user_id: i64, room_id: i64) -> Result<()> {
+    async fn can_access_room(&self, user_id: i64, room_id: i64, action: Action) -> Result<()> {
         let room_house = self.user_houses_repo.get_house_by_room_id(room_id).await?;
-        self.validate_house_access(room_house.id, user_id).await?;
-        Ok(())
+        let membership = self.active_membership(room_house.id, user_id).await?;
+
+        if !membership.access.allows_room(room_id) {
+            return Err(AppError::AuthorizationError(
+                "You don't have access to this room".to_string(),
+            ));
+        }
+
+        Self::check_role(&membership, action)
     }
 
-    async fn can_access_house(&self, user_id: i64, house_id: i64) -> Result<()> {
-        self.validate_house_access(house_id, user_id).await?;
+    async fn visible_rooms(&self, user_id: i64, house_id: i64) -> Result<Option<Vec<i64>>> {
+        let membership = self.active_membership(house_id, user_id).await?;
+
+        Ok(membership.access.room_ids)
+    }
+
+    async fn validate_token_house(
+        &self,
+        grant: &ApiTokenGrant,
+        target: AccessTarget,
+    ) -> Result<()> {
+        let Some(token_house_id) = grant.house_id else {
+            return Ok(());
+        };
+
+        let target_house_id = match target {
+            AccessTarget::House(house_id) => house_id,
+            AccessTarget::Room(room_id) => {
+                self.user_houses_repo
+                    .get_house_by_room_id(room_id)
+                    .await?
+                    .id
+            }
+            AccessTarget::Device(device_id) => {
+                self.user_houses_repo
+                    .get_house_by_device_id(device_id)
+                    .await?
+                    .id
+            }
+        };
+
+        if target_house_id != token_house_id {
+            return Err(AppError::AuthorizationError(
+                "API token is not valid for this house".to_string(),
+            ));
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            api_tokens::ApiTokenScope,
+            houses::{House, UnitSystem},
+            user_houses::{AccessWindow, MemberAccess},
+        },
+        repositories::user_houses_repository::MockUserHousesRepositoryTrait,
+    };
+    use chrono::{Datelike, Duration};
+    use chrono_tz::Tz;
+    use mockall::predicate::eq;
+
+    fn house(id: i64) -> House {
+        House {
+            id,
+            name: "Test House".to_string(),
+            address: "123 Main St".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            description: "".to_string(),
+            r#type: "apartment".to_string(),
+            timezone: "UTC".to_string(),
+            latitude: None,
+            longitude: None,
+            unit_system: UnitSystem::Metric,
+            currency: "USD".to_string(),
+        }
+    }
+
+    fn grant(house_id: Option<i64>) -> ApiTokenGrant {
+        ApiTokenGrant {
+            scopes: vec![ApiTokenScope::MetricsWrite],
+            house_id,
+        }
+    }
+
+    #[test]
+    fn test_permission_matrix() {
+        use HouseRole::*;
+
+        for role in [Owner, Admin, Member, Guest] {
+            assert!(Action::ViewHouse.is_allowed_for(role));
+        }
+        assert!(Action::ManageDevices.is_allowed_for(Member));
+        assert!(!Action::ManageDevices.is_allowed_for(Guest));
+        assert!(!Action::WriteMetrics.is_allowed_for(Guest));
+        assert!(Action::ManageRooms.is_allowed_for(Admin));
+        assert!(!Action::ManageRooms.is_allowed_for(Member));
+        assert!(Action::UpdateHouse.is_allowed_for(Admin));
+        assert!(!Action::UpdateHouse.is_allowed_for(Member));
+        assert!(Action::ManageMembers.is_allowed_for(Admin));
+        assert!(!Action::ManageMembers.is_allowed_for(Member));
+        assert!(!Action::ManageOwners.is_allowed_for(Admin));
+        assert!(!Action::TransferOwnership.is_allowed_for(Admin));
+        assert!(Action::DeleteHouse.is_allowed_for(Owner));
+        assert!(!Action::DeleteHouse.is_allowed_for(Admin));
+    }
+
+    fn membership(role: HouseRole, access: MemberAccess) -> HouseMembership {
+        HouseMembership {
+            role,
+            access,
+            timezone: Tz::UTC,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_can_checks_the_role() {
+        let mut mock_repo = MockUserHousesRepositoryTrait::new();
+        mock_repo
+            .expect_get_membership()
+            .with(eq(1), eq(2))
+            .returning(|_, _| Ok(Some(membership(HouseRole::Admin, MemberAccess::default()))));
+        mock_repo
+            .expect_get_membership()
+            .with(eq(1), eq(3))
+            .returning(|_, _| Ok(None));
+        let service = AccessControlService::new(Arc::new(mock_repo));
+
+        assert!(service.can(2, 1, Action::ManageRooms).await.is_ok());
+        assert!(matches!(
+            service.can(2, 1, Action::DeleteHouse).await,
+            Err(AppError::AuthorizationError(_))
+        ));
+        assert!(matches!(
+            service.can(3, 1, Action::ViewHouse).await,
+            Err(AppError::AuthenticationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_can_checks_the_access_limits() {
+        let mut mock_repo = MockUserHousesRepositoryTrait::new();
+        mock_repo
+            .expect_get_membership()
+            .with(eq(1), eq(2))
+            .returning(|_, _| {
+                Ok(Some(membership(
+                    HouseRole::Guest,
+                    MemberAccess {
+                        valid_until: Some(Utc::now() - Duration::minutes(1)),
+                        ..Default::default()
+                    },
+                )))
+            });
+        mock_repo
+            .expect_get_membership()
+            .with(eq(1), eq(3))
+            .returning(|_, _| {
+                Ok(Some(membership(
+                    HouseRole::Guest,
+                    MemberAccess {
+                        valid_from: Some(Utc::now() - Duration::days(1)),
+                        valid_until: Some(Utc::now() + Duration::days(1)),
+                        ..Default::default()
+                    },
+                )))
+            });
+        let service = AccessControlService::new(Arc::new(mock_repo));
+
+        assert!(matches!(
+            service.validate_house_access(1, 2).await,
+            Err(AppError::AuthorizationError(_))
+        ));
+        assert!(service.can(3, 1, Action::ViewHouse).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_is_in_the_local_time_of_the_house() {
+        // UTC+14 all year, so the current local time is well away from UTC
+        let timezone: Tz = "Pacific/Kiritimati".parse().unwrap();
+        let window_start = Utc::now().with_timezone(&timezone) - Duration::hours(1);
+        let access = MemberAccess {
+            schedule: Some(vec![AccessWindow {
+                days: vec![window_start.weekday().into()],
+                start: window_start.time(),
+                end: (window_start + Duration::hours(2)).time(),
+            }]),
+            ..Default::default()
+        };
+
+        let mut mock_repo = MockUserHousesRepositoryTrait::new();
+        let local_access = access.clone();
+        mock_repo
+            .expect_get_membership()
+            .with(eq(1), eq(2))
+            .returning(move |_, _| {
+                Ok(Some(HouseMembership {
+                    timezone,
+                    ..membership(HouseRole::Guest, local_access.clone())
+                }))
+            });
+        mock_repo
+            .expect_get_membership()
+            .with(eq(2), eq(2))
+            .returning(move |_, _| Ok(Some(membership(HouseRole::Guest, access.clone()))));
+        let service = AccessControlService::new(Arc::new(mock_repo));
+
+        assert!(service.can(2, 1, Action::ViewHouse).await.is_ok());
+        // The same window is not active now in a house on UTC
+        assert!(matches!(
+            service.can(2, 2, Action::ViewHouse).await,
+            Err(AppError::AuthorizationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_room_scoped_membership() {
+        let mut mock_repo = MockUserHousesRepositoryTrait::new();
+        mock_repo
+            .expect_get_house_by_room_id()
+            .returning(|_| Ok(house(1)));
+        mock_repo
+            .expect_get_room_id_by_device_id()
+            .with(eq(7))
+            .returning(|_| Ok(5));
+        mock_repo.expect_get_membership().returning(|_, _| {
+            Ok(Some(membership(
+                HouseRole::Member,
+                MemberAccess {
+                    room_ids: Some(vec![4]),
+                    ..Default::default()
+                },
+            )))
+        });
+        let service = AccessControlService::new(Arc::new(mock_repo));
+
+        assert!(service
+            .can_access_room(2, 4, Action::ManageDevices)
+            .await
+            .is_ok());
+        assert!(matches!(
+            service.can_access_device(2, 7, Action::ViewHouse).await,
+            Err(AppError::AuthorizationError(_))
+        ));
+        assert_eq!(service.visible_rooms(2, 1).await.unwrap(), Some(vec![4]));
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_house_unrestricted() {
+        let service = AccessControlService::new(Arc::new(MockUserHousesRepositoryTrait::new()));
+
+        let result = service
+            .validate_token_house(&grant(None), AccessTarget::Device(5))
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_house_matching_device() {
+        let mut mock_repo = MockUserHousesRepositoryTrait::new();
+        mock_repo
+            .expect_get_house_by_device_id()
+            .with(eq(5))
+            .times(1)
+            .returning(|_| Ok(house(1)));
+        let service = AccessControlService::new(Arc::new(mock_repo));
+
+        let result = service
+            .validate_token_house(&grant(Some(1)), AccessTarget::Device(5))
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_house_other_house() {
+        let mut mock_repo = MockUserHousesRepositoryTrait::new();
+        mock_repo
+            .expect_get_house_by_room_id()
+            .with(eq(3))
+            .times(1)
+            .returning(|_| Ok(house(2)));
+        let service = AccessControlService::new(Arc::new(mock_repo));
+
+        let room_result = service
+            .validate_token_house(&grant(Some(1)), AccessTarget::Room(3))
+            .await;
+        let house_result = service
+            .validate_token_house(&grant(Some(1)), AccessTarget::House(2))
+            .await;
+
+        assert!(matches!(room_result, Err(AppError::AuthorizationError(_))));
+        assert!(matches!(house_result, Err(AppError::AuthorizationError(_))));
+    }
+}
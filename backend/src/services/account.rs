@@ -0,0 +1,657 @@
+use std::{borrow::Cow, sync::Arc};
+
+use async_trait::async_trait;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use mockall::automock;
+use rand::{distr::Alphanumeric, Rng};
+use sha2::Sha256;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::{
+    config::Config,
+    errors::{AppError, Result},
+    models::{
+        account_tokens::AccountTokenPurpose,
+        auth_events::{AuthEventType, NewAuthEvent},
+        mail::EmailMessage,
+        sessions::ClientInfo,
+        users::User,
+    },
+    repositories::{AccountTokensRepositoryTrait, SessionsRepositoryTrait, UserRepositoryTrait},
+    services::{auth_events::AuthEventsServiceTrait, mailer::Mailer},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ACCOUNT_TOKEN_LENGTH: usize = 48;
+const PASSWORD_RESET_EXPIRES_IN_MINUTES: i64 = 60;
+const EMAIL_VERIFICATION_EXPIRES_IN_HOURS: i64 = 48;
+
+/// Account credentials and lifecycle: email verification and password reset
+/// through single-use tokens sent by email, password changes and deletion.
+#[automock]
+#[async_trait]
+pub trait AccountServiceTrait {
+    async fn send_email_verification(&self, user: &User) -> Result<()>;
+    async fn resend_email_verification(&self, user_id: i64) -> Result<()>;
+    async fn verify_email(&self, token: &str) -> Result<()>;
+    /// Sends a reset link if an account with this email exists. Succeeds either
+    /// way so the endpoint can't be used to probe for registered emails.
+    async fn request_password_reset(&self, email: &str) -> Result<()>;
+    /// Sets a new password and revokes all sessions of the user.
+    async fn reset_password(
+        &self,
+        token: &str,
+        new_password: &str,
+        client: ClientInfo,
+    ) -> Result<()>;
+    /// Sets a new password after checking the current one and revokes all other
+    /// sessions of the user.
+    async fn change_password(
+        &self,
+        user_id: i64,
+        current_password: &str,
+        new_password: &str,
+        current_session_id: Option<i64>,
+        client: ClientInfo,
+    ) -> Result<()>;
+    /// Deletes the account after checking the password. Houses only this user
+    /// has access to are deleted with it. Fails while the user is the only
+    /// owner of a house shared with others.
+    async fn delete_account(&self, user_id: i64, password: &str) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct AccountService {
+    config: Config,
+    user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+    account_tokens_repository: Arc<dyn AccountTokensRepositoryTrait + Send + Sync>,
+    sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+    mailer: Arc<dyn Mailer + Send + Sync>,
+    auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
+}
+
+impl AccountService {
+    pub fn new(
+        config: Config,
+        user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+        account_tokens_repository: Arc<dyn AccountTokensRepositoryTrait + Send + Sync>,
+        sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+        mailer: Arc<dyn Mailer + Send + Sync>,
+        auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            config,
+            user_repository,
+            account_tokens_repository,
+            sessions_repository,
+            mailer,
+            auth_events_service,
+        }
+    }
+
+    /// Tokens are stored as an HMAC keyed with the JWT secret, so a leaked
+    /// database can't be used to mint valid links.
+    fn sign_token(&self, token: &str) -> Result<String> {
+        let mut mac = HmacSha256::new_from_slice(self.config.jwt_secret.as_bytes())
+            .map_err(|_| AppError::InternalServerError("Invalid signing key".to_string()))?;
+        mac.update(token.as_bytes());
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+
+    /// Replaces any outstanding token of this purpose with a new one and
+    /// returns the raw token to be sent to the user.
+    async fn issue_token(
+        &self,
+        user_id: i64,
+        purpose: AccountTokenPurpose,
+        lifetime: Duration,
+    ) -> Result<String> {
+        let token: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(ACCOUNT_TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+
+        self.account_tokens_repository
+            .invalidate_for_user(user_id, purpose)
+            .await?;
+        self.account_tokens_repository
+            .create(
+                user_id,
+                purpose,
+                &self.sign_token(&token)?,
+                Utc::now() + lifetime,
+            )
+            .await?;
+
+        Ok(token)
+    }
+
+    /// Fails with a validation error on `field` if the password is wrong.
+    async fn check_password(
+        &self,
+        user_id: i64,
+        password: &str,
+        field: &'static str,
+    ) -> Result<()> {
+        let password_data = self
+            .user_repository
+            .get_password_hash_by_id(user_id)
+            .await?;
+
+        if !verify(password, &password_data.password_hash)? {
+            let mut errors = ValidationErrors::new();
+            errors.add(
+                field,
+                ValidationError::new("invalid").with_message(Cow::from("Password is incorrect")),
+            );
+            return Err(AppError::ValidationError(errors));
+        }
+
+        Ok(())
+    }
+
+    async fn consume_token(&self, purpose: AccountTokenPurpose, token: &str) -> Result<i64> {
+        let account_token = self
+            .account_tokens_repository
+            .consume(purpose, &self.sign_token(token)?)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired token".to_string()))?;
+
+        Ok(account_token.user_id)
+    }
+}
+
+#[async_trait]
+impl AccountServiceTrait for AccountService {
+    async fn send_email_verification(&self, user: &User) -> Result<()> {
+        if user.email_verified_at.is_some() {
+            return Err(AppError::BadRequest(
+                "Email address is already verified".to_string(),
+            ));
+        }
+
+        let token = self
+            .issue_token(
+                user.id,
+                AccountTokenPurpose::EmailVerification,
+                Duration::hours(EMAIL_VERIFICATION_EXPIRES_IN_HOURS),
+            )
+            .await?;
+
+        self.mailer
+            .send(EmailMessage {
+                to: user.email.clone(),
+                subject: "Verify your email address".to_string(),
+                body: format!(
+                    "Hi {},\n\nPlease verify your email address by opening the link below:\n\n{}/verify-email?token={}\n\nThe link expires in {} hours.\n",
+                    user.first_name,
+                    self.config.frontend_origin,
+                    token,
+                    EMAIL_VERIFICATION_EXPIRES_IN_HOURS
+                ),
+            })
+            .await
+    }
+
+    async fn resend_email_verification(&self, user_id: i64) -> Result<()> {
+        let user = self.user_repository.get_user_by_id(user_id).await?;
+        self.send_email_verification(&user).await
+    }
+
+    async fn verify_email(&self, token: &str) -> Result<()> {
+        let user_id = self
+            .consume_token(AccountTokenPurpose::EmailVerification, token)
+            .await?;
+
+        self.user_repository.mark_email_verified(user_id).await
+    }
+
+    async fn request_password_reset(&self, email: &str) -> Result<()> {
+        let Some(user) = self.user_repository.find_by_email(email).await? else {
+            return Ok(());
+        };
+
+        let token = self
+            .issue_token(
+                user.id,
+                AccountTokenPurpose::PasswordReset,
+                Duration::minutes(PASSWORD_RESET_EXPIRES_IN_MINUTES),
+            )
+            .await?;
+
+        self.mailer
+            .send(EmailMessage {
+                to: user.email,
+                subject: "Reset your password".to_string(),
+                body: format!(
+                    "Hi {},\n\nA password reset was requested for your account. Set a new password by opening the link below:\n\n{}/reset-password?token={}\n\nThe link expires in {} minutes. If you didn't request this, you can ignore this email.\n",
+                    user.first_name,
+                    self.config.frontend_origin,
+                    token,
+                    PASSWORD_RESET_EXPIRES_IN_MINUTES
+                ),
+            })
+            .await
+    }
+
+    async fn reset_password(
+        &self,
+        token: &str,
+        new_password: &str,
+        client: ClientInfo,
+    ) -> Result<()> {
+        let user_id = self
+            .consume_token(AccountTokenPurpose::PasswordReset, token)
+            .await?;
+
+        let password_hash = hash(new_password, DEFAULT_COST)?;
+        self.user_repository
+            .update_password(user_id, &password_hash)
+            .await?;
+        self.sessions_repository.revoke_all(user_id, None).await?;
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::PasswordChanged, Some(user_id), &client)
+                    .with_details("Reset by email"),
+            )
+            .await;
+
+        // Receiving the reset link proves ownership of the address
+        self.user_repository.mark_email_verified(user_id).await
+    }
+
+    async fn change_password(
+        &self,
+        user_id: i64,
+        current_password: &str,
+        new_password: &str,
+        current_session_id: Option<i64>,
+        client: ClientInfo,
+    ) -> Result<()> {
+        self.check_password(user_id, current_password, "current_password")
+            .await?;
+
+        let password_hash = hash(new_password, DEFAULT_COST)?;
+        self.user_repository
+            .update_password(user_id, &password_hash)
+            .await?;
+        self.account_tokens_repository
+            .invalidate_for_user(user_id, AccountTokenPurpose::PasswordReset)
+            .await?;
+        self.sessions_repository
+            .revoke_all(user_id, current_session_id)
+            .await?;
+        self.auth_events_service
+            .record(NewAuthEvent::new(
+                AuthEventType::PasswordChanged,
+                Some(user_id),
+                &client,
+            ))
+            .await;
+
+        Ok(())
+    }
+
+    async fn delete_account(&self, user_id: i64, password: &str) -> Result<()> {
+        self.check_password(user_id, password, "password").await?;
+
+        self.user_repository.delete_user(user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        config::JwtAlgorithm,
+        models::{account_tokens::AccountToken, auth::PasswordHash, users::UserRole},
+        repositories::{
+            account_tokens_repository::MockAccountTokensRepositoryTrait,
+            sessions_repository::MockSessionsRepositoryTrait,
+            user_repository::MockUserRepositoryTrait,
+        },
+        services::{auth_events::MockAuthEventsServiceTrait, mailer::MockMailer},
+    };
+
+    fn create_test_config() -> Config {
+        Config {
+            port: 3000,
+            db_host: "localhost".to_string(),
+            db_name: "test".to_string(),
+            db_port: 5432,
+            db_user: "test".to_string(),
+            db_pass: "test".to_string(),
+            jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
+            jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
+            frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
+        }
+    }
+
+    fn user() -> User {
+        User {
+            id: 1,
+            first_name: "John".to_string(),
+            last_name: "Doe".to_string(),
+            phone: "1234567890".to_string(),
+            email: "john@example.com".to_string(),
+            role: UserRole::User,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
+        }
+    }
+
+    fn account_token(user_id: i64, purpose: AccountTokenPurpose) -> AccountToken {
+        AccountToken {
+            id: 1,
+            user_id,
+            purpose: purpose.to_string(),
+            token_hash: "hash".to_string(),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::hours(1),
+            used_at: Some(Utc::now()),
+        }
+    }
+
+    fn service(
+        user_repository: MockUserRepositoryTrait,
+        account_tokens_repository: MockAccountTokensRepositoryTrait,
+        sessions_repository: MockSessionsRepositoryTrait,
+        mailer: MockMailer,
+    ) -> AccountService {
+        AccountService::new(
+            create_test_config(),
+            Arc::new(user_repository),
+            Arc::new(account_tokens_repository),
+            Arc::new(sessions_repository),
+            Arc::new(mailer),
+            Arc::new(events_ignored()),
+        )
+    }
+
+    // Audit log that accepts any event
+    fn events_ignored() -> MockAuthEventsServiceTrait {
+        let mut mock_events = MockAuthEventsServiceTrait::new();
+        mock_events.expect_record().returning(|_| ());
+        mock_events
+    }
+
+    #[tokio::test]
+    async fn test_send_email_verification_mails_signed_token() {
+        let mut mock_tokens = MockAccountTokensRepositoryTrait::new();
+        let mut mock_mailer = MockMailer::new();
+
+        mock_tokens
+            .expect_invalidate_for_user()
+            .with(eq(1i64), eq(AccountTokenPurpose::EmailVerification))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_tokens
+            .expect_create()
+            .withf(|user_id, purpose, hash, _| {
+                *user_id == 1
+                    && *purpose == AccountTokenPurpose::EmailVerification
+                    && hash.len() == 64
+            })
+            .times(1)
+            .returning(|user_id, purpose, _, _| Ok(account_token(user_id, purpose)));
+        mock_mailer
+            .expect_send()
+            .withf(|m| {
+                m.to == "john@example.com"
+                    && m.body.contains("http://localhost:5173/verify-email?token=")
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service = service(
+            MockUserRepositoryTrait::new(),
+            mock_tokens,
+            MockSessionsRepositoryTrait::new(),
+            mock_mailer,
+        );
+
+        assert!(service.send_email_verification(&user()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_email_verification_to_verified_user() {
+        let service = service(
+            MockUserRepositoryTrait::new(),
+            MockAccountTokensRepositoryTrait::new(),
+            MockSessionsRepositoryTrait::new(),
+            MockMailer::new(),
+        );
+        let verified = User {
+            email_verified_at: Some(Utc::now()),
+            ..user()
+        };
+
+        let result = service.send_email_verification(&verified).await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_with_invalid_token() {
+        let mut mock_tokens = MockAccountTokensRepositoryTrait::new();
+        let mut mock_users = MockUserRepositoryTrait::new();
+
+        mock_tokens.expect_consume().returning(|_, _| Ok(None));
+        mock_users.expect_mark_email_verified().never();
+
+        let service = service(
+            mock_users,
+            mock_tokens,
+            MockSessionsRepositoryTrait::new(),
+            MockMailer::new(),
+        );
+
+        let result = service.verify_email("unknown").await;
+
+        match result {
+            Err(AppError::BadRequest(msg)) => assert_eq!(msg, "Invalid or expired token"),
+            _ => panic!("Expected BadRequest"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_for_unknown_email_sends_nothing() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        let mut mock_mailer = MockMailer::new();
+
+        mock_users.expect_find_by_email().returning(|_| Ok(None));
+        mock_mailer.expect_send().never();
+
+        let service = service(
+            mock_users,
+            MockAccountTokensRepositoryTrait::new(),
+            MockSessionsRepositoryTrait::new(),
+            mock_mailer,
+        );
+
+        assert!(service
+            .request_password_reset("nobody@example.com")
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_updates_hash_and_revokes_sessions() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        let mut mock_tokens = MockAccountTokensRepositoryTrait::new();
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+
+        mock_tokens
+            .expect_consume()
+            .withf(|purpose, hash| {
+                *purpose == AccountTokenPurpose::PasswordReset && hash.len() == 64
+            })
+            .times(1)
+            .returning(|purpose, _| Ok(Some(account_token(1, purpose))));
+        mock_users
+            .expect_update_password()
+            .withf(|id, hash| *id == 1 && bcrypt::verify("new_password", hash).unwrap())
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_users
+            .expect_mark_email_verified()
+            .with(eq(1i64))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_sessions
+            .expect_revoke_all()
+            .with(eq(1i64), eq(None))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = service(mock_users, mock_tokens, mock_sessions, MockMailer::new());
+
+        assert!(service
+            .reset_password("token", "new_password", ClientInfo::default())
+            .await
+            .is_ok());
+    }
+
+    fn password_hash(password: &str) -> PasswordHash {
+        PasswordHash {
+            id: 1,
+            password_hash: hash(password, DEFAULT_COST).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_change_password_keeps_current_session() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        let mut mock_tokens = MockAccountTokensRepositoryTrait::new();
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+
+        mock_users
+            .expect_get_password_hash_by_id()
+            .with(eq(1i64))
+            .returning(|_| Ok(password_hash("old_password")));
+        mock_users
+            .expect_update_password()
+            .withf(|id, hash| *id == 1 && bcrypt::verify("new_password", hash).unwrap())
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_tokens
+            .expect_invalidate_for_user()
+            .with(eq(1i64), eq(AccountTokenPurpose::PasswordReset))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_sessions
+            .expect_revoke_all()
+            .with(eq(1i64), eq(Some(7i64)))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let mut mock_events = MockAuthEventsServiceTrait::new();
+        mock_events
+            .expect_record()
+            .withf(|event| {
+                event.event_type == AuthEventType::PasswordChanged
+                    && event.user_id == Some(1)
+                    && event.client.ip.as_deref() == Some("203.0.113.7")
+            })
+            .times(1)
+            .returning(|_| ());
+
+        let service = AccountService::new(
+            create_test_config(),
+            Arc::new(mock_users),
+            Arc::new(mock_tokens),
+            Arc::new(mock_sessions),
+            Arc::new(MockMailer::new()),
+            Arc::new(mock_events),
+        );
+        let client = ClientInfo {
+            user_agent: None,
+            ip: Some("203.0.113.7".to_string()),
+        };
+
+        assert!(service
+            .change_password(1, "old_password", "new_password", Some(7), client)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_change_password_with_wrong_current_password() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+
+        mock_users
+            .expect_get_password_hash_by_id()
+            .returning(|_| Ok(password_hash("old_password")));
+        mock_users.expect_update_password().never();
+        mock_sessions.expect_revoke_all().never();
+
+        let service = service(
+            mock_users,
+            MockAccountTokensRepositoryTrait::new(),
+            mock_sessions,
+            MockMailer::new(),
+        );
+
+        let result = service
+            .change_password(
+                1,
+                "wrong_password",
+                "new_password",
+                Some(7),
+                ClientInfo::default(),
+            )
+            .await;
+
+        match result {
+            Err(AppError::ValidationError(errors)) => {
+                assert!(errors.field_errors().contains_key("current_password"))
+            }
+            _ => panic!("Expected ValidationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_account_requires_password() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        mock_users
+            .expect_get_password_hash_by_id()
+            .returning(|_| Ok(password_hash("password123")));
+        mock_users
+            .expect_delete_user()
+            .with(eq(1i64))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service = service(
+            mock_users,
+            MockAccountTokensRepositoryTrait::new(),
+            MockSessionsRepositoryTrait::new(),
+            MockMailer::new(),
+        );
+
+        assert!(matches!(
+            service.delete_account(1, "wrong_password").await,
+            Err(AppError::ValidationError(_))
+        ));
+        assert!(service.delete_account(1, "password123").await.is_ok());
+    }
+}
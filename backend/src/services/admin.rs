@@ -0,0 +1,198 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{
+    errors::{AppError, Result},
+    models::{
+        admin::{AdminUserFilters, SystemStats},
+        users::{User, UserRole},
+    },
+    repositories::{SessionsRepositoryTrait, StatsRepositoryTrait, UserRepositoryTrait},
+};
+
+/// User administration and system overview for admins.
+#[automock]
+#[async_trait]
+pub trait AdminServiceTrait {
+    async fn list_users(&self, filters: AdminUserFilters) -> Result<Vec<User>>;
+    async fn update_role(&self, admin_id: i64, user_id: i64, role: UserRole) -> Result<User>;
+    /// Disables the account and signs it out everywhere.
+    async fn disable_user(&self, admin_id: i64, user_id: i64) -> Result<User>;
+    async fn enable_user(&self, user_id: i64) -> Result<User>;
+    /// Revokes all sessions of the user.
+    async fn force_logout(&self, user_id: i64) -> Result<()>;
+    async fn get_system_stats(&self) -> Result<SystemStats>;
+}
+
+#[derive(Clone)]
+pub struct AdminService {
+    user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+    sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+    stats_repository: Arc<dyn StatsRepositoryTrait + Send + Sync>,
+}
+
+impl AdminService {
+    pub fn new(
+        user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+        sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+        stats_repository: Arc<dyn StatsRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            user_repository,
+            sessions_repository,
+            stats_repository,
+        }
+    }
+
+    /// Admins can't lock themselves out by demoting or disabling their own account.
+    fn reject_self(admin_id: i64, user_id: i64, action: &str) -> Result<()> {
+        if admin_id == user_id {
+            return Err(AppError::BadRequest(format!(
+                "Admins cannot {} their own account",
+                action
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AdminServiceTrait for AdminService {
+    async fn list_users(&self, filters: AdminUserFilters) -> Result<Vec<User>> {
+        self.user_repository.search_users(&filters).await
+    }
+
+    async fn update_role(&self, admin_id: i64, user_id: i64, role: UserRole) -> Result<User> {
+        Self::reject_self(admin_id, user_id, "change the role of")?;
+
+        self.user_repository.update_role(user_id, role).await
+    }
+
+    async fn disable_user(&self, admin_id: i64, user_id: i64) -> Result<User> {
+        Self::reject_self(admin_id, user_id, "disable")?;
+
+        let user = self.user_repository.set_disabled(user_id, true).await?;
+        self.sessions_repository.revoke_all(user_id, None).await?;
+
+        Ok(user)
+    }
+
+    async fn enable_user(&self, user_id: i64) -> Result<User> {
+        self.user_repository.set_disabled(user_id, false).await
+    }
+
+    async fn force_logout(&self, user_id: i64) -> Result<()> {
+        self.user_repository.get_user_by_id(user_id).await?;
+
+        self.sessions_repository.revoke_all(user_id, None).await
+    }
+
+    async fn get_system_stats(&self) -> Result<SystemStats> {
+        self.stats_repository.get_system_stats().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::repositories::{
+        sessions_repository::MockSessionsRepositoryTrait,
+        stats_repository::MockStatsRepositoryTrait, user_repository::MockUserRepositoryTrait,
+    };
+
+    fn user(id: i64) -> User {
+        User {
+            id,
+            first_name: "John".to_string(),
+            last_name: "Doe".to_string(),
+            phone: "1234567890".to_string(),
+            email: "john@example.com".to_string(),
+            role: UserRole::User,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
+        }
+    }
+
+    fn service(
+        user_repository: MockUserRepositoryTrait,
+        sessions_repository: MockSessionsRepositoryTrait,
+    ) -> AdminService {
+        AdminService::new(
+            Arc::new(user_repository),
+            Arc::new(sessions_repository),
+            Arc::new(MockStatsRepositoryTrait::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_disable_user_revokes_sessions() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+
+        mock_users
+            .expect_set_disabled()
+            .with(eq(2i64), eq(true))
+            .times(1)
+            .returning(|id, _| {
+                Ok(User {
+                    disabled_at: Some(Utc::now()),
+                    ..user(id)
+                })
+            });
+        mock_sessions
+            .expect_revoke_all()
+            .with(eq(2i64), eq(None))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = service(mock_users, mock_sessions);
+        let user = service.disable_user(1, 2).await.unwrap();
+
+        assert!(user.disabled_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_admin_cannot_demote_or_disable_self() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        mock_users.expect_update_role().never();
+        mock_users.expect_set_disabled().never();
+
+        let service = service(mock_users, MockSessionsRepositoryTrait::new());
+
+        assert!(matches!(
+            service.update_role(1, 1, UserRole::User).await,
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            service.disable_user(1, 1).await,
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_force_logout_of_unknown_user() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+
+        mock_users
+            .expect_get_user_by_id()
+            .returning(|_| Err(AppError::NotFound("User not found".to_string())));
+        mock_sessions.expect_revoke_all().never();
+
+        let service = service(mock_users, mock_sessions);
+
+        assert!(matches!(
+            service.force_logout(99).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -1,51 +1,158 @@
 use crate::{
-    errors::Result,
-    models::api_tokens::{CreateApiToken, NewApiToken, PublicApiToken},
+    config::Config,
+    errors::{AppError, Result},
+    models::{
+        api_tokens::{ApiToken, ApiTokenScope, CreateApiToken, NewApiToken, PublicApiToken},
+        auth_events::{AuthEventType, NewAuthEvent},
+        sessions::ClientInfo,
+    },
     repositories::api_tokens_repository::ApiTokensRepositoryTrait,
+    services::{
+        access_control_service::{AccessControlServiceTrait, Action},
+        auth_events::AuthEventsServiceTrait,
+    },
 };
-use bcrypt::{hash, DEFAULT_COST};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
 use rand::Rng;
+use sha2::Sha256;
 use std::sync::Arc;
 
+/// Prefix that distinguishes API tokens from JWTs in the Authorization header.
+pub const API_TOKEN_PREFIX: &str = "sh_";
+
+const PUBLIC_ID_LENGTH: usize = 12;
+const SECRET_LENGTH: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
 #[derive(Clone)]
 pub struct ApiTokensService {
+    config: Config,
     repo: Arc<dyn ApiTokensRepositoryTrait + Send + Sync>,
+    access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+    auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
 }
 
 impl ApiTokensService {
-    pub fn new(repo: Arc<dyn ApiTokensRepositoryTrait + Send + Sync>) -> Self {
-        Self { repo }
+    pub fn new(
+        config: Config,
+        repo: Arc<dyn ApiTokensRepositoryTrait + Send + Sync>,
+        access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+        auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            config,
+            repo,
+            access_control_service,
+            auth_events_service,
+        }
+    }
+
+    fn random_string(length: usize) -> String {
+        const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+        let mut rng = rand::rng();
+        (0..length)
+            .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
+            .collect()
+    }
+
+    /// Splits `sh_<public_id>_<secret>` into its public id and secret.
+    fn parse_token(token: &str) -> Option<(&str, &str)> {
+        token.strip_prefix(API_TOKEN_PREFIX)?.split_once('_')
+    }
+
+    /// House action a role needs to grant a token the scope.
+    fn scope_action(scope: ApiTokenScope) -> Action {
+        match scope {
+            ApiTokenScope::HousesRead | ApiTokenScope::DevicesRead | ApiTokenScope::MetricsRead => {
+                Action::ViewHouse
+            }
+            ApiTokenScope::DevicesWrite => Action::ManageDevices,
+            ApiTokenScope::MetricsWrite => Action::WriteMetrics,
+            ApiTokenScope::HousesAdmin => Action::UpdateHouse,
+        }
+    }
+
+    fn keyed_hash(&self, secret: &str) -> Result<HmacSha256> {
+        let mut mac = HmacSha256::new_from_slice(self.config.api_token_secret.as_bytes())
+            .map_err(|_| AppError::InternalServerError("Invalid API token key".to_string()))?;
+        mac.update(secret.as_bytes());
+        Ok(mac)
     }
 
     pub async fn create_api_token(
         &self,
         user_id: i64,
         token_data: CreateApiToken,
+        client: ClientInfo,
     ) -> Result<NewApiToken> {
-        // 1. Generate a new random token
-        const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-        let plaintext_token: String = (0..32)
-            .map(|_| {
-                let mut rng = rand::rng();
-                let idx = rng.random_range(0..CHARSET.len());
-                CHARSET[idx] as char
-            })
-            .collect();
+        if token_data
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            return Err(AppError::BadRequest(
+                "Expiration date must be in the future".to_string(),
+            ));
+        }
+
+        if token_data
+            .scopes
+            .as_deref()
+            .is_none_or(|scopes| scopes.is_empty())
+        {
+            return Err(AppError::BadRequest(
+                "At least one scope is required".to_string(),
+            ));
+        }
+
+        // A token restricted to a house may only claim what the user's role there allows
+        if let Some(house_id) = token_data.house_id {
+            let mut actions = Vec::new();
+            for scope in token_data.scopes.iter().flatten() {
+                let action = Self::scope_action(*scope);
+                if !actions.contains(&action) {
+                    actions.push(action);
+                }
+            }
+            for action in actions {
+                self.access_control_service
+                    .can(user_id, house_id, action)
+                    .await?;
+            }
+        }
 
-        // 2. Hash the token
-        let token_hash = hash(&plaintext_token, DEFAULT_COST)?;
+        // 1. Generate a new public id and secret
+        let public_id = Self::random_string(PUBLIC_ID_LENGTH);
+        let secret = Self::random_string(SECRET_LENGTH);
+        let plaintext_token = format!("{}{}_{}", API_TOKEN_PREFIX, public_id, secret);
+
+        // 2. Hash the secret
+        let token_hash = hex::encode(self.keyed_hash(&secret)?.finalize().into_bytes());
 
         // 3. Save to the database
         let created_token = self
             .repo
-            .create(user_id, &token_data.name, &token_hash)
+            .create(user_id, &public_id, &token_hash, &token_data)
             .await?;
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::TokenCreated, Some(user_id), &client)
+                    .with_details(format!(
+                        "Created API token {} ({})",
+                        created_token.id, created_token.name
+                    )),
+            )
+            .await;
 
         // 4. Return the public data + the plaintext token
         Ok(NewApiToken {
             id: created_token.id,
             name: created_token.name,
             created_at: created_token.created_at,
+            expires_at: created_token.expires_at,
+            scopes: created_token.scopes,
+            house_id: created_token.house_id,
             token: plaintext_token,
         })
     }
@@ -55,15 +162,140 @@ impl ApiTokensService {
         let public_tokens = tokens.into_iter().map(PublicApiToken::from).collect();
         Ok(public_tokens)
     }
+
+    /// Revokes one of the user's tokens. Tokens of other users are reported
+    /// as not found.
+    pub async fn delete_api_token(
+        &self,
+        user_id: i64,
+        token_id: i64,
+        client: ClientInfo,
+    ) -> Result<()> {
+        self.repo.delete(token_id, user_id).await?;
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::TokenRevoked, Some(user_id), &client)
+                    .with_details(format!("Deleted API token {}", token_id)),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Resolves the owner of a plaintext API token.
+    ///
+    /// The token row is looked up by its public id and the secret is checked
+    /// against the stored HMAC in constant time. Expired tokens are rejected,
+    /// and every successful use is recorded along with the client IP.
+    /// Returns the matching token, whose owner, scopes and house restriction
+    /// apply to the request, or an authentication error.
+    pub async fn authenticate(&self, token: &str, ip: Option<String>) -> Result<ApiToken> {
+        let invalid_token = || AppError::AuthenticationError("Invalid API token".to_string());
+
+        let (public_id, secret) = Self::parse_token(token).ok_or_else(invalid_token)?;
+
+        let api_token = self
+            .repo
+            .find_by_public_id(public_id)
+            .await?
+            .ok_or_else(invalid_token)?;
+
+        let expected_hash = hex::decode(&api_token.token_hash).map_err(|_| invalid_token())?;
+
+        self.keyed_hash(secret)?
+            .verify_slice(&expected_hash)
+            .map_err(|_| invalid_token())?;
+
+        if api_token
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            return Err(AppError::AuthenticationError(
+                "API token has expired".to_string(),
+            ));
+        }
+
+        self.repo.record_usage(api_token.id, ip).await?;
+
+        Ok(api_token)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::api_tokens::ApiToken;
+    use crate::config::JwtAlgorithm;
     use crate::repositories::api_tokens_repository::MockApiTokensRepositoryTrait;
+    use crate::services::access_control_service::MockAccessControlServiceTrait;
+    use crate::services::auth_events::MockAuthEventsServiceTrait;
     use chrono::Utc;
     use mockall::predicate::eq;
+    use std::sync::Mutex;
+
+    fn create_test_config() -> Config {
+        Config {
+            port: 3000,
+            db_host: "localhost".to_string(),
+            db_name: "test".to_string(),
+            db_port: 5432,
+            db_user: "test".to_string(),
+            db_pass: "test".to_string(),
+            jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
+            jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
+            frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
+        }
+    }
+
+    fn create_service(mock_repo: MockApiTokensRepositoryTrait) -> ApiTokensService {
+        ApiTokensService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(MockAccessControlServiceTrait::new()),
+            Arc::new(events_ignored()),
+        )
+    }
+
+    // Audit log that accepts any event
+    fn events_ignored() -> MockAuthEventsServiceTrait {
+        let mut mock_events = MockAuthEventsServiceTrait::new();
+        mock_events.expect_record().returning(|_| ());
+        mock_events
+    }
+
+    fn token_data(name: &str) -> CreateApiToken {
+        CreateApiToken {
+            name: name.to_string(),
+            expires_at: None,
+            scopes: Some(vec![ApiTokenScope::MetricsWrite]),
+            house_id: None,
+        }
+    }
+
+    fn api_token(user_id: i64, public_id: &str, token_hash: &str) -> ApiToken {
+        ApiToken {
+            id: 1,
+            user_id,
+            name: "gateway".to_string(),
+            public_id: Some(public_id.to_string()),
+            token_hash: token_hash.to_string(),
+            created_at: Utc::now(),
+            expires_at: None,
+            last_used_at: None,
+            last_used_ip: None,
+            scopes: vec!["houses:admin".to_string()],
+            house_id: None,
+        }
+    }
 
     #[tokio::test]
     async fn test_create_api_token() {
@@ -71,30 +303,33 @@ mod tests {
         let user_id = 1;
         let token_name = "test_token".to_string();
 
-        let expected_token = ApiToken {
-            id: 1,
-            user_id,
-            name: token_name.clone(),
-            token_hash: "hashed_token".to_string(),
-            created_at: Utc::now(),
-        };
+        let expected_token = api_token(user_id, "publicid0001", "hashed_token");
 
         let token_name_clone = token_name.clone();
         mock_repo
             .expect_create()
-            .withf(move |uid, name, _hash| *uid == user_id && name == token_name_clone)
+            .withf(move |uid, public_id, hash, token_data| {
+                *uid == user_id
+                    && token_data.name == token_name_clone
+                    && public_id.len() == PUBLIC_ID_LENGTH
+                    && hash.len() == 64
+                    && token_data.scopes == Some(vec![ApiTokenScope::MetricsWrite])
+            })
             .times(1)
-            .returning(move |_, _, _| Ok(expected_token.clone()));
+            .returning(move |_, _, _, _| Ok(expected_token.clone()));
 
-        let service = ApiTokensService::new(Arc::new(mock_repo));
+        let service = create_service(mock_repo);
         let result = service
-            .create_api_token(user_id, CreateApiToken { name: token_name })
+            .create_api_token(user_id, token_data(&token_name), ClientInfo::default())
             .await;
 
         assert!(result.is_ok());
         let new_token = result.unwrap();
-        assert_eq!(new_token.name, "test_token");
-        assert!(!new_token.token.is_empty());
+        assert_eq!(new_token.name, "gateway");
+        assert!(new_token.token.starts_with(API_TOKEN_PREFIX));
+        let (public_id, secret) = ApiTokensService::parse_token(&new_token.token).unwrap();
+        assert_eq!(public_id.len(), PUBLIC_ID_LENGTH);
+        assert_eq!(secret.len(), SECRET_LENGTH);
     }
 
     #[tokio::test]
@@ -102,22 +337,10 @@ mod tests {
         let mut mock_repo = MockApiTokensRepositoryTrait::new();
         let user_id = 1;
 
-        let tokens = vec![
-            ApiToken {
-                id: 1,
-                user_id,
-                name: "token1".to_string(),
-                token_hash: "hash1".to_string(),
-                created_at: Utc::now(),
-            },
-            ApiToken {
-                id: 2,
-                user_id,
-                name: "token2".to_string(),
-                token_hash: "hash2".to_string(),
-                created_at: Utc::now(),
-            },
-        ];
+        let mut legacy_token = api_token(user_id, "", "hash2");
+        legacy_token.id = 2;
+        legacy_token.public_id = None;
+        let tokens = vec![api_token(user_id, "publicid0001", "hash1"), legacy_token];
 
         mock_repo
             .expect_find_by_user_id()
@@ -125,13 +348,329 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(tokens.clone()));
 
-        let service = ApiTokensService::new(Arc::new(mock_repo));
+        let service = create_service(mock_repo);
         let result = service.get_api_tokens(user_id).await;
 
         assert!(result.is_ok());
         let public_tokens = result.unwrap();
         assert_eq!(public_tokens.len(), 2);
-        assert_eq!(public_tokens[0].name, "token1");
-        assert_eq!(public_tokens[1].name, "token2");
+        assert!(!public_tokens[0].legacy);
+        assert!(public_tokens[1].legacy);
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_api_token() {
+        let stored = Arc::new(Mutex::new(None::<ApiToken>));
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+
+        let created = stored.clone();
+        mock_repo
+            .expect_create()
+            .times(1)
+            .returning(move |user_id, public_id, hash, _| {
+                let token = api_token(user_id, public_id, hash);
+                *created.lock().unwrap() = Some(token.clone());
+                Ok(token)
+            });
+        let found = stored.clone();
+        mock_repo
+            .expect_find_by_public_id()
+            .times(1)
+            .returning(move |public_id| {
+                Ok(found
+                    .lock()
+                    .unwrap()
+                    .clone()
+                    .filter(|t| t.public_id.as_deref() == Some(public_id)))
+            });
+        mock_repo
+            .expect_record_usage()
+            .with(eq(1), eq(Some("192.168.1.10".to_string())))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = create_service(mock_repo);
+        let new_token = service
+            .create_api_token(42, token_data("gateway"), ClientInfo::default())
+            .await
+            .unwrap();
+
+        let result = service
+            .authenticate(&new_token.token, Some("192.168.1.10".to_string()))
+            .await;
+
+        assert_eq!(result.unwrap().user_id, 42);
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_unknown_api_token() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+
+        mock_repo
+            .expect_find_by_public_id()
+            .with(eq("unknown"))
+            .times(1)
+            .returning(|_| Ok(None));
+
+        let service = create_service(mock_repo);
+        let result = service
+            .authenticate(&format!("{}unknown_secret", API_TOKEN_PREFIX), None)
+            .await;
+
+        match result.unwrap_err() {
+            AppError::AuthenticationError(msg) => assert_eq!(msg, "Invalid API token"),
+            _ => panic!("Expected AuthenticationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_wrong_secret() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        let service_for_hash = create_service(MockApiTokensRepositoryTrait::new());
+        let token_hash = hex::encode(
+            service_for_hash
+                .keyed_hash("right_secret")
+                .unwrap()
+                .finalize()
+                .into_bytes(),
+        );
+
+        mock_repo
+            .expect_find_by_public_id()
+            .with(eq("publicid0001"))
+            .times(1)
+            .returning(move |public_id| Ok(Some(api_token(42, public_id, &token_hash))));
+
+        let service = create_service(mock_repo);
+        let result = service
+            .authenticate("sh_publicid0001_wrongsecret", None)
+            .await;
+
+        match result.unwrap_err() {
+            AppError::AuthenticationError(msg) => assert_eq!(msg, "Invalid API token"),
+            _ => panic!("Expected AuthenticationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_malformed_token() {
+        let service = create_service(MockApiTokensRepositoryTrait::new());
+
+        let result = service.authenticate("sh_withoutseparator", None).await;
+
+        assert!(matches!(result, Err(AppError::AuthenticationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_expired_api_token() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        let service_for_hash = create_service(MockApiTokensRepositoryTrait::new());
+        let token_hash = hex::encode(
+            service_for_hash
+                .keyed_hash("secret")
+                .unwrap()
+                .finalize()
+                .into_bytes(),
+        );
+
+        mock_repo
+            .expect_find_by_public_id()
+            .with(eq("publicid0001"))
+            .times(1)
+            .returning(move |public_id| {
+                let mut token = api_token(42, public_id, &token_hash);
+                token.expires_at = Some(Utc::now() - chrono::Duration::hours(1));
+                Ok(Some(token))
+            });
+        mock_repo.expect_record_usage().never();
+
+        let service = create_service(mock_repo);
+        let result = service.authenticate("sh_publicid0001_secret", None).await;
+
+        match result.unwrap_err() {
+            AppError::AuthenticationError(msg) => assert_eq!(msg, "API token has expired"),
+            _ => panic!("Expected AuthenticationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_api_token_with_past_expiry() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        mock_repo.expect_create().never();
+
+        let service = create_service(mock_repo);
+        let result = service
+            .create_api_token(
+                1,
+                CreateApiToken {
+                    expires_at: Some(Utc::now() - chrono::Duration::minutes(1)),
+                    ..token_data("gateway")
+                },
+                ClientInfo::default(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_api_token() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        mock_repo
+            .expect_delete()
+            .with(eq(7), eq(1))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = create_service(mock_repo);
+        let result = service.delete_api_token(1, 7, ClientInfo::default()).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_create_house_restricted_api_token() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        let mut mock_access_control = MockAccessControlServiceTrait::new();
+
+        mock_access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::WriteMetrics))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        mock_repo
+            .expect_create()
+            .withf(|_, _, _, token_data| {
+                token_data.scopes == Some(vec![ApiTokenScope::MetricsWrite])
+                    && token_data.house_id == Some(10)
+            })
+            .times(1)
+            .returning(|user_id, public_id, hash, _| {
+                let mut token = api_token(user_id, public_id, hash);
+                token.scopes = vec!["metrics:write".to_string()];
+                token.house_id = Some(10);
+                Ok(token)
+            });
+
+        let service = ApiTokensService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(mock_access_control),
+            Arc::new(events_ignored()),
+        );
+        let result = service
+            .create_api_token(
+                1,
+                CreateApiToken {
+                    scopes: Some(vec![ApiTokenScope::MetricsWrite]),
+                    house_id: Some(10),
+                    ..token_data("gateway")
+                },
+                ClientInfo::default(),
+            )
+            .await;
+
+        let new_token = result.unwrap();
+        assert_eq!(new_token.scopes, vec!["metrics:write".to_string()]);
+        assert_eq!(new_token.house_id, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_create_api_token_for_inaccessible_house() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        let mut mock_access_control = MockAccessControlServiceTrait::new();
+
+        mock_access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::WriteMetrics))
+            .times(1)
+            .returning(|_, _, _| Err(AppError::AuthenticationError("Access denied".to_string())));
+        mock_repo.expect_create().never();
+
+        let service = ApiTokensService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(mock_access_control),
+            Arc::new(events_ignored()),
+        );
+        let result = service
+            .create_api_token(
+                1,
+                CreateApiToken {
+                    house_id: Some(10),
+                    ..token_data("gateway")
+                },
+                ClientInfo::default(),
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_api_token_with_scopes_beyond_role() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        let mut mock_access_control = MockAccessControlServiceTrait::new();
+
+        // A guest may view the house but not manage its devices
+        mock_access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ViewHouse))
+            .returning(|_, _, _| Ok(()));
+        mock_access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageDevices))
+            .times(1)
+            .returning(|_, _, _| {
+                Err(AppError::AuthorizationError(
+                    "The guest role does not allow this action".to_string(),
+                ))
+            });
+        mock_repo.expect_create().never();
+
+        let service = ApiTokensService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(mock_access_control),
+            Arc::new(events_ignored()),
+        );
+        let result = service
+            .create_api_token(
+                1,
+                CreateApiToken {
+                    scopes: Some(vec![
+                        ApiTokenScope::DevicesRead,
+                        ApiTokenScope::DevicesWrite,
+                    ]),
+                    house_id: Some(10),
+                    ..token_data("gateway")
+                },
+                ClientInfo::default(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::AuthorizationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_api_token_without_scopes() {
+        let mut mock_repo = MockApiTokensRepositoryTrait::new();
+        mock_repo.expect_create().never();
+
+        let service = create_service(mock_repo);
+        for scopes in [None, Some(vec![])] {
+            let result = service
+                .create_api_token(
+                    1,
+                    CreateApiToken {
+                        scopes,
+                        ..token_data("gateway")
+                    },
+                    ClientInfo::default(),
+                )
+                .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+        }
     }
 }
@@ -5,27 +5,66 @@ use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::Utc;
 use jsonwebtoken::{encode, EncodingKey, Header};
 use mockall::automock;
+use rand::{distr::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
 
 use crate::{
     config::Config,
     errors::{AppError, Result},
-    models::auth::{AuthResponse, Claims, LoginRequest, RegisterUser},
-    repositories::UserRepositoryTrait,
+    models::{
+        auth::{AuthResponse, Claims, LoginRequest, LoginResponse, RegisterUser},
+        auth_events::{AuthEventType, NewAuthEvent},
+        login_attempts::{AttemptScope, ThrottleKey},
+        sessions::{ClientInfo, TokenPair},
+        two_factor::{TwoFactorChallenge, TwoFactorChallengeClaims, TwoFactorLoginRequest},
+        users::User,
+    },
+    repositories::{SessionsRepositoryTrait, UserRepositoryTrait},
+    services::{
+        auth_events::AuthEventsServiceTrait, jwt_keys::JwtKeys,
+        login_throttle::LoginThrottleServiceTrait, two_factor::TwoFactorServiceTrait,
+    },
 };
 
+const REFRESH_TOKEN_LENGTH: usize = 48;
+const TWO_FACTOR_CHALLENGE_EXPIRES_IN: u64 = 300;
+const TWO_FACTOR_CHALLENGE_PURPOSE: &str = "two_factor_login";
+
 #[automock]
 #[async_trait]
 pub trait AuthServiceTrait {
-    async fn login(&self, login_request: LoginRequest) -> Result<AuthResponse>;
-    async fn register(&self, register_user: RegisterUser) -> Result<AuthResponse>; // Changed return type
+    /// Checks the password. Accounts with two-factor authentication get a
+    /// challenge for `login_two_factor` instead of tokens.
+    async fn login(&self, login_request: LoginRequest, client: ClientInfo)
+        -> Result<LoginResponse>;
+    /// Completes a login challenge with a TOTP or recovery code.
+    async fn login_two_factor(
+        &self,
+        request: TwoFactorLoginRequest,
+        client: ClientInfo,
+    ) -> Result<AuthResponse>;
+    async fn register(
+        &self,
+        register_user: RegisterUser,
+        client: ClientInfo,
+    ) -> Result<AuthResponse>; // Changed return type
+    /// Exchanges a refresh token for a new access token and rotates the refresh token.
+    async fn refresh(&self, refresh_token: &str, client: ClientInfo) -> Result<TokenPair>;
+    /// Revokes the session a refresh token belongs to.
+    async fn logout(&self, refresh_token: &str, client: ClientInfo) -> Result<()>;
     async fn validate_token(&self, token: &str) -> Result<Claims>;
-    fn generate_token(&self, user_id: i64) -> Result<String>;
+    fn generate_token(&self, user_id: i64, session_id: i64) -> Result<String>;
 }
 
 #[derive(Clone)]
 pub struct AuthService {
     config: Config,
     user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+    sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+    two_factor_service: Arc<dyn TwoFactorServiceTrait + Send + Sync>,
+    login_throttle_service: Arc<dyn LoginThrottleServiceTrait + Send + Sync>,
+    auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
+    jwt_keys: Arc<JwtKeys>,
 }
 
 use validator::ValidationErrors;
@@ -34,11 +73,193 @@ impl AuthService {
     pub fn new(
         config: Config,
         user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+        sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+        two_factor_service: Arc<dyn TwoFactorServiceTrait + Send + Sync>,
+        login_throttle_service: Arc<dyn LoginThrottleServiceTrait + Send + Sync>,
+        auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
+        jwt_keys: Arc<JwtKeys>,
     ) -> Self {
         Self {
             config,
             user_repository,
+            sessions_repository,
+            two_factor_service,
+            login_throttle_service,
+            auth_events_service,
+            jwt_keys,
+        }
+    }
+
+    /// Keys failed attempts are counted against: the email address and, when
+    /// known, the client IP address.
+    fn throttle_keys(email: Option<&str>, client: &ClientInfo) -> Vec<ThrottleKey> {
+        email
+            .map(ThrottleKey::account)
+            .into_iter()
+            .chain(client.ip.as_deref().map(ThrottleKey::ip))
+            .collect()
+    }
+
+    /// Forgets failures of the account after a successful login. Failures of
+    /// the IP address are left to age out, so logging into an account of one's
+    /// own does not clear the address' failures from guessing at others.
+    async fn reset_account_throttle(&self, keys: &[ThrottleKey]) -> Result<()> {
+        let account_keys: Vec<ThrottleKey> = keys
+            .iter()
+            .filter(|key| key.scope == AttemptScope::Account)
+            .cloned()
+            .collect();
+        self.login_throttle_service.reset(&account_keys).await
+    }
+
+    /// Checks a login step, counting a failure if it did not succeed.
+    async fn throttled<T>(&self, keys: &[ThrottleKey], result: Result<T>) -> Result<T> {
+        if result.is_err() {
+            self.login_throttle_service.record_failure(keys).await?;
         }
+        result
+    }
+
+    async fn record_login_failure(
+        &self,
+        user_id: Option<i64>,
+        email: &str,
+        client: &ClientInfo,
+        reason: &str,
+    ) {
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::LoginFailed, user_id, client)
+                    .with_email(email)
+                    .with_details(reason),
+            )
+            .await;
+    }
+
+    fn generate_refresh_token() -> String {
+        rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(REFRESH_TOKEN_LENGTH)
+            .map(char::from)
+            .collect()
+    }
+
+    /// Refresh tokens are random and long, so an unsalted SHA-256 is enough
+    /// to keep them out of the database while allowing indexed lookups.
+    fn hash_refresh_token(refresh_token: &str) -> String {
+        hex::encode(Sha256::digest(refresh_token.as_bytes()))
+    }
+
+    /// Opens a new session and issues its access and refresh tokens.
+    async fn start_session(&self, user_id: i64, client: ClientInfo) -> Result<TokenPair> {
+        let refresh_token = Self::generate_refresh_token();
+        let expires_at = Utc::now()
+            .checked_add_signed(chrono::Duration::seconds(
+                self.config.refresh_token_expires_in as i64,
+            ))
+            .ok_or_else(|| AppError::InternalServerError("Invalid timestamp".to_string()))?;
+
+        let session = self
+            .sessions_repository
+            .create(
+                user_id,
+                &Self::hash_refresh_token(&refresh_token),
+                &client,
+                expires_at,
+            )
+            .await?;
+
+        Ok(TokenPair {
+            token: self.generate_token(user_id, session.id)?,
+            refresh_token,
+        })
+    }
+
+    fn generate_two_factor_challenge(&self, user_id: i64) -> Result<TwoFactorChallenge> {
+        let expiration = Utc::now()
+            .checked_add_signed(chrono::Duration::seconds(
+                TWO_FACTOR_CHALLENGE_EXPIRES_IN as i64,
+            ))
+            .ok_or_else(|| AppError::InternalServerError("Invalid timestamp".to_string()))?
+            .timestamp() as usize;
+
+        let claims = TwoFactorChallengeClaims {
+            sub: user_id,
+            exp: expiration,
+            purpose: TWO_FACTOR_CHALLENGE_PURPOSE.to_string(),
+        };
+
+        // Always signed with the shared secret: challenges are only checked by
+        // this service and must never pass as access tokens elsewhere.
+        let challenge_token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.config.jwt_secret.as_ref()),
+        )?;
+
+        Ok(TwoFactorChallenge {
+            two_factor_required: true,
+            challenge_token,
+            expires_in: TWO_FACTOR_CHALLENGE_EXPIRES_IN,
+        })
+    }
+
+    /// Returns the user id of a valid challenge token.
+    fn validate_two_factor_challenge(&self, challenge_token: &str) -> Result<i64> {
+        let invalid =
+            || AppError::AuthenticationError("Invalid or expired challenge token".to_string());
+
+        let token_data = jsonwebtoken::decode::<TwoFactorChallengeClaims>(
+            challenge_token,
+            &jsonwebtoken::DecodingKey::from_secret(self.config.jwt_secret.as_ref()),
+            &jsonwebtoken::Validation::default(),
+        )
+        .map_err(|_| invalid())?;
+
+        if token_data.claims.purpose != TWO_FACTOR_CHALLENGE_PURPOSE {
+            return Err(invalid());
+        }
+
+        Ok(token_data.claims.sub)
+    }
+
+    fn reject_disabled(user: &User) -> Result<()> {
+        if user.disabled_at.is_some() {
+            return Err(AppError::AuthorizationError(
+                "Account is disabled".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Records the login and opens a session for a fully authenticated user.
+    async fn complete_login(&self, mut user: User, client: ClientInfo) -> Result<AuthResponse> {
+        if self
+            .user_repository
+            .update_last_login(user.id)
+            .await
+            .is_err()
+        {
+            return Err(AppError::InternalServerError(
+                "Failed to update last login".to_string(),
+            ));
+        }
+        user.last_login_at = Some(Utc::now());
+
+        let tokens = self.start_session(user.id, client.clone()).await?;
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::LoginSucceeded, Some(user.id), &client)
+                    .with_email(&user.email),
+            )
+            .await;
+
+        Ok(AuthResponse {
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
+            user,
+        })
     }
 
     async fn hash_password(&self, password: &str) -> Result<String> {
@@ -83,50 +304,141 @@ impl AuthService {
 
 #[async_trait]
 impl AuthServiceTrait for AuthService {
-    async fn login(&self, login_request: LoginRequest) -> Result<AuthResponse> {
-        let password_data = self
-            .user_repository
-            .get_password_hash_by_email(login_request.email.as_ref())
-            .await
-            .map_err(|_| AppError::AuthenticationError("User not found".to_string()))?;
+    async fn login(
+        &self,
+        login_request: LoginRequest,
+        client: ClientInfo,
+    ) -> Result<LoginResponse> {
+        let email = login_request.email.as_str();
+        let keys = Self::throttle_keys(Some(email), &client);
+        if let Err(e) = self.login_throttle_service.check(&keys).await {
+            self.record_login_failure(None, email, &client, &e.to_string())
+                .await;
+            return Err(e);
+        }
 
-        if !self
+        let password_data = match self.user_repository.get_password_hash_by_email(email).await {
+            Ok(password_data) => password_data,
+            Err(_) => {
+                self.record_login_failure(None, email, &client, "Unknown email")
+                    .await;
+                return self
+                    .throttled(
+                        &keys,
+                        Err(AppError::AuthenticationError("User not found".to_string())),
+                    )
+                    .await;
+            }
+        };
+
+        let is_valid = self
             .verify_password(
                 login_request.password.as_ref(),
                 &password_data.password_hash,
             )
-            .await?
-        {
-            return Err(AppError::AuthenticationError(
-                "Invalid credentials".to_string(),
-            ));
+            .await?;
+        if !is_valid {
+            self.record_login_failure(Some(password_data.id), email, &client, "Invalid password")
+                .await;
+            return self
+                .throttled(
+                    &keys,
+                    Err(AppError::AuthenticationError(
+                        "Invalid credentials".to_string(),
+                    )),
+                )
+                .await;
         }
 
-        if self
+        let user = self
             .user_repository
-            .update_last_login(password_data.id)
-            .await
-            .is_err()
-        {
-            return Err(AppError::InternalServerError(
-                "Failed to update last login".to_string(),
+            .get_user_by_id(password_data.id)
+            .await?;
+        if let Err(e) = Self::reject_disabled(&user) {
+            self.record_login_failure(Some(user.id), email, &client, "Account is disabled")
+                .await;
+            return Err(e);
+        }
+
+        if self.config.require_email_verification && user.email_verified_at.is_none() {
+            self.record_login_failure(
+                Some(user.id),
+                email,
+                &client,
+                "Email address is not verified",
+            )
+            .await;
+            return Err(AppError::AuthorizationError(
+                "Email address is not verified".to_string(),
             ));
         }
 
-        let token = self.generate_token(password_data.id)?;
+        if self.two_factor_service.is_enabled(user.id).await? {
+            return Ok(LoginResponse::TwoFactorRequired(
+                self.generate_two_factor_challenge(user.id)?,
+            ));
+        }
 
-        let user = self
-            .user_repository
-            .get_user_by_id(password_data.id)
-            .await?;
+        let auth_response = self.complete_login(user, client).await?;
+        self.reset_account_throttle(&keys).await?;
 
-        Ok(AuthResponse { token, user })
+        Ok(LoginResponse::Authenticated(auth_response))
     }
 
-    async fn register(&self, register_user: RegisterUser) -> Result<AuthResponse> {
+    async fn login_two_factor(
+        &self,
+        request: TwoFactorLoginRequest,
+        client: ClientInfo,
+    ) -> Result<AuthResponse> {
+        let user_id = self.validate_two_factor_challenge(&request.challenge_token)?;
+        let user = self.user_repository.get_user_by_id(user_id).await?;
+        if let Err(e) = Self::reject_disabled(&user) {
+            self.record_login_failure(Some(user_id), &user.email, &client, "Account is disabled")
+                .await;
+            return Err(e);
+        }
+
+        let keys = Self::throttle_keys(Some(&user.email), &client);
+        if let Err(e) = self.login_throttle_service.check(&keys).await {
+            self.record_login_failure(Some(user_id), &user.email, &client, &e.to_string())
+                .await;
+            return Err(e);
+        }
+
+        let verified = self
+            .two_factor_service
+            .verify(user_id, request.code.trim())
+            .await;
+        if verified.is_err() {
+            self.record_login_failure(
+                Some(user_id),
+                &user.email,
+                &client,
+                "Invalid two-factor code",
+            )
+            .await;
+        }
+        self.throttled(&keys, verified).await?;
+
+        let auth_response = self.complete_login(user, client).await?;
+        self.reset_account_throttle(&keys).await?;
+
+        Ok(auth_response)
+    }
+
+    async fn register(
+        &self,
+        register_user: RegisterUser,
+        client: ClientInfo,
+    ) -> Result<AuthResponse> {
+        // Signup reveals whether an email is taken, so it is limited per IP address
+        let keys = Self::throttle_keys(None, &client);
+        self.login_throttle_service.check(&keys).await?;
+
         let email = register_user.email.as_ref();
         let phone = register_user.phone.as_ref();
-        self.validate_unique_email_and_phone(email, phone).await?;
+        let unique = self.validate_unique_email_and_phone(email, phone).await;
+        self.throttled(&keys, unique).await?;
 
         let password = register_user.password.as_ref();
         let hashed_password = self.hash_password(password).await?;
@@ -135,27 +447,82 @@ impl AuthServiceTrait for AuthService {
         new_user.password = hashed_password;
 
         let user = self.user_repository.create_user(new_user).await?;
-        let token = self.generate_token(user.id)?;
+        let tokens = self.start_session(user.id, client.clone()).await?;
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::Signup, Some(user.id), &client)
+                    .with_email(&user.email),
+            )
+            .await;
+
+        Ok(AuthResponse {
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
+            user,
+        })
+    }
+
+    async fn refresh(&self, refresh_token: &str, client: ClientInfo) -> Result<TokenPair> {
+        let new_refresh_token = Self::generate_refresh_token();
+        let session = self
+            .sessions_repository
+            .rotate(
+                &Self::hash_refresh_token(refresh_token),
+                &Self::hash_refresh_token(&new_refresh_token),
+                &client,
+            )
+            .await?
+            .ok_or_else(|| AppError::AuthenticationError("Invalid refresh token".to_string()))?;
+
+        Ok(TokenPair {
+            token: self.generate_token(session.user_id, session.id)?,
+            refresh_token: new_refresh_token,
+        })
+    }
+
+    async fn logout(&self, refresh_token: &str, client: ClientInfo) -> Result<()> {
+        let session = self
+            .sessions_repository
+            .find_active_by_refresh_token_hash(&Self::hash_refresh_token(refresh_token))
+            .await?
+            .ok_or_else(|| AppError::AuthenticationError("Invalid refresh token".to_string()))?;
+
+        self.sessions_repository
+            .revoke(session.id, session.user_id)
+            .await?;
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::TokenRevoked, Some(session.user_id), &client)
+                    .with_details(format!("Signed out of session {}", session.id)),
+            )
+            .await;
 
-        Ok(AuthResponse { token, user })
+        Ok(())
     }
 
     async fn validate_token(&self, token: &str) -> Result<Claims> {
-        let token_data = jsonwebtoken::decode::<Claims>(
-            token,
-            &jsonwebtoken::DecodingKey::from_secret(self.config.jwt_secret.as_ref()),
-            &jsonwebtoken::Validation::default(),
-        )?;
+        let token_data = self.jwt_keys.decode::<Claims>(token)?;
 
         // Verify user still exists
         self.user_repository
             .get_user_by_id(token_data.claims.sub)
             .await?;
 
+        // Verify the session has not been revoked
+        if !self
+            .sessions_repository
+            .is_active(token_data.claims.sid, token_data.claims.sub)
+            .await?
+        {
+            return Err(AppError::AuthenticationError(
+                "Session has been revoked".to_string(),
+            ));
+        }
+
         Ok(token_data.claims)
     }
 
-    fn generate_token(&self, user_id: i64) -> Result<String> {
+    fn generate_token(&self, user_id: i64, session_id: i64) -> Result<String> {
         let expiration = Utc::now()
             .checked_add_signed(chrono::Duration::seconds(self.config.jwt_expires_in as i64))
             .ok_or_else(|| AppError::InternalServerError("Invalid timestamp".to_string()))?
@@ -164,15 +531,10 @@ impl AuthServiceTrait for AuthService {
         let claims = Claims {
             sub: user_id,
             exp: expiration,
+            sid: session_id,
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.config.jwt_secret.as_ref()),
-        )?;
-
-        Ok(token)
+        self.jwt_keys.encode(&claims)
     }
 }
 
@@ -181,9 +543,15 @@ mod tests {
     use mockall::predicate::eq;
 
     use super::*;
+    use crate::config::JwtAlgorithm;
     use crate::models::auth::PasswordHash;
+    use crate::models::sessions::Session;
     use crate::models::users::{User, UserRole};
+    use crate::repositories::sessions_repository::MockSessionsRepositoryTrait;
     use crate::repositories::user_repository::MockUserRepositoryTrait;
+    use crate::services::auth_events::MockAuthEventsServiceTrait;
+    use crate::services::login_throttle::MockLoginThrottleServiceTrait;
+    use crate::services::two_factor::MockTwoFactorServiceTrait;
 
     fn create_test_config() -> Config {
         Config {
@@ -194,11 +562,72 @@ mod tests {
             db_user: "test".to_string(),
             db_pass: "test".to_string(),
             jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
             jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
             frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
+        }
+    }
+
+    fn session(id: i64, user_id: i64) -> Session {
+        Session {
+            id,
+            user_id,
+            refresh_token_hash: "hash".to_string(),
+            user_agent: None,
+            ip: None,
+            created_at: Utc::now(),
+            last_used_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::days(30),
+            revoked_at: None,
         }
     }
 
+    // Sessions repository that accepts any new session for `user_id`
+    fn sessions_creating(user_id: i64) -> MockSessionsRepositoryTrait {
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+        mock_sessions
+            .expect_create()
+            .withf(move |uid, hash, _, _| *uid == user_id && hash.len() == 64)
+            .times(1)
+            .returning(|user_id, _, _, _| Ok(session(7, user_id)));
+        mock_sessions
+    }
+
+    fn two_factor_disabled() -> MockTwoFactorServiceTrait {
+        let mut mock_two_factor = MockTwoFactorServiceTrait::new();
+        mock_two_factor.expect_is_enabled().returning(|_| Ok(false));
+        mock_two_factor
+    }
+
+    // Audit log that accepts any event
+    fn events_ignored() -> MockAuthEventsServiceTrait {
+        let mut mock_events = MockAuthEventsServiceTrait::new();
+        mock_events.expect_record().returning(|_| ());
+        mock_events
+    }
+
+    fn jwt_keys() -> Arc<JwtKeys> {
+        Arc::new(JwtKeys::from_config(&create_test_config()).unwrap())
+    }
+
+    // Throttle that never locks anything out
+    fn throttle_allowing() -> MockLoginThrottleServiceTrait {
+        let mut mock_throttle = MockLoginThrottleServiceTrait::new();
+        mock_throttle.expect_check().returning(|_| Ok(()));
+        mock_throttle.expect_record_failure().returning(|_| Ok(()));
+        mock_throttle.expect_reset().returning(|_| Ok(()));
+        mock_throttle
+    }
+
     #[tokio::test]
     async fn test_successful_login() {
         let mut mock_repo = MockUserRepositoryTrait::new();
@@ -219,6 +648,8 @@ mod tests {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
             phone: "1234567890".to_string(),
         };
 
@@ -240,19 +671,32 @@ mod tests {
             .times(1)
             .returning(|_| Ok(()));
 
-        let auth_service = AuthService::new(config, Arc::new(mock_repo));
+        let auth_service = AuthService::new(
+            config,
+            Arc::new(mock_repo),
+            Arc::new(sessions_creating(1)),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
 
         let login_request = LoginRequest {
             email: "test@example.com".to_string(),
             password: "password123".to_string(),
         };
 
-        let result = auth_service.login(login_request).await;
+        let result = auth_service
+            .login(login_request, ClientInfo::default())
+            .await;
         assert!(result.is_ok());
 
-        let auth_response = result.unwrap();
+        let LoginResponse::Authenticated(auth_response) = result.unwrap() else {
+            panic!("Expected tokens");
+        };
         assert_eq!(auth_response.user.id, 1);
         assert!(!auth_response.token.is_empty());
+        assert_eq!(auth_response.refresh_token.len(), REFRESH_TOKEN_LENGTH);
     }
 
     #[tokio::test]
@@ -271,15 +715,39 @@ mod tests {
             .with(mockall::predicate::eq("test@example.com"))
             .times(1)
             .returning(move |_| Ok(password_data.clone()));
+        let mut mock_events = MockAuthEventsServiceTrait::new();
+        mock_events
+            .expect_record()
+            .withf(|event| {
+                event.event_type == AuthEventType::LoginFailed
+                    && event.user_id == Some(1)
+                    && event.email.as_deref() == Some("test@example.com")
+                    && event.details.as_deref() == Some("Invalid password")
+                    && event.client.ip.as_deref() == Some("203.0.113.7")
+            })
+            .times(1)
+            .returning(|_| ());
 
-        let auth_service = AuthService::new(config, Arc::new(mock_repo));
+        let auth_service = AuthService::new(
+            config,
+            Arc::new(mock_repo),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(mock_events),
+            jwt_keys(),
+        );
 
         let login_request = LoginRequest {
             email: "test@example.com".to_string(),
             password: "wrongpassword".to_string(),
         };
+        let client = ClientInfo {
+            user_agent: None,
+            ip: Some("203.0.113.7".to_string()),
+        };
 
-        let result = auth_service.login(login_request).await;
+        let result = auth_service.login(login_request, client).await;
         assert!(result.is_err());
 
         match result.unwrap_err() {
@@ -290,6 +758,119 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_login_of_unverified_user_when_verification_required() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+        let mut config = create_test_config();
+        config.require_email_verification = true;
+
+        let password_data = PasswordHash {
+            id: 1,
+            password_hash: hash("password123", DEFAULT_COST).unwrap(),
+        };
+        let user = User {
+            id: 1,
+            email: "test@example.com".to_string(),
+            first_name: "John".to_string(),
+            last_name: "Doe".to_string(),
+            role: UserRole::User,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
+            phone: "1234567890".to_string(),
+        };
+
+        mock_repo
+            .expect_get_password_hash_by_email()
+            .returning(move |_| Ok(password_data.clone()));
+        mock_repo
+            .expect_get_user_by_id()
+            .returning(move |_| Ok(user.clone()));
+        mock_repo.expect_update_last_login().never();
+
+        let auth_service = AuthService::new(
+            config,
+            Arc::new(mock_repo),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let login_request = LoginRequest {
+            email: "test@example.com".to_string(),
+            password: "password123".to_string(),
+        };
+
+        let result = auth_service
+            .login(login_request, ClientInfo::default())
+            .await;
+
+        match result {
+            Err(AppError::AuthorizationError(msg)) => {
+                assert_eq!(msg, "Email address is not verified")
+            }
+            _ => panic!("Expected AuthorizationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_of_disabled_user() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+
+        mock_repo
+            .expect_get_password_hash_by_email()
+            .returning(|_| {
+                Ok(PasswordHash {
+                    id: 1,
+                    password_hash: hash("password123", DEFAULT_COST).unwrap(),
+                })
+            });
+        mock_repo.expect_get_user_by_id().returning(|_| {
+            Ok(User {
+                id: 1,
+                email: "test@example.com".to_string(),
+                first_name: "John".to_string(),
+                last_name: "Doe".to_string(),
+                role: UserRole::User,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                last_login_at: None,
+                email_verified_at: None,
+                disabled_at: Some(Utc::now()),
+                phone: "1234567890".to_string(),
+            })
+        });
+        mock_repo.expect_update_last_login().never();
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let login_request = LoginRequest {
+            email: "test@example.com".to_string(),
+            password: "password123".to_string(),
+        };
+
+        let result = auth_service
+            .login(login_request, ClientInfo::default())
+            .await;
+
+        match result {
+            Err(AppError::AuthorizationError(msg)) => assert_eq!(msg, "Account is disabled"),
+            _ => panic!("Expected AuthorizationError"),
+        }
+    }
+
     #[tokio::test]
     async fn test_login_with_nonexistent_user() {
         let mut mock_repo = MockUserRepositoryTrait::new();
@@ -301,14 +882,24 @@ mod tests {
             .times(1)
             .returning(|_| Err(AppError::NotFound("User not found".to_string())));
 
-        let auth_service = AuthService::new(config, Arc::new(mock_repo));
+        let auth_service = AuthService::new(
+            config,
+            Arc::new(mock_repo),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
 
         let login_request = LoginRequest {
             email: "nonexistent@example.com".to_string(),
             password: "password123".to_string(),
         };
 
-        let result = auth_service.login(login_request).await;
+        let result = auth_service
+            .login(login_request, ClientInfo::default())
+            .await;
         assert!(result.is_err());
 
         match result.unwrap_err() {
@@ -332,6 +923,8 @@ mod tests {
             email: "test@example.com".to_string(),
             created_at: Utc::now(),
             last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
             role: UserRole::User,
             updated_at: Utc::now(),
         };
@@ -353,7 +946,15 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(expected_user.clone()));
 
-        let auth_service = AuthService::new(config, Arc::new(mock_repo));
+        let auth_service = AuthService::new(
+            config,
+            Arc::new(mock_repo),
+            Arc::new(sessions_creating(1)),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
 
         let register_request = RegisterUser {
             first_name: "John".to_string(),
@@ -363,7 +964,9 @@ mod tests {
             password: "password123".to_string(),
         };
 
-        let result = auth_service.register(register_request).await;
+        let result = auth_service
+            .register(register_request, ClientInfo::default())
+            .await;
         assert!(result.is_ok());
 
         let resp = result.unwrap();
@@ -393,6 +996,8 @@ mod tests {
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
                     last_login_at: None,
+                    email_verified_at: None,
+                    disabled_at: None,
                 }))
             });
 
@@ -402,7 +1007,15 @@ mod tests {
             .times(1)
             .returning(|_| Ok(None));
 
-        let auth_service = AuthService::new(config, Arc::new(mock_repo));
+        let auth_service = AuthService::new(
+            config,
+            Arc::new(mock_repo),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
 
         let register_request = RegisterUser {
             first_name: "John".to_string(),
@@ -412,7 +1025,9 @@ mod tests {
             password: "password123".to_string(),
         };
 
-        let result = auth_service.register(register_request).await;
+        let result = auth_service
+            .register(register_request, ClientInfo::default())
+            .await;
         assert!(result.is_err());
 
         match result.unwrap_err() {
@@ -442,15 +1057,32 @@ mod tests {
                     created_at: chrono::Utc::now(),
                     updated_at: chrono::Utc::now(),
                     last_login_at: None,
+                    email_verified_at: None,
+                    disabled_at: None,
                 })
             });
 
         let mock_repo = Arc::new(mock_repo);
 
-        let config = create_test_config();
-        let auth_service = AuthService::new(config, mock_repo.clone());
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+        mock_sessions
+            .expect_is_active()
+            .with(eq(7), eq(1))
+            .times(1)
+            .returning(|_, _| Ok(true));
 
-        let token = auth_service.generate_token(1).unwrap();
+        let config = create_test_config();
+        let auth_service = AuthService::new(
+            config,
+            mock_repo.clone(),
+            Arc::new(mock_sessions),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let token = auth_service.generate_token(1, 7).unwrap();
         assert!(!token.is_empty());
 
         let claims = auth_service.validate_token(&token).await;
@@ -472,6 +1104,8 @@ mod tests {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
         };
 
         mock_repo
@@ -480,13 +1114,324 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(user_entity.clone()));
 
-        let auth_service = AuthService::new(config, Arc::new(mock_repo));
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+        mock_sessions
+            .expect_is_active()
+            .with(eq(7), eq(1))
+            .times(1)
+            .returning(|_, _| Ok(true));
 
-        let token = auth_service.generate_token(1).unwrap();
+        let auth_service = AuthService::new(
+            config,
+            Arc::new(mock_repo),
+            Arc::new(mock_sessions),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let token = auth_service.generate_token(1, 7).unwrap();
         let result = auth_service.validate_token(&token).await;
 
         assert!(result.is_ok());
         let claims = result.unwrap();
         assert_eq!(claims.sub, 1);
+        assert_eq!(claims.sid, 7);
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_with_revoked_session() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+        mock_repo.expect_get_user_by_id().returning(|_| {
+            Ok(User {
+                id: 1,
+                first_name: "John".to_string(),
+                last_name: "Doe".to_string(),
+                phone: "1234567890".to_string(),
+                email: "test@example.com".to_string(),
+                role: UserRole::User,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                last_login_at: None,
+                email_verified_at: None,
+                disabled_at: None,
+            })
+        });
+
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+        mock_sessions
+            .expect_is_active()
+            .with(eq(7), eq(1))
+            .times(1)
+            .returning(|_, _| Ok(false));
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(mock_sessions),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let token = auth_service.generate_token(1, 7).unwrap();
+        let result = auth_service.validate_token(&token).await;
+
+        match result.unwrap_err() {
+            AppError::AuthenticationError(msg) => assert_eq!(msg, "Session has been revoked"),
+            _ => panic!("Expected AuthenticationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotates_refresh_token() {
+        let refresh_token = "a".repeat(REFRESH_TOKEN_LENGTH);
+        let refresh_token_hash = AuthService::hash_refresh_token(&refresh_token);
+
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+        mock_sessions
+            .expect_rotate()
+            .withf(move |hash, new_hash, _| *hash == refresh_token_hash && new_hash != hash)
+            .times(1)
+            .returning(|_, _, _| Ok(Some(session(7, 1))));
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(MockUserRepositoryTrait::new()),
+            Arc::new(mock_sessions),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let tokens = auth_service
+            .refresh(&refresh_token, ClientInfo::default())
+            .await
+            .unwrap();
+
+        assert_ne!(tokens.refresh_token, refresh_token);
+        let claims = jsonwebtoken::decode::<Claims>(
+            &tokens.token,
+            &jsonwebtoken::DecodingKey::from_secret(create_test_config().jwt_secret.as_ref()),
+            &jsonwebtoken::Validation::default(),
+        )
+        .unwrap()
+        .claims;
+        assert_eq!(claims.sub, 1);
+        assert_eq!(claims.sid, 7);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_with_unknown_token() {
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+        mock_sessions
+            .expect_rotate()
+            .times(1)
+            .returning(|_, _, _| Ok(None));
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(MockUserRepositoryTrait::new()),
+            Arc::new(mock_sessions),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let result = auth_service.refresh("unknown", ClientInfo::default()).await;
+
+        match result.unwrap_err() {
+            AppError::AuthenticationError(msg) => assert_eq!(msg, "Invalid refresh token"),
+            _ => panic!("Expected AuthenticationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_session() {
+        let mut mock_sessions = MockSessionsRepositoryTrait::new();
+        mock_sessions
+            .expect_find_active_by_refresh_token_hash()
+            .times(1)
+            .returning(|_| Ok(Some(session(7, 1))));
+        mock_sessions
+            .expect_revoke()
+            .with(eq(7), eq(1))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(MockUserRepositoryTrait::new()),
+            Arc::new(mock_sessions),
+            Arc::new(two_factor_disabled()),
+            Arc::new(throttle_allowing()),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        assert!(auth_service
+            .logout("refresh", ClientInfo::default())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_login_when_locked_out() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+        mock_repo.expect_get_password_hash_by_email().never();
+
+        let mut mock_throttle = MockLoginThrottleServiceTrait::new();
+        mock_throttle
+            .expect_check()
+            .withf(|keys| {
+                keys == [
+                    ThrottleKey::account("test@example.com"),
+                    ThrottleKey::ip("10.0.0.1"),
+                ]
+            })
+            .times(1)
+            .returning(|_| {
+                Err(AppError::TooManyRequests {
+                    message: "Too many failed attempts, try again later".to_string(),
+                    retry_after: 30,
+                })
+            });
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(two_factor_disabled()),
+            Arc::new(mock_throttle),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let login_request = LoginRequest {
+            email: "Test@example.com".to_string(),
+            password: "password123".to_string(),
+        };
+        let client = ClientInfo {
+            user_agent: None,
+            ip: Some("10.0.0.1".to_string()),
+        };
+
+        let result = auth_service.login(login_request, client).await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::TooManyRequests {
+                retry_after: 30,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_login_is_recorded() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+        mock_repo
+            .expect_get_password_hash_by_email()
+            .returning(|_| {
+                Ok(PasswordHash {
+                    id: 1,
+                    password_hash: hash("password123", DEFAULT_COST).unwrap(),
+                })
+            });
+
+        let mut mock_throttle = MockLoginThrottleServiceTrait::new();
+        mock_throttle.expect_check().returning(|_| Ok(()));
+        mock_throttle
+            .expect_record_failure()
+            .withf(|keys| keys == [ThrottleKey::account("test@example.com")])
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_throttle.expect_reset().never();
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(MockSessionsRepositoryTrait::new()),
+            Arc::new(two_factor_disabled()),
+            Arc::new(mock_throttle),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let login_request = LoginRequest {
+            email: "test@example.com".to_string(),
+            password: "wrongpassword".to_string(),
+        };
+
+        let result = auth_service
+            .login(login_request, ClientInfo::default())
+            .await;
+
+        assert!(matches!(result, Err(AppError::AuthenticationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_resets_account_failures_only() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+        mock_repo
+            .expect_get_password_hash_by_email()
+            .returning(|_| {
+                Ok(PasswordHash {
+                    id: 1,
+                    password_hash: hash("password123", DEFAULT_COST).unwrap(),
+                })
+            });
+        mock_repo.expect_get_user_by_id().returning(|_| {
+            Ok(User {
+                id: 1,
+                email: "test@example.com".to_string(),
+                first_name: "John".to_string(),
+                last_name: "Doe".to_string(),
+                role: UserRole::User,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                last_login_at: None,
+                email_verified_at: None,
+                disabled_at: None,
+                phone: "1234567890".to_string(),
+            })
+        });
+        mock_repo.expect_update_last_login().returning(|_| Ok(()));
+
+        let mut mock_throttle = MockLoginThrottleServiceTrait::new();
+        mock_throttle.expect_check().returning(|_| Ok(()));
+        mock_throttle.expect_record_failure().never();
+        mock_throttle
+            .expect_reset()
+            .withf(|keys| keys == [ThrottleKey::account("test@example.com")])
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let auth_service = AuthService::new(
+            create_test_config(),
+            Arc::new(mock_repo),
+            Arc::new(sessions_creating(1)),
+            Arc::new(two_factor_disabled()),
+            Arc::new(mock_throttle),
+            Arc::new(events_ignored()),
+            jwt_keys(),
+        );
+
+        let login_request = LoginRequest {
+            email: "test@example.com".to_string(),
+            password: "password123".to_string(),
+        };
+
+        let client = ClientInfo {
+            ip: Some("203.0.113.7".to_string()),
+            ..Default::default()
+        };
+        let result = auth_service.login(login_request, client).await;
+
+        assert!(matches!(result, Ok(LoginResponse::Authenticated(_))));
     }
 }
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{
+    errors::Result,
+    models::auth_events::{AuthEvent, AuthEventFilters, NewAuthEvent, SecurityEventsQuery},
+    repositories::AuthEventsRepositoryTrait,
+};
+
+/// Authentication audit log.
+#[automock]
+#[async_trait]
+pub trait AuthEventsServiceTrait {
+    /// Stores an event. Failures are logged instead of returned, so a broken
+    /// audit log never locks users out.
+    async fn record(&self, event: NewAuthEvent);
+    /// Events of one user, newest first.
+    async fn list_for_user(
+        &self,
+        user_id: i64,
+        query: SecurityEventsQuery,
+    ) -> Result<Vec<AuthEvent>>;
+    /// Events of all users, newest first.
+    async fn search(&self, filters: AuthEventFilters) -> Result<Vec<AuthEvent>>;
+}
+
+#[derive(Clone)]
+pub struct AuthEventsService {
+    auth_events_repository: Arc<dyn AuthEventsRepositoryTrait + Send + Sync>,
+}
+
+impl AuthEventsService {
+    pub fn new(auth_events_repository: Arc<dyn AuthEventsRepositoryTrait + Send + Sync>) -> Self {
+        Self {
+            auth_events_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl AuthEventsServiceTrait for AuthEventsService {
+    async fn record(&self, event: NewAuthEvent) {
+        if let Err(e) = self.auth_events_repository.create(&event).await {
+            tracing::warn!("Failed to record {} auth event: {}", event.event_type, e);
+        }
+    }
+
+    async fn list_for_user(
+        &self,
+        user_id: i64,
+        query: SecurityEventsQuery,
+    ) -> Result<Vec<AuthEvent>> {
+        self.auth_events_repository
+            .search(&AuthEventFilters {
+                user_id: Some(user_id),
+                event_type: query.event_type,
+                limit: query.limit,
+                offset: query.offset,
+                ..Default::default()
+            })
+            .await
+    }
+
+    async fn search(&self, filters: AuthEventFilters) -> Result<Vec<AuthEvent>> {
+        self.auth_events_repository.search(&filters).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::AppError,
+        models::{auth_events::AuthEventType, sessions::ClientInfo},
+        repositories::auth_events_repository::MockAuthEventsRepositoryTrait,
+    };
+
+    #[tokio::test]
+    async fn test_record_ignores_storage_errors() {
+        let mut mock_repo = MockAuthEventsRepositoryTrait::new();
+        mock_repo
+            .expect_create()
+            .times(1)
+            .returning(|_| Err(AppError::InternalServerError("down".to_string())));
+
+        let service = AuthEventsService::new(Arc::new(mock_repo));
+
+        service
+            .record(NewAuthEvent::new(
+                AuthEventType::LoginFailed,
+                None,
+                &ClientInfo::default(),
+            ))
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_list_for_user_is_limited_to_the_user() {
+        let mut mock_repo = MockAuthEventsRepositoryTrait::new();
+        mock_repo
+            .expect_search()
+            .withf(|filters| {
+                *filters
+                    == AuthEventFilters {
+                        user_id: Some(1),
+                        event_type: Some(AuthEventType::TokenUsed),
+                        limit: Some(10),
+                        ..Default::default()
+                    }
+            })
+            .times(1)
+            .returning(|_| Ok(vec![]));
+
+        let service = AuthEventsService::new(Arc::new(mock_repo));
+
+        let events = service
+            .list_for_user(
+                1,
+                SecurityEventsQuery {
+                    event_type: Some(AuthEventType::TokenUsed),
+                    limit: Some(10),
+                    offset: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(events.is_empty());
+    }
+}
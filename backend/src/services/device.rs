@@ -6,7 +6,12 @@ use validator::Validate;
 
 use crate::{
     errors::Result,
-    models::devices::{CreateDevice, Device, UpdateDevice},
+    models::{
+        device_types::{DEFAULT_OFFLINE_AFTER_SECONDS, DEVICE_TYPES},
+        devices::{
+            ConnectivityEvent, CreateDevice, Device, DeviceFilters, DeviceLocation, UpdateDevice,
+        },
+    },
     repositories::device_repository::DeviceRepositoryTrait,
 };
 
@@ -16,9 +21,36 @@ pub trait DeviceServiceTrait {
     async fn create_device(&self, new_device: CreateDevice) -> Result<Device>;
     async fn get_device_by_id(&self, id: i64) -> Result<Device>;
     async fn update_device(&self, id: i64, updated_device: UpdateDevice) -> Result<Device>;
+    /// Moves the device to another room, possibly in another house. Callers
+    /// check that the user may manage devices in both rooms.
+    async fn move_device(&self, id: i64, room_id: i64, user_id: i64) -> Result<Device>;
+    async fn get_location_history(&self, id: i64) -> Result<Vec<DeviceLocation>>;
     async fn delete_device(&self, id: i64) -> Result<()>;
-    async fn get_devices_by_room_id(&self, room_id: i64) -> Result<Vec<Device>>;
-    async fn get_devices_by_house_id(&self, house_id: i64) -> Result<Vec<Device>>;
+    async fn get_devices_by_room_id(
+        &self,
+        room_id: i64,
+        filters: DeviceFilters,
+    ) -> Result<Vec<Device>>;
+    /// Devices in the house, only in the given rooms if any.
+    async fn get_devices_by_house_id(
+        &self,
+        house_id: i64,
+        room_ids: Option<Vec<i64>>,
+        filters: DeviceFilters,
+    ) -> Result<Vec<Device>>;
+    /// Devices in the rooms of the zone, only in the given rooms if any.
+    async fn get_devices_by_zone_id(
+        &self,
+        zone_id: i64,
+        room_ids: Option<Vec<i64>>,
+        filters: DeviceFilters,
+    ) -> Result<Vec<Device>>;
+    /// Records a sign of life from the device, bringing it online.
+    async fn record_heartbeat(&self, id: i64) -> Result<Device>;
+    /// Takes devices offline that have not been heard from within the offline
+    /// timeout of their type. Returns how many went offline.
+    async fn mark_offline_devices(&self) -> Result<u64>;
+    async fn get_connectivity_events(&self, id: i64) -> Result<Vec<ConnectivityEvent>>;
 }
 
 #[derive(Clone)]
@@ -50,17 +82,101 @@ impl DeviceServiceTrait for DeviceService {
             .await
     }
 
+    async fn move_device(&self, id: i64, room_id: i64, user_id: i64) -> Result<Device> {
+        self.device_repository
+            .move_device(id, room_id, user_id)
+            .await
+    }
+
+    async fn get_location_history(&self, id: i64) -> Result<Vec<DeviceLocation>> {
+        self.device_repository.get_location_history(id).await
+    }
+
     async fn delete_device(&self, id: i64) -> Result<()> {
         self.device_repository.delete_device(id).await
     }
 
-    async fn get_devices_by_room_id(&self, room_id: i64) -> Result<Vec<Device>> {
-        self.device_repository.get_devices_by_room_id(room_id).await
+    async fn get_devices_by_room_id(
+        &self,
+        room_id: i64,
+        filters: DeviceFilters,
+    ) -> Result<Vec<Device>> {
+        self.device_repository
+            .get_devices_by_room_id(room_id, filters.status)
+            .await
+    }
+
+    async fn get_devices_by_house_id(
+        &self,
+        house_id: i64,
+        room_ids: Option<Vec<i64>>,
+        filters: DeviceFilters,
+    ) -> Result<Vec<Device>> {
+        self.device_repository
+            .get_devices_by_house_id(house_id, room_ids, filters.status)
+            .await
+    }
+
+    async fn get_devices_by_zone_id(
+        &self,
+        zone_id: i64,
+        room_ids: Option<Vec<i64>>,
+        filters: DeviceFilters,
+    ) -> Result<Vec<Device>> {
+        self.device_repository
+            .get_devices_by_zone_id(zone_id, room_ids, filters.status)
+            .await
+    }
+
+    async fn record_heartbeat(&self, id: i64) -> Result<Device> {
+        self.device_repository.mark_seen(id).await
     }
 
-    async fn get_devices_by_house_id(&self, house_id: i64) -> Result<Vec<Device>> {
+    async fn mark_offline_devices(&self) -> Result<u64> {
+        let (device_types, timeouts) = DEVICE_TYPES
+            .iter()
+            .map(|device_type| {
+                (
+                    device_type.key.to_string(),
+                    device_type.offline_after_seconds,
+                )
+            })
+            .unzip();
+
         self.device_repository
-            .get_devices_by_house_id(house_id)
+            .mark_offline(device_types, timeouts, DEFAULT_OFFLINE_AFTER_SECONDS)
             .await
     }
+
+    async fn get_connectivity_events(&self, id: i64) -> Result<Vec<ConnectivityEvent>> {
+        self.device_repository.get_connectivity_events(id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::device_repository::MockDeviceRepositoryTrait;
+
+    #[tokio::test]
+    async fn test_offline_timeouts_come_from_the_device_types() {
+        let mut device_repository = MockDeviceRepositoryTrait::new();
+        device_repository
+            .expect_mark_offline()
+            .withf(|device_types, timeouts, default_timeout| {
+                let light = device_types.iter().position(|key| key == "light");
+                let motion_sensor = device_types.iter().position(|key| key == "motion_sensor");
+
+                device_types.len() == DEVICE_TYPES.len()
+                    && light.is_some_and(|i| timeouts[i] == 300)
+                    && motion_sensor.is_some_and(|i| timeouts[i] == 3600)
+                    && *default_timeout == DEFAULT_OFFLINE_AFTER_SECONDS
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(2));
+
+        let service = DeviceService::new(Arc::new(device_repository));
+
+        assert_eq!(service.mark_offline_devices().await.unwrap(), 2);
+    }
 }
@@ -0,0 +1,393 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use validator::Validate;
+
+use crate::{
+    errors::{AppError, Result},
+    models::{
+        device_commands::{
+            CommandKind, CommandStatus, DeviceCommand, DeviceCommandFilters, FailDeviceCommand,
+            NewDeviceCommand,
+        },
+        device_types::DeviceType,
+    },
+    repositories::{
+        device_commands_repository::DeviceCommandsRepositoryTrait,
+        device_repository::DeviceRepositoryTrait,
+    },
+    services::access_control_service::{AccessControlServiceTrait, Action},
+};
+
+/// How many commands a gateway gets per fetch at most
+pub const MAX_FETCHED_COMMANDS: i64 = 50;
+
+#[async_trait]
+pub trait DeviceCommandsServiceTrait {
+    async fn create_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command: NewDeviceCommand,
+    ) -> Result<DeviceCommand>;
+    async fn get_commands(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        filters: DeviceCommandFilters,
+    ) -> Result<Vec<DeviceCommand>>;
+    /// Hands out the commands due for delivery to the device.
+    async fn fetch_device_commands(
+        &self,
+        user_id: i64,
+        device_id: i64,
+    ) -> Result<Vec<DeviceCommand>>;
+    /// Hands out the commands due for delivery to the devices of the house the
+    /// user may access, for gateways serving a whole house.
+    async fn fetch_house_commands(&self, user_id: i64, house_id: i64)
+        -> Result<Vec<DeviceCommand>>;
+    async fn acknowledge_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command_id: i64,
+    ) -> Result<DeviceCommand>;
+    async fn fail_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command_id: i64,
+        failure: FailDeviceCommand,
+    ) -> Result<DeviceCommand>;
+    /// Cancels a command that has not been completed yet.
+    async fn expire_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command_id: i64,
+    ) -> Result<DeviceCommand>;
+    /// Expires overdue commands and fails those out of attempts.
+    async fn expire_commands(&self) -> Result<u64>;
+}
+
+#[derive(Clone)]
+pub struct DeviceCommandsService {
+    device_commands_repository: Arc<dyn DeviceCommandsRepositoryTrait + Send + Sync>,
+    device_repository: Arc<dyn DeviceRepositoryTrait + Send + Sync>,
+    access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+}
+
+impl DeviceCommandsService {
+    pub fn new(
+        device_commands_repository: Arc<dyn DeviceCommandsRepositoryTrait + Send + Sync>,
+        device_repository: Arc<dyn DeviceRepositoryTrait + Send + Sync>,
+        access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            device_commands_repository,
+            device_repository,
+            access_control_service,
+        }
+    }
+
+    /// The command, once it is known to be for the device.
+    async fn device_command(&self, device_id: i64, command_id: i64) -> Result<DeviceCommand> {
+        let command = self
+            .device_commands_repository
+            .get_command(command_id)
+            .await?;
+        if command.device_id != device_id {
+            return Err(AppError::NotFound("Command not found".to_string()));
+        }
+
+        Ok(command)
+    }
+
+    async fn finish_command(
+        &self,
+        device_id: i64,
+        command_id: i64,
+        status: CommandStatus,
+        error: Option<String>,
+    ) -> Result<DeviceCommand> {
+        let command = self.device_command(device_id, command_id).await?;
+
+        self.device_commands_repository
+            .finish_command(command_id, status, error)
+            .await?
+            .ok_or_else(|| AppError::Conflict(format!("Command is {}", command.status)))
+    }
+}
+
+/// Checks that the device type can run the command.
+fn check_command(device_type: Option<&DeviceType>, command: &NewDeviceCommand) -> Result<()> {
+    let (CommandKind::Set, Some(key), Some(value)) =
+        (command.command, &command.capability, command.value)
+    else {
+        return Ok(());
+    };
+
+    let capability = device_type
+        .and_then(|device_type| device_type.capability_by_key(key))
+        .ok_or_else(|| AppError::BadRequest(format!("The device has no {} capability", key)))?;
+
+    if !capability.writable {
+        return Err(AppError::BadRequest(format!("{} cannot be set", key)));
+    }
+    if !capability.allows(value) {
+        return Err(AppError::BadRequest(format!(
+            "{} must be from {} to {}",
+            key, capability.min, capability.max
+        )));
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl DeviceCommandsServiceTrait for DeviceCommandsService {
+    async fn create_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command: NewDeviceCommand,
+    ) -> Result<DeviceCommand> {
+        command.validate()?;
+        self.access_control_service
+            .can_access_device(user_id, device_id, Action::ManageDevices)
+            .await?;
+
+        let device = self.device_repository.get_device_by_id(device_id).await?;
+        check_command(DeviceType::find(&device.device_type), &command)?;
+
+        self.device_commands_repository
+            .create_command(device_id, command, user_id)
+            .await
+    }
+
+    async fn get_commands(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        filters: DeviceCommandFilters,
+    ) -> Result<Vec<DeviceCommand>> {
+        self.access_control_service
+            .can_access_device(user_id, device_id, Action::ViewHouse)
+            .await?;
+        self.device_commands_repository
+            .get_device_commands(device_id, filters.status)
+            .await
+    }
+
+    async fn fetch_device_commands(
+        &self,
+        user_id: i64,
+        device_id: i64,
+    ) -> Result<Vec<DeviceCommand>> {
+        self.access_control_service
+            .can_access_device(user_id, device_id, Action::WriteMetrics)
+            .await?;
+        self.device_commands_repository
+            .fetch_commands(Some(device_id), None, None, MAX_FETCHED_COMMANDS)
+            .await
+    }
+
+    async fn fetch_house_commands(
+        &self,
+        user_id: i64,
+        house_id: i64,
+    ) -> Result<Vec<DeviceCommand>> {
+        self.access_control_service
+            .can(user_id, house_id, Action::WriteMetrics)
+            .await?;
+        let room_ids = self
+            .access_control_service
+            .visible_rooms(user_id, house_id)
+            .await?;
+        self.device_commands_repository
+            .fetch_commands(None, Some(house_id), room_ids, MAX_FETCHED_COMMANDS)
+            .await
+    }
+
+    async fn acknowledge_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command_id: i64,
+    ) -> Result<DeviceCommand> {
+        self.access_control_service
+            .can_access_device(user_id, device_id, Action::WriteMetrics)
+            .await?;
+        self.finish_command(device_id, command_id, CommandStatus::Acknowledged, None)
+            .await
+    }
+
+    async fn fail_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command_id: i64,
+        failure: FailDeviceCommand,
+    ) -> Result<DeviceCommand> {
+        failure.validate()?;
+        self.access_control_service
+            .can_access_device(user_id, device_id, Action::WriteMetrics)
+            .await?;
+        self.finish_command(
+            device_id,
+            command_id,
+            CommandStatus::Failed,
+            Some(failure.error),
+        )
+        .await
+    }
+
+    async fn expire_command(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        command_id: i64,
+    ) -> Result<DeviceCommand> {
+        self.access_control_service
+            .can_access_device(user_id, device_id, Action::ManageDevices)
+            .await?;
+        self.finish_command(device_id, command_id, CommandStatus::Expired, None)
+            .await
+    }
+
+    async fn expire_commands(&self) -> Result<u64> {
+        self.device_commands_repository.expire_commands().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            device_commands::{
+                DEFAULT_COMMAND_MAX_ATTEMPTS, DEFAULT_COMMAND_TIMEOUT_SECONDS,
+                DEFAULT_COMMAND_TTL_SECONDS,
+            },
+            devices::{Device, DeviceStatus},
+        },
+        repositories::{
+            device_commands_repository::MockDeviceCommandsRepositoryTrait,
+            device_repository::MockDeviceRepositoryTrait,
+        },
+        services::access_control_service::MockAccessControlServiceTrait,
+    };
+    use chrono::Utc;
+    use mockall::predicate::eq;
+
+    fn service(
+        device_commands_repository: MockDeviceCommandsRepositoryTrait,
+    ) -> DeviceCommandsService {
+        let mut device_repository = MockDeviceRepositoryTrait::new();
+        device_repository.expect_get_device_by_id().returning(|id| {
+            Ok(Device {
+                id,
+                name: "Thermostat".to_string(),
+                device_type: "thermostat".to_string(),
+                room_id: 1,
+                status: DeviceStatus::Offline,
+                last_seen_at: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            })
+        });
+        let mut access_control = MockAccessControlServiceTrait::new();
+        access_control
+            .expect_can_access_device()
+            .returning(|_, _, _| Ok(()));
+
+        DeviceCommandsService::new(
+            Arc::new(device_commands_repository),
+            Arc::new(device_repository),
+            Arc::new(access_control),
+        )
+    }
+
+    fn set(capability: &str, value: f64) -> NewDeviceCommand {
+        NewDeviceCommand {
+            command: CommandKind::Set,
+            capability: Some(capability.to_string()),
+            value: Some(value),
+            timeout_seconds: DEFAULT_COMMAND_TIMEOUT_SECONDS,
+            max_attempts: DEFAULT_COMMAND_MAX_ATTEMPTS,
+            ttl_seconds: DEFAULT_COMMAND_TTL_SECONDS,
+        }
+    }
+
+    fn command(id: i64, device_id: i64, status: CommandStatus) -> DeviceCommand {
+        DeviceCommand {
+            id,
+            device_id,
+            command: CommandKind::Reboot,
+            capability: None,
+            value: None,
+            status,
+            attempts: 1,
+            max_attempts: DEFAULT_COMMAND_MAX_ATTEMPTS,
+            timeout_seconds: DEFAULT_COMMAND_TIMEOUT_SECONDS,
+            expires_at: Utc::now(),
+            delivered_at: Some(Utc::now()),
+            completed_at: None,
+            error: None,
+            created_by: Some(1),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commands_are_checked_against_the_device_type() {
+        let mut device_commands_repository = MockDeviceCommandsRepositoryTrait::new();
+        device_commands_repository.expect_create_command().never();
+        let service = service(device_commands_repository);
+
+        for new_command in [
+            set("setpoint", 45.0),
+            set("temperature_sensor", 20.0),
+            set("on_off", 1.0),
+        ] {
+            assert!(matches!(
+                service.create_command(1, 5, new_command).await,
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acknowledging_a_finished_command() {
+        let mut device_commands_repository = MockDeviceCommandsRepositoryTrait::new();
+        device_commands_repository
+            .expect_get_command()
+            .with(eq(7i64))
+            .returning(|id| Ok(command(id, 5, CommandStatus::Expired)));
+        device_commands_repository
+            .expect_finish_command()
+            .returning(|_, _, _| Ok(None));
+        let service = service(device_commands_repository);
+
+        assert!(matches!(
+            service.acknowledge_command(1, 5, 7).await,
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_command_of_another_device() {
+        let mut device_commands_repository = MockDeviceCommandsRepositoryTrait::new();
+        device_commands_repository
+            .expect_get_command()
+            .returning(|id| Ok(command(id, 6, CommandStatus::Delivered)));
+        device_commands_repository.expect_finish_command().never();
+        let service = service(device_commands_repository);
+
+        assert!(matches!(
+            service.acknowledge_command(1, 5, 7).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -4,12 +4,15 @@ use async_trait::async_trait;
 use validator::Validate;
 
 use crate::{
-    errors::Result,
+    errors::{AppError, Result},
     models::device_metrics::{
         AggregatedDeviceMetric, CreateDeviceMetric, DeviceMetric, DeviceMetricFilters,
     },
-    repositories::device_metrics_repository::DeviceMetricsRepositoryTrait,
-    services::access_control_service::AccessControlServiceTrait,
+    repositories::{
+        device_metrics_repository::DeviceMetricsRepositoryTrait,
+        device_repository::DeviceRepositoryTrait, zones_repository::ZonesRepositoryTrait,
+    },
+    services::access_control_service::{AccessControlServiceTrait, Action},
 };
 
 #[async_trait]
@@ -31,6 +34,7 @@ pub trait DeviceMetricsServiceTrait {
         room_id: i64,
         filters: DeviceMetricFilters,
     ) -> Result<Vec<DeviceMetric>>;
+    /// Metrics of the devices in the rooms of the house the user may see.
     async fn get_metrics_for_house(
         &self,
         user_id: i64,
@@ -49,24 +53,66 @@ pub trait DeviceMetricsServiceTrait {
         house_id: i64,
         filters: DeviceMetricFilters,
     ) -> Result<Vec<AggregatedDeviceMetric>>;
+    /// Metrics of the devices in the rooms of the zone the user may see.
+    async fn get_metrics_for_zone(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+        filters: DeviceMetricFilters,
+    ) -> Result<Vec<DeviceMetric>>;
+    async fn get_aggregated_metrics_for_zone(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+        filters: DeviceMetricFilters,
+    ) -> Result<Vec<AggregatedDeviceMetric>>;
 }
 
 #[derive(Clone)]
 pub struct DeviceMetricsService {
     device_metrics_repository: Arc<dyn DeviceMetricsRepositoryTrait + Send + Sync>,
+    device_repository: Arc<dyn DeviceRepositoryTrait + Send + Sync>,
+    zones_repository: Arc<dyn ZonesRepositoryTrait + Send + Sync>,
     access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
 }
 
 impl DeviceMetricsService {
     pub fn new(
         device_metrics_repository: Arc<dyn DeviceMetricsRepositoryTrait + Send + Sync>,
+        device_repository: Arc<dyn DeviceRepositoryTrait + Send + Sync>,
+        zones_repository: Arc<dyn ZonesRepositoryTrait + Send + Sync>,
         access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
     ) -> Self {
         Self {
             device_metrics_repository,
+            device_repository,
+            zones_repository,
             access_control_service,
         }
     }
+
+    /// Rooms of the zone's house the user may see, once the zone is known to be
+    /// in that house.
+    async fn visible_zone_rooms(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+    ) -> Result<Option<Vec<i64>>> {
+        let room_ids = self
+            .access_control_service
+            .visible_rooms(user_id, house_id)
+            .await?;
+
+        let zone = self.zones_repository.get_zone(zone_id).await?;
+        if zone.house_id != house_id {
+            return Err(AppError::NotFound("Zone not found".to_string()));
+        }
+
+        Ok(room_ids)
+    }
 }
 
 #[async_trait]
@@ -78,11 +124,17 @@ impl DeviceMetricsServiceTrait for DeviceMetricsService {
     ) -> Result<DeviceMetric> {
         new_metric.validate()?;
         self.access_control_service
-            .can_access_device(user_id, new_metric.device_id)
+            .can_access_device(user_id, new_metric.device_id, Action::WriteMetrics)
             .await?;
-        self.device_metrics_repository
+
+        let metric = self
+            .device_metrics_repository
             .create_metric(new_metric)
-            .await
+            .await?;
+        // A metric is a sign of life from the device
+        self.device_repository.mark_seen(metric.device_id).await?;
+
+        Ok(metric)
     }
 
     async fn get_metrics(
@@ -92,7 +144,7 @@ impl DeviceMetricsServiceTrait for DeviceMetricsService {
         filters: DeviceMetricFilters,
     ) -> Result<Vec<DeviceMetric>> {
         self.access_control_service
-            .can_access_device(user_id, device_id)
+            .can_access_device(user_id, device_id, Action::ViewHouse)
             .await?;
         self.device_metrics_repository
             .get_metrics(device_id, filters)
@@ -106,7 +158,7 @@ impl DeviceMetricsServiceTrait for DeviceMetricsService {
         filters: DeviceMetricFilters,
     ) -> Result<Vec<DeviceMetric>> {
         self.access_control_service
-            .can_access_room(user_id, room_id)
+            .can_access_room(user_id, room_id, Action::ViewHouse)
             .await?;
         self.device_metrics_repository
             .get_metrics_for_room(room_id, filters)
@@ -119,11 +171,12 @@ impl DeviceMetricsServiceTrait for DeviceMetricsService {
         house_id: i64,
         filters: DeviceMetricFilters,
     ) -> Result<Vec<DeviceMetric>> {
-        self.access_control_service
-            .can_access_house(user_id, house_id)
+        let room_ids = self
+            .access_control_service
+            .visible_rooms(user_id, house_id)
             .await?;
         self.device_metrics_repository
-            .get_metrics_for_house(house_id, filters)
+            .get_metrics_for_house(house_id, room_ids, filters)
             .await
     }
 
@@ -134,7 +187,7 @@ impl DeviceMetricsServiceTrait for DeviceMetricsService {
         filters: DeviceMetricFilters,
     ) -> Result<Vec<AggregatedDeviceMetric>> {
         self.access_control_service
-            .can_access_room(user_id, room_id)
+            .can_access_room(user_id, room_id, Action::ViewHouse)
             .await?;
         self.device_metrics_repository
             .get_aggregated_metrics_for_room(room_id, filters)
@@ -147,11 +200,38 @@ impl DeviceMetricsServiceTrait for DeviceMetricsService {
         house_id: i64,
         filters: DeviceMetricFilters,
     ) -> Result<Vec<AggregatedDeviceMetric>> {
-        self.access_control_service
-            .can_access_house(user_id, house_id)
+        let room_ids = self
+            .access_control_service
+            .visible_rooms(user_id, house_id)
             .await?;
         self.device_metrics_repository
-            .get_aggregated_metrics_for_house(house_id, filters)
+            .get_aggregated_metrics_for_house(house_id, room_ids, filters)
+            .await
+    }
+
+    async fn get_metrics_for_zone(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+        filters: DeviceMetricFilters,
+    ) -> Result<Vec<DeviceMetric>> {
+        let room_ids = self.visible_zone_rooms(user_id, house_id, zone_id).await?;
+        self.device_metrics_repository
+            .get_metrics_for_zone(zone_id, room_ids, filters)
+            .await
+    }
+
+    async fn get_aggregated_metrics_for_zone(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+        filters: DeviceMetricFilters,
+    ) -> Result<Vec<AggregatedDeviceMetric>> {
+        let room_ids = self.visible_zone_rooms(user_id, house_id, zone_id).await?;
+        self.device_metrics_repository
+            .get_aggregated_metrics_for_zone(zone_id, room_ids, filters)
             .await
     }
 }
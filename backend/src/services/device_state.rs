@@ -0,0 +1,243 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    errors::{AppError, Result},
+    models::{
+        device_state::{DeviceState, DeviceStateDelta, StateValues, UpdateDeviceState},
+        device_types::DeviceType,
+    },
+    repositories::{
+        device_repository::DeviceRepositoryTrait,
+        device_state_repository::DeviceStateRepositoryTrait,
+    },
+    services::access_control_service::{AccessControlServiceTrait, Action},
+};
+
+#[async_trait]
+pub trait DeviceStateServiceTrait {
+    async fn get_state(&self, user_id: i64, device_id: i64) -> Result<DeviceState>;
+    /// Setting desired values takes the right to manage the device, reporting
+    /// values the right to write its metrics.
+    async fn update_state(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        update: UpdateDeviceState,
+    ) -> Result<DeviceState>;
+    async fn get_delta(&self, user_id: i64, device_id: i64) -> Result<DeviceStateDelta>;
+}
+
+#[derive(Clone)]
+pub struct DeviceStateService {
+    device_state_repository: Arc<dyn DeviceStateRepositoryTrait + Send + Sync>,
+    device_repository: Arc<dyn DeviceRepositoryTrait + Send + Sync>,
+    access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+}
+
+impl DeviceStateService {
+    pub fn new(
+        device_state_repository: Arc<dyn DeviceStateRepositoryTrait + Send + Sync>,
+        device_repository: Arc<dyn DeviceRepositoryTrait + Send + Sync>,
+        access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            device_state_repository,
+            device_repository,
+            access_control_service,
+        }
+    }
+}
+
+/// Checks that the values are for capabilities of the device type and within
+/// their range. Only capabilities that can be set take desired values.
+fn check_values(
+    device_type: Option<&DeviceType>,
+    values: &StateValues,
+    desired: bool,
+) -> Result<()> {
+    for (key, value) in values {
+        let capability = device_type
+            .and_then(|device_type| device_type.capability_by_key(key))
+            .ok_or_else(|| AppError::BadRequest(format!("The device has no {} capability", key)))?;
+
+        if desired && !capability.writable {
+            return Err(AppError::BadRequest(format!("{} cannot be set", key)));
+        }
+
+        if value.is_null() {
+            continue;
+        }
+        match value.as_f64() {
+            Some(number) if capability.allows(number) => {}
+            _ => {
+                return Err(AppError::BadRequest(format!(
+                    "{} must be a number from {} to {}",
+                    key, capability.min, capability.max
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl DeviceStateServiceTrait for DeviceStateService {
+    async fn get_state(&self, user_id: i64, device_id: i64) -> Result<DeviceState> {
+        self.access_control_service
+            .can_access_device(user_id, device_id, Action::ViewHouse)
+            .await?;
+
+        let state = self.device_state_repository.get_state(device_id).await?;
+        Ok(state.unwrap_or_else(|| DeviceState::empty(device_id)))
+    }
+
+    async fn update_state(
+        &self,
+        user_id: i64,
+        device_id: i64,
+        update: UpdateDeviceState,
+    ) -> Result<DeviceState> {
+        if update.desired.is_none() && update.reported.is_none() {
+            return Err(AppError::BadRequest(
+                "Either desired or reported values are required".to_string(),
+            ));
+        }
+
+        if update.desired.is_some() {
+            self.access_control_service
+                .can_access_device(user_id, device_id, Action::ManageDevices)
+                .await?;
+        }
+        if update.reported.is_some() {
+            self.access_control_service
+                .can_access_device(user_id, device_id, Action::WriteMetrics)
+                .await?;
+        }
+
+        let device = self.device_repository.get_device_by_id(device_id).await?;
+        let device_type = DeviceType::find(&device.device_type);
+        if let Some(desired) = &update.desired {
+            check_values(device_type, desired, true)?;
+        }
+        if let Some(reported) = &update.reported {
+            check_values(device_type, reported, false)?;
+        }
+
+        self.device_state_repository
+            .update_state(device_id, update.desired, update.reported, update.version)
+            .await
+    }
+
+    async fn get_delta(&self, user_id: i64, device_id: i64) -> Result<DeviceStateDelta> {
+        Ok(self.get_state(user_id, device_id).await?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::devices::{Device, DeviceStatus},
+        repositories::{
+            device_repository::MockDeviceRepositoryTrait,
+            device_state_repository::MockDeviceStateRepositoryTrait,
+        },
+        services::access_control_service::MockAccessControlServiceTrait,
+    };
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn service(
+        device_state_repository: MockDeviceStateRepositoryTrait,
+        device_type: &'static str,
+    ) -> DeviceStateService {
+        let mut device_repository = MockDeviceRepositoryTrait::new();
+        device_repository
+            .expect_get_device_by_id()
+            .returning(move |id| {
+                Ok(Device {
+                    id,
+                    name: "Thermostat".to_string(),
+                    device_type: device_type.to_string(),
+                    room_id: 1,
+                    status: DeviceStatus::Offline,
+                    last_seen_at: None,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                })
+            });
+        let mut access_control = MockAccessControlServiceTrait::new();
+        access_control
+            .expect_can_access_device()
+            .returning(|_, _, _| Ok(()));
+
+        DeviceStateService::new(
+            Arc::new(device_state_repository),
+            Arc::new(device_repository),
+            Arc::new(access_control),
+        )
+    }
+
+    fn values(value: serde_json::Value) -> Option<StateValues> {
+        value.as_object().cloned()
+    }
+
+    #[tokio::test]
+    async fn test_desired_values_are_checked_against_capabilities() {
+        let mut device_state_repository = MockDeviceStateRepositoryTrait::new();
+        device_state_repository.expect_update_state().never();
+        let service = service(device_state_repository, "thermostat");
+
+        for desired in [
+            json!({ "setpoint": 45 }),
+            json!({ "setpoint": "warm" }),
+            json!({ "temperature_sensor": 21 }),
+            json!({ "brightness": 40 }),
+        ] {
+            let update = UpdateDeviceState {
+                desired: values(desired),
+                reported: None,
+                version: None,
+            };
+            assert!(matches!(
+                service.update_state(1, 5, update).await,
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reported_values_may_be_read_only() {
+        let mut device_state_repository = MockDeviceStateRepositoryTrait::new();
+        device_state_repository
+            .expect_update_state()
+            .times(1)
+            .returning(|device_id, _, reported, _| {
+                let mut state = DeviceState::empty(device_id);
+                state.reported = reported.unwrap();
+                state.version = 1;
+                Ok(state)
+            });
+        let service = service(device_state_repository, "thermostat");
+
+        let update = UpdateDeviceState {
+            desired: None,
+            reported: values(json!({ "temperature_sensor": 20.5, "setpoint": null })),
+            version: Some(0),
+        };
+        let state = service.update_state(1, 5, update).await.unwrap();
+        assert_eq!(state.version, 1);
+    }
+
+    #[test]
+    fn test_delta_skips_reported_values() {
+        let mut state = DeviceState::empty(5);
+        state.desired = values(json!({ "on_off": 1, "brightness": 40 })).unwrap();
+        state.reported = values(json!({ "on_off": 1.0, "brightness": 10 })).unwrap();
+
+        assert_eq!(state.delta(), values(json!({ "brightness": 40 })).unwrap());
+    }
+}
@@ -6,7 +6,10 @@ use validator::ValidationErrors;
 
 use crate::{
     errors::{AppError, Result},
-    models::houses::{House, NewHouse},
+    models::{
+        houses::{House, NewHouse, UpdateHouse},
+        user_houses::HouseRole,
+    },
     repositories::{user_houses_repository::UserHousesRepositoryTrait, HouseRepositoryTrait},
 };
 
@@ -16,6 +19,8 @@ pub trait HouseServiceTrait {
     async fn get_user_houses(&self, user_id: i64) -> Result<Vec<House>>;
     async fn get_house_by_id(&self, id: i64) -> Result<House>;
     async fn create_house(&self, user_id: i64, new_house: NewHouse) -> Result<House>;
+    /// Edits the house. The address must stay unique.
+    async fn update_house(&self, id: i64, house: UpdateHouse) -> Result<House>;
     async fn delete_house(&self, id: i64) -> Result<()>;
 }
 
@@ -35,6 +40,24 @@ impl HouseService {
             user_house_repository,
         }
     }
+
+    async fn check_address_is_free(&self, address: &str, house_id: Option<i64>) -> Result<()> {
+        let house = self
+            .house_repository
+            .find_house_by_address(address.to_string())
+            .await?;
+        if house.is_some_and(|house| Some(house.id) != house_id) {
+            let mut errors = ValidationErrors::new();
+            errors.add(
+                "address",
+                validator::ValidationError::new("already_exists")
+                    .with_message(Cow::from("House with this address already exists")),
+            );
+            return Err(AppError::ValidationError(errors));
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -52,28 +75,24 @@ impl HouseServiceTrait for HouseService {
     }
 
     async fn create_house(&self, user_id: i64, new_house: NewHouse) -> Result<House> {
-        let house = self
-            .house_repository
-            .find_house_by_address(new_house.address.clone())
-            .await?;
-        if house.is_some() {
-            let mut errors = ValidationErrors::new();
-            errors.add(
-                "address",
-                validator::ValidationError::new("already_exists")
-                    .with_message(Cow::from("House with this address already exists")),
-            );
-            return Err(AppError::ValidationError(errors));
-        }
+        self.check_address_is_free(&new_house.address, None).await?;
         let house = self.house_repository.create_house(new_house).await?;
         let _ = self
             .user_house_repository
-            .add_house_to_user(user_id, house.id)
+            .add_house_to_user(user_id, house.id, HouseRole::Owner)
             .await?;
 
         Ok(house)
     }
 
+    async fn update_house(&self, id: i64, house: UpdateHouse) -> Result<House> {
+        if let Some(address) = &house.address {
+            self.check_address_is_free(address, Some(id)).await?;
+        }
+
+        self.house_repository.update_house(id, house).await
+    }
+
     async fn delete_house(&self, id: i64) -> Result<()> {
         self.house_repository.delete_house(id).await?;
 
@@ -0,0 +1,976 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use mockall::automock;
+use rand::{distr::Alphanumeric, Rng};
+use sha2::Sha256;
+
+use crate::{
+    config::Config,
+    errors::{AppError, Result},
+    models::{
+        house_invitations::{
+            CreateHouseInvitation, CreatedHouseInvitation, HouseInvitation, NewHouseInvitation,
+            ReceivedHouseInvitation, DEFAULT_INVITATION_EXPIRES_IN_HOURS,
+        },
+        mail::EmailMessage,
+        user_houses::{ExpiredMember, HouseMember, HouseRole, MemberAccess},
+        users::User,
+    },
+    repositories::{
+        HouseInvitationsRepositoryTrait, HouseRepositoryTrait, UserHousesRepositoryTrait,
+        UserRepositoryTrait,
+    },
+    services::{
+        access_control_service::{AccessControlServiceTrait, Action},
+        mailer::Mailer,
+    },
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const INVITATION_CODE_LENGTH: usize = 32;
+
+/// Who has access to a house: members, invitations and leaving.
+#[automock]
+#[async_trait]
+pub trait HouseMembersServiceTrait {
+    async fn list_members(&self, user_id: i64, house_id: i64) -> Result<Vec<HouseMember>>;
+    /// Removes a member from the house. Only owners can remove owners.
+    async fn remove_member(&self, user_id: i64, house_id: i64, member_id: i64) -> Result<()>;
+    /// Removes the user from the house. The last owner can't leave.
+    async fn leave_house(&self, user_id: i64, house_id: i64) -> Result<()>;
+    /// Replaces the access limits of a member. Owners can't be limited, and
+    /// admins can't be limited to rooms.
+    async fn update_member_access(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        member_id: i64,
+        access: MemberAccess,
+    ) -> Result<()>;
+    /// Removes the memberships whose access has ended and notifies the owners
+    /// of their houses. Returns the number of removed memberships.
+    async fn remove_expired_members(&self) -> Result<usize>;
+    /// Creates an invitation and emails it if it's addressed to an email
+    /// address. Only owners can invite owners.
+    async fn create_invitation(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        data: CreateHouseInvitation,
+    ) -> Result<CreatedHouseInvitation>;
+    async fn list_house_invitations(
+        &self,
+        user_id: i64,
+        house_id: i64,
+    ) -> Result<Vec<HouseInvitation>>;
+    async fn revoke_invitation(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        invitation_id: i64,
+    ) -> Result<()>;
+    /// Pending invitations addressed to the verified email address of the user.
+    async fn list_received_invitations(&self, user_id: i64)
+        -> Result<Vec<ReceivedHouseInvitation>>;
+    /// Accepts an invitation with its code.
+    async fn accept_invitation_code(&self, user_id: i64, code: &str) -> Result<HouseInvitation>;
+    /// Accepts an invitation addressed to the user.
+    async fn accept_invitation(&self, user_id: i64, invitation_id: i64) -> Result<HouseInvitation>;
+    /// Declines an invitation addressed to the user.
+    async fn decline_invitation(&self, user_id: i64, invitation_id: i64) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct HouseMembersService {
+    config: Config,
+    user_houses_repository: Arc<dyn UserHousesRepositoryTrait + Send + Sync>,
+    house_invitations_repository: Arc<dyn HouseInvitationsRepositoryTrait + Send + Sync>,
+    house_repository: Arc<dyn HouseRepositoryTrait + Send + Sync>,
+    user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+    access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+    mailer: Arc<dyn Mailer + Send + Sync>,
+}
+
+impl HouseMembersService {
+    pub fn new(
+        config: Config,
+        user_houses_repository: Arc<dyn UserHousesRepositoryTrait + Send + Sync>,
+        house_invitations_repository: Arc<dyn HouseInvitationsRepositoryTrait + Send + Sync>,
+        house_repository: Arc<dyn HouseRepositoryTrait + Send + Sync>,
+        user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+        access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+        mailer: Arc<dyn Mailer + Send + Sync>,
+    ) -> Self {
+        Self {
+            config,
+            user_houses_repository,
+            house_invitations_repository,
+            house_repository,
+            user_repository,
+            access_control_service,
+            mailer,
+        }
+    }
+
+    /// Codes are stored as an HMAC keyed with the JWT secret, like account
+    /// tokens, so a leaked database can't be used to join houses.
+    fn sign_code(&self, code: &str) -> Result<String> {
+        let mut mac = HmacSha256::new_from_slice(self.config.jwt_secret.as_bytes())
+            .map_err(|_| AppError::InternalServerError("Invalid signing key".to_string()))?;
+        mac.update(code.as_bytes());
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+
+    /// Users see invitations sent to their email address only once they've
+    /// proven they own it.
+    async fn verified_user(&self, user_id: i64) -> Result<User> {
+        let user = self.user_repository.get_user_by_id(user_id).await?;
+        if user.email_verified_at.is_none() {
+            return Err(AppError::AuthorizationError(
+                "Verify your email address to see invitations sent to it".to_string(),
+            ));
+        }
+
+        Ok(user)
+    }
+
+    /// A pending invitation addressed to the user.
+    async fn received_invitation(
+        &self,
+        user_id: i64,
+        invitation_id: i64,
+    ) -> Result<HouseInvitation> {
+        let user = self.verified_user(user_id).await?;
+
+        self.house_invitations_repository
+            .find_pending_by_id(invitation_id)
+            .await?
+            .filter(|invitation| invitation.email.as_deref() == Some(&user.email.to_lowercase()))
+            .ok_or_else(|| AppError::NotFound("Invitation not found".to_string()))
+    }
+
+    async fn check_access_limits(
+        &self,
+        house_id: i64,
+        role: HouseRole,
+        access: &MemberAccess,
+    ) -> Result<()> {
+        if !access.is_limited() {
+            return Ok(());
+        }
+        if role == HouseRole::Owner {
+            return Err(AppError::BadRequest(
+                "Owners can't have limited access".to_string(),
+            ));
+        }
+        if let (Some(valid_from), Some(valid_until)) = (access.valid_from, access.valid_until) {
+            if valid_from >= valid_until {
+                return Err(AppError::BadRequest(
+                    "valid_from must be before valid_until".to_string(),
+                ));
+            }
+        }
+        if access
+            .valid_until
+            .is_some_and(|valid_until| valid_until <= Utc::now())
+        {
+            return Err(AppError::BadRequest(
+                "valid_until must be in the future".to_string(),
+            ));
+        }
+        if access
+            .schedule
+            .iter()
+            .flatten()
+            .any(|window| window.start == window.end)
+        {
+            return Err(AppError::BadRequest(
+                "Access windows can't start and end at the same time".to_string(),
+            ));
+        }
+        if let Some(room_ids) = &access.room_ids {
+            // Admins manage rooms, so they need the whole house
+            if role == HouseRole::Admin {
+                return Err(AppError::BadRequest(
+                    "Admins can't be limited to rooms".to_string(),
+                ));
+            }
+
+            for room_id in room_ids {
+                let room_house = self
+                    .user_houses_repository
+                    .get_house_by_room_id(*room_id)
+                    .await;
+                if !room_house.is_ok_and(|house| house.id == house_id) {
+                    return Err(AppError::BadRequest(
+                        "Rooms must belong to the house".to_string(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn notify_owners(&self, expired: &ExpiredMember) -> Result<()> {
+        let house = self
+            .house_repository
+            .get_house_by_id(expired.house_id)
+            .await?;
+        let owners = self
+            .user_houses_repository
+            .list_members(expired.house_id)
+            .await?
+            .into_iter()
+            .filter(|member| member.role == HouseRole::Owner);
+
+        for owner in owners {
+            self.mailer
+                .send(EmailMessage {
+                    to: owner.email,
+                    subject: format!("Access to {} ended", house.name),
+                    body: format!(
+                        "Hi {},\n\nThe access of {} {} to {} ended at {} and they were removed from the house. Invite them again if they still need access.\n",
+                        owner.first_name,
+                        expired.first_name,
+                        expired.last_name,
+                        house.name,
+                        expired.valid_until.format("%Y-%m-%d %H:%M UTC")
+                    ),
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn accept(&self, user_id: i64, invitation: HouseInvitation) -> Result<HouseInvitation> {
+        if let Some(email) = &invitation.email {
+            let user = self.user_repository.get_user_by_id(user_id).await?;
+            if user.email.to_lowercase() != *email {
+                return Err(AppError::AuthorizationError(
+                    "This invitation is for a different email address".to_string(),
+                ));
+            }
+        }
+
+        if self
+            .user_houses_repository
+            .get_user_role(invitation.house_id, user_id)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::BadRequest(
+                "You are already a member of this house".to_string(),
+            ));
+        }
+
+        self.house_invitations_repository
+            .accept(invitation.id, user_id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired invitation".to_string()))
+    }
+}
+
+#[async_trait]
+impl HouseMembersServiceTrait for HouseMembersService {
+    async fn list_members(&self, user_id: i64, house_id: i64) -> Result<Vec<HouseMember>> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ViewHouse)
+            .await?;
+
+        self.user_houses_repository.list_members(house_id).await
+    }
+
+    async fn remove_member(&self, user_id: i64, house_id: i64, member_id: i64) -> Result<()> {
+        if member_id == user_id {
+            return self.leave_house(user_id, house_id).await;
+        }
+
+        self.access_control_service
+            .can(user_id, house_id, Action::ManageMembers)
+            .await?;
+
+        let member_role = self
+            .user_houses_repository
+            .get_user_role(house_id, member_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Member not found".to_string()))?;
+        if member_role == HouseRole::Owner {
+            self.access_control_service
+                .can(user_id, house_id, Action::ManageOwners)
+                .await?;
+        }
+
+        self.user_houses_repository
+            .remove_member(house_id, member_id)
+            .await
+    }
+
+    async fn leave_house(&self, user_id: i64, house_id: i64) -> Result<()> {
+        // Members can leave outside of their access windows too
+        self.user_houses_repository
+            .get_user_role(house_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::AuthenticationError("Access denied".to_string()))?;
+
+        self.user_houses_repository
+            .remove_member(house_id, user_id)
+            .await
+    }
+
+    async fn update_member_access(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        member_id: i64,
+        access: MemberAccess,
+    ) -> Result<()> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ManageMembers)
+            .await?;
+
+        let member_role = self
+            .user_houses_repository
+            .get_user_role(house_id, member_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Member not found".to_string()))?;
+        self.check_access_limits(house_id, member_role, &access)
+            .await?;
+
+        self.user_houses_repository
+            .update_member_access(house_id, member_id, &access)
+            .await
+    }
+
+    async fn remove_expired_members(&self) -> Result<usize> {
+        let expired = self.user_houses_repository.remove_expired_members().await?;
+
+        for member in &expired {
+            // The membership is already gone, a failed notification shouldn't
+            // keep the others from being sent
+            if let Err(e) = self.notify_owners(member).await {
+                tracing::error!(
+                    "Failed to notify the owners of house {} about expired access: {}",
+                    member.house_id,
+                    e
+                );
+            }
+        }
+
+        Ok(expired.len())
+    }
+
+    async fn create_invitation(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        data: CreateHouseInvitation,
+    ) -> Result<CreatedHouseInvitation> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ManageMembers)
+            .await?;
+
+        let role = data.role.unwrap_or(HouseRole::Member);
+        if role == HouseRole::Owner {
+            self.access_control_service
+                .can(user_id, house_id, Action::ManageOwners)
+                .await?;
+        }
+        self.check_access_limits(house_id, role, &data.access)
+            .await?;
+
+        let email = data.email.map(|email| email.trim().to_lowercase());
+        if let Some(email) = &email {
+            let members = self.user_houses_repository.list_members(house_id).await?;
+            if members
+                .iter()
+                .any(|member| member.email.to_lowercase() == *email)
+            {
+                return Err(AppError::BadRequest(
+                    "This user is already a member of the house".to_string(),
+                ));
+            }
+        }
+
+        let code: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(INVITATION_CODE_LENGTH)
+            .map(char::from)
+            .collect();
+        let expires_in_hours = data
+            .expires_in_hours
+            .unwrap_or(DEFAULT_INVITATION_EXPIRES_IN_HOURS);
+
+        let invitation = self
+            .house_invitations_repository
+            .create(&NewHouseInvitation {
+                house_id,
+                invited_by: user_id,
+                email: email.clone(),
+                role,
+                code_hash: self.sign_code(&code)?,
+                expires_at: Utc::now() + Duration::hours(expires_in_hours),
+                access: data.access,
+            })
+            .await?;
+        let link = format!(
+            "{}/invitations/accept?code={}",
+            self.config.frontend_origin, code
+        );
+
+        if let Some(email) = email {
+            let house = self.house_repository.get_house_by_id(house_id).await?;
+            let inviter = self.user_repository.get_user_by_id(user_id).await?;
+
+            self.mailer
+                .send(EmailMessage {
+                    to: email,
+                    subject: format!("You're invited to {}", house.name),
+                    body: format!(
+                        "Hi,\n\n{} {} invited you to join {} as {}. Accept the invitation by opening the link below:\n\n{}\n\nThe invitation expires in {} hours. If you don't want to join, you can ignore this email.\n",
+                        inviter.first_name,
+                        inviter.last_name,
+                        house.name,
+                        role,
+                        link,
+                        expires_in_hours
+                    ),
+                })
+                .await?;
+        }
+
+        Ok(CreatedHouseInvitation {
+            invitation,
+            code,
+            link,
+        })
+    }
+
+    async fn list_house_invitations(
+        &self,
+        user_id: i64,
+        house_id: i64,
+    ) -> Result<Vec<HouseInvitation>> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ManageMembers)
+            .await?;
+
+        self.house_invitations_repository
+            .list_pending_for_house(house_id)
+            .await
+    }
+
+    async fn revoke_invitation(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        invitation_id: i64,
+    ) -> Result<()> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ManageMembers)
+            .await?;
+
+        self.house_invitations_repository
+            .delete(house_id, invitation_id)
+            .await
+    }
+
+    async fn list_received_invitations(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<ReceivedHouseInvitation>> {
+        let user = self.verified_user(user_id).await?;
+
+        self.house_invitations_repository
+            .list_pending_for_email(&user.email)
+            .await
+    }
+
+    async fn accept_invitation_code(&self, user_id: i64, code: &str) -> Result<HouseInvitation> {
+        let invitation = self
+            .house_invitations_repository
+            .find_pending_by_code_hash(&self.sign_code(code.trim())?)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired invitation".to_string()))?;
+
+        self.accept(user_id, invitation).await
+    }
+
+    async fn accept_invitation(&self, user_id: i64, invitation_id: i64) -> Result<HouseInvitation> {
+        let invitation = self.received_invitation(user_id, invitation_id).await?;
+
+        self.accept(user_id, invitation).await
+    }
+
+    async fn decline_invitation(&self, user_id: i64, invitation_id: i64) -> Result<()> {
+        let invitation = self.received_invitation(user_id, invitation_id).await?;
+
+        if !self
+            .house_invitations_repository
+            .decline(invitation.id)
+            .await?
+        {
+            return Err(AppError::BadRequest(
+                "Invalid or expired invitation".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::JwtAlgorithm,
+        models::{
+            houses::{House, UnitSystem},
+            users::UserRole,
+        },
+        repositories::{
+            house_invitations_repository::MockHouseInvitationsRepositoryTrait,
+            house_repository::MockHouseRepositoryTrait,
+            user_houses_repository::MockUserHousesRepositoryTrait,
+            user_repository::MockUserRepositoryTrait,
+        },
+        services::{access_control_service::MockAccessControlServiceTrait, mailer::MockMailer},
+    };
+    use mockall::predicate::eq;
+
+    fn create_test_config() -> Config {
+        Config {
+            port: 3000,
+            db_host: "localhost".to_string(),
+            db_name: "test".to_string(),
+            db_port: 5432,
+            db_user: "test".to_string(),
+            db_pass: "test".to_string(),
+            jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
+            jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
+            frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
+        }
+    }
+
+    fn user() -> User {
+        User {
+            id: 1,
+            first_name: "John".to_string(),
+            last_name: "Doe".to_string(),
+            phone: "1234567890".to_string(),
+            email: "john@example.com".to_string(),
+            role: UserRole::User,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            email_verified_at: Some(Utc::now()),
+            disabled_at: None,
+        }
+    }
+
+    fn invitation(email: Option<&str>, role: HouseRole) -> HouseInvitation {
+        HouseInvitation {
+            id: 5,
+            house_id: 10,
+            invited_by: Some(2),
+            email: email.map(str::to_string),
+            role,
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::hours(1),
+            accepted_by: None,
+            accepted_at: None,
+            declined_at: None,
+            access: MemberAccess::default(),
+        }
+    }
+
+    #[derive(Default)]
+    struct Mocks {
+        user_houses: MockUserHousesRepositoryTrait,
+        invitations: MockHouseInvitationsRepositoryTrait,
+        houses: MockHouseRepositoryTrait,
+        users: MockUserRepositoryTrait,
+        access_control: MockAccessControlServiceTrait,
+        mailer: MockMailer,
+    }
+
+    impl Mocks {
+        fn service(self) -> HouseMembersService {
+            HouseMembersService::new(
+                create_test_config(),
+                Arc::new(self.user_houses),
+                Arc::new(self.invitations),
+                Arc::new(self.houses),
+                Arc::new(self.users),
+                Arc::new(self.access_control),
+                Arc::new(self.mailer),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_email_invitation_mails_the_code() {
+        let mut mocks = Mocks::default();
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageMembers))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        mocks
+            .user_houses
+            .expect_list_members()
+            .returning(|_| Ok(vec![]));
+        mocks
+            .invitations
+            .expect_create()
+            .withf(|new| {
+                new.email.as_deref() == Some("anna@example.com")
+                    && new.role == HouseRole::Member
+                    && new.code_hash.len() == 64
+            })
+            .times(1)
+            .returning(|new| Ok(invitation(new.email.as_deref(), new.role)));
+        mocks.houses.expect_get_house_by_id().returning(|id| {
+            Ok(House {
+                id,
+                name: "Cottage".to_string(),
+                address: "1 Lake Road".to_string(),
+                r#type: "house".to_string(),
+                description: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                timezone: "UTC".to_string(),
+                latitude: None,
+                longitude: None,
+                unit_system: UnitSystem::Metric,
+                currency: "USD".to_string(),
+            })
+        });
+        mocks
+            .users
+            .expect_get_user_by_id()
+            .returning(|_| Ok(user()));
+        mocks
+            .mailer
+            .expect_send()
+            .withf(|message| {
+                message.to == "anna@example.com"
+                    && message.subject == "You're invited to Cottage"
+                    && message
+                        .body
+                        .contains("http://localhost:5173/invitations/accept?code=")
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let created = mocks
+            .service()
+            .create_invitation(
+                1,
+                10,
+                CreateHouseInvitation {
+                    email: Some(" Anna@Example.com ".to_string()),
+                    role: None,
+                    expires_in_hours: None,
+                    access: MemberAccess::default(),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(created.code.len(), INVITATION_CODE_LENGTH);
+        assert!(created.link.ends_with(&created.code));
+    }
+
+    #[tokio::test]
+    async fn test_only_owners_invite_owners() {
+        let mut mocks = Mocks::default();
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageMembers))
+            .returning(|_, _, _| Ok(()));
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageOwners))
+            .returning(|_, _, _| {
+                Err(AppError::AuthorizationError(
+                    "The admin role does not allow this action".to_string(),
+                ))
+            });
+        mocks.invitations.expect_create().never();
+
+        let result = mocks
+            .service()
+            .create_invitation(
+                1,
+                10,
+                CreateHouseInvitation {
+                    email: None,
+                    role: Some(HouseRole::Owner),
+                    expires_in_hours: None,
+                    access: MemberAccess::default(),
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::AuthorizationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_accept_invitation_for_other_email() {
+        let mut mocks = Mocks::default();
+        mocks
+            .invitations
+            .expect_find_pending_by_code_hash()
+            .returning(|_| {
+                Ok(Some(invitation(
+                    Some("anna@example.com"),
+                    HouseRole::Member,
+                )))
+            });
+        mocks
+            .users
+            .expect_get_user_by_id()
+            .returning(|_| Ok(user()));
+        mocks.invitations.expect_accept().never();
+
+        let result = mocks.service().accept_invitation_code(1, "code").await;
+
+        assert!(matches!(result, Err(AppError::AuthorizationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_accept_open_invitation_code() {
+        let mut mocks = Mocks::default();
+        mocks
+            .invitations
+            .expect_find_pending_by_code_hash()
+            .returning(|_| Ok(Some(invitation(None, HouseRole::Guest))));
+        mocks
+            .user_houses
+            .expect_get_user_role()
+            .with(eq(10), eq(1))
+            .returning(|_, _| Ok(None));
+        mocks
+            .invitations
+            .expect_accept()
+            .with(eq(5), eq(1))
+            .times(1)
+            .returning(|_, user_id| {
+                let mut accepted = invitation(None, HouseRole::Guest);
+                accepted.accepted_by = Some(user_id);
+                Ok(Some(accepted))
+            });
+
+        let accepted = mocks
+            .service()
+            .accept_invitation_code(1, "code")
+            .await
+            .unwrap();
+
+        assert_eq!(accepted.accepted_by, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_remove_owner_requires_owner() {
+        let mut mocks = Mocks::default();
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageMembers))
+            .returning(|_, _, _| Ok(()));
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageOwners))
+            .returning(|_, _, _| {
+                Err(AppError::AuthorizationError(
+                    "The admin role does not allow this action".to_string(),
+                ))
+            });
+        mocks
+            .user_houses
+            .expect_get_user_role()
+            .with(eq(10), eq(2))
+            .returning(|_, _| Ok(Some(HouseRole::Owner)));
+        mocks.user_houses.expect_remove_member().never();
+
+        let result = mocks.service().remove_member(1, 10, 2).await;
+
+        assert!(matches!(result, Err(AppError::AuthorizationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_owners_cant_be_time_boxed() {
+        let mut mocks = Mocks::default();
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageMembers))
+            .returning(|_, _, _| Ok(()));
+        mocks
+            .user_houses
+            .expect_get_user_role()
+            .with(eq(10), eq(2))
+            .returning(|_, _| Ok(Some(HouseRole::Owner)));
+        mocks.user_houses.expect_update_member_access().never();
+
+        let result = mocks
+            .service()
+            .update_member_access(
+                1,
+                10,
+                2,
+                MemberAccess {
+                    valid_until: Some(Utc::now() + Duration::days(1)),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_remove_expired_members_notifies_owners() {
+        let mut mocks = Mocks::default();
+        mocks
+            .user_houses
+            .expect_remove_expired_members()
+            .times(1)
+            .returning(|| {
+                Ok(vec![ExpiredMember {
+                    house_id: 10,
+                    user_id: 3,
+                    first_name: "Anna".to_string(),
+                    last_name: "Smith".to_string(),
+                    valid_until: Utc::now(),
+                }])
+            });
+        mocks.houses.expect_get_house_by_id().returning(|id| {
+            Ok(House {
+                id,
+                name: "Cottage".to_string(),
+                address: "1 Lake Road".to_string(),
+                r#type: "house".to_string(),
+                description: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                timezone: "UTC".to_string(),
+                latitude: None,
+                longitude: None,
+                unit_system: UnitSystem::Metric,
+                currency: "USD".to_string(),
+            })
+        });
+        mocks
+            .user_houses
+            .expect_list_members()
+            .with(eq(10))
+            .returning(|_| {
+                Ok(vec![
+                    HouseMember {
+                        user_id: 1,
+                        first_name: "John".to_string(),
+                        last_name: "Doe".to_string(),
+                        email: "john@example.com".to_string(),
+                        role: HouseRole::Owner,
+                        joined_at: Utc::now(),
+                        access: MemberAccess::default(),
+                    },
+                    HouseMember {
+                        user_id: 2,
+                        first_name: "Jane".to_string(),
+                        last_name: "Doe".to_string(),
+                        email: "jane@example.com".to_string(),
+                        role: HouseRole::Member,
+                        joined_at: Utc::now(),
+                        access: MemberAccess::default(),
+                    },
+                ])
+            });
+        mocks
+            .mailer
+            .expect_send()
+            .withf(|message| {
+                message.to == "john@example.com"
+                    && message.subject == "Access to Cottage ended"
+                    && message.body.contains("Anna Smith")
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let removed = mocks.service().remove_expired_members().await.unwrap();
+
+        assert_eq!(removed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_room_limits_must_be_house_rooms() {
+        let mut mocks = Mocks::default();
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::ManageMembers))
+            .returning(|_, _, _| Ok(()));
+        mocks
+            .user_houses
+            .expect_get_user_role()
+            .with(eq(10), eq(2))
+            .returning(|_, _| Ok(Some(HouseRole::Member)));
+        // Room 5 is in another house
+        mocks
+            .user_houses
+            .expect_get_house_by_room_id()
+            .returning(|room_id| {
+                Ok(House {
+                    id: if room_id == 4 { 10 } else { 11 },
+                    name: "Cottage".to_string(),
+                    address: "1 Lake Road".to_string(),
+                    r#type: "house".to_string(),
+                    description: "".to_string(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    timezone: "UTC".to_string(),
+                    latitude: None,
+                    longitude: None,
+                    unit_system: UnitSystem::Metric,
+                    currency: "USD".to_string(),
+                })
+            });
+        mocks.user_houses.expect_update_member_access().never();
+
+        let result = mocks
+            .service()
+            .update_member_access(
+                1,
+                10,
+                2,
+                MemberAccess {
+                    room_ids: Some(vec![4, 5]),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,439 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use mockall::automock;
+
+use crate::{
+    errors::{AppError, Result},
+    models::{
+        house_transfers::{
+            CreateHouseTransfer, HouseTransfer, ReceivedHouseTransfer, TRANSFER_EXPIRES_IN_HOURS,
+        },
+        mail::EmailMessage,
+        user_houses::HouseRole,
+    },
+    repositories::{
+        HouseRepositoryTrait, HouseTransfersRepositoryTrait, UserHousesRepositoryTrait,
+        UserRepositoryTrait,
+    },
+    services::{
+        access_control_service::{AccessControlServiceTrait, Action},
+        mailer::Mailer,
+    },
+};
+
+/// Handing a house over from an owner to another member.
+#[automock]
+#[async_trait]
+pub trait HouseTransfersServiceTrait {
+    /// Nominates a member to take over the house and emails them. Only owners
+    /// can transfer a house. A pending transfer of the house is replaced.
+    async fn create_transfer(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        data: CreateHouseTransfer,
+    ) -> Result<HouseTransfer>;
+    /// All transfers of the house, newest first.
+    async fn list_transfers(&self, user_id: i64, house_id: i64) -> Result<Vec<HouseTransfer>>;
+    async fn cancel_transfer(&self, user_id: i64, house_id: i64, transfer_id: i64) -> Result<()>;
+    /// Pending transfers to the user.
+    async fn list_received_transfers(&self, user_id: i64) -> Result<Vec<ReceivedHouseTransfer>>;
+    /// Accepts a transfer to the user. The user becomes an owner and the
+    /// previous owner gets the role the user had.
+    async fn accept_transfer(&self, user_id: i64, transfer_id: i64) -> Result<HouseTransfer>;
+    async fn decline_transfer(&self, user_id: i64, transfer_id: i64) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct HouseTransfersService {
+    house_transfers_repository: Arc<dyn HouseTransfersRepositoryTrait + Send + Sync>,
+    user_houses_repository: Arc<dyn UserHousesRepositoryTrait + Send + Sync>,
+    house_repository: Arc<dyn HouseRepositoryTrait + Send + Sync>,
+    user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+    access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+    mailer: Arc<dyn Mailer + Send + Sync>,
+}
+
+impl HouseTransfersService {
+    pub fn new(
+        house_transfers_repository: Arc<dyn HouseTransfersRepositoryTrait + Send + Sync>,
+        user_houses_repository: Arc<dyn UserHousesRepositoryTrait + Send + Sync>,
+        house_repository: Arc<dyn HouseRepositoryTrait + Send + Sync>,
+        user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+        access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+        mailer: Arc<dyn Mailer + Send + Sync>,
+    ) -> Self {
+        Self {
+            house_transfers_repository,
+            user_houses_repository,
+            house_repository,
+            user_repository,
+            access_control_service,
+            mailer,
+        }
+    }
+
+    /// Only members who have proven their email address and whose access is
+    /// active right now can take over a house.
+    async fn check_nominee(&self, house_id: i64, nominee_id: i64) -> Result<()> {
+        let membership = self
+            .user_houses_repository
+            .get_membership(house_id, nominee_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest("The nominee must be a member of the house".to_string())
+            })?;
+        if membership.role == HouseRole::Owner {
+            return Err(AppError::BadRequest(
+                "The nominee already owns the house".to_string(),
+            ));
+        }
+        if !membership
+            .access
+            .allows(&Utc::now().with_timezone(&membership.timezone))
+        {
+            return Err(AppError::BadRequest(
+                "The access of the nominee to the house is not active".to_string(),
+            ));
+        }
+
+        let nominee = self.user_repository.get_user_by_id(nominee_id).await?;
+        if nominee.email_verified_at.is_none() {
+            return Err(AppError::BadRequest(
+                "The nominee must verify their email address first".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// A pending transfer to the user.
+    async fn received_transfer(&self, user_id: i64, transfer_id: i64) -> Result<HouseTransfer> {
+        self.house_transfers_repository
+            .find_pending_by_id(transfer_id)
+            .await?
+            .filter(|transfer| transfer.to_user_id == Some(user_id))
+            .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))
+    }
+}
+
+#[async_trait]
+impl HouseTransfersServiceTrait for HouseTransfersService {
+    async fn create_transfer(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        data: CreateHouseTransfer,
+    ) -> Result<HouseTransfer> {
+        self.access_control_service
+            .can(user_id, house_id, Action::TransferOwnership)
+            .await?;
+
+        if data.to_user_id == user_id {
+            return Err(AppError::BadRequest(
+                "You can't transfer the house to yourself".to_string(),
+            ));
+        }
+        self.check_nominee(house_id, data.to_user_id).await?;
+
+        let transfer = self
+            .house_transfers_repository
+            .create(
+                house_id,
+                user_id,
+                data.to_user_id,
+                Utc::now() + Duration::hours(TRANSFER_EXPIRES_IN_HOURS),
+            )
+            .await?;
+
+        let house = self.house_repository.get_house_by_id(house_id).await?;
+        let owner = self.user_repository.get_user_by_id(user_id).await?;
+        let nominee = self.user_repository.get_user_by_id(data.to_user_id).await?;
+        self.mailer
+            .send(EmailMessage {
+                to: nominee.email,
+                subject: format!("{} wants to hand {} over to you", owner.first_name, house.name),
+                body: format!(
+                    "Hi {},\n\n{} {} wants you to take over {} as its owner. You can accept or decline the transfer in the app within {} hours. Once you accept, {} {} keeps access to the house with your current role.\n",
+                    nominee.first_name,
+                    owner.first_name,
+                    owner.last_name,
+                    house.name,
+                    TRANSFER_EXPIRES_IN_HOURS,
+                    owner.first_name,
+                    owner.last_name
+                ),
+            })
+            .await?;
+
+        Ok(transfer)
+    }
+
+    async fn list_transfers(&self, user_id: i64, house_id: i64) -> Result<Vec<HouseTransfer>> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ManageMembers)
+            .await?;
+
+        self.house_transfers_repository
+            .list_for_house(house_id)
+            .await
+    }
+
+    async fn cancel_transfer(&self, user_id: i64, house_id: i64, transfer_id: i64) -> Result<()> {
+        self.access_control_service
+            .can(user_id, house_id, Action::TransferOwnership)
+            .await?;
+
+        self.house_transfers_repository
+            .cancel(house_id, transfer_id)
+            .await
+    }
+
+    async fn list_received_transfers(&self, user_id: i64) -> Result<Vec<ReceivedHouseTransfer>> {
+        self.house_transfers_repository
+            .list_pending_for_user(user_id)
+            .await
+    }
+
+    async fn accept_transfer(&self, user_id: i64, transfer_id: i64) -> Result<HouseTransfer> {
+        let transfer = self.received_transfer(user_id, transfer_id).await?;
+
+        // The membership of the nominee may have changed since the nomination
+        self.check_nominee(transfer.house_id, user_id).await?;
+
+        let transfer = self
+            .house_transfers_repository
+            .accept(transfer.id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired transfer".to_string()))?;
+        tracing::info!(
+            "House {} transferred from user {:?} to user {}",
+            transfer.house_id,
+            transfer.from_user_id,
+            user_id
+        );
+
+        Ok(transfer)
+    }
+
+    async fn decline_transfer(&self, user_id: i64, transfer_id: i64) -> Result<()> {
+        let transfer = self.received_transfer(user_id, transfer_id).await?;
+
+        if !self.house_transfers_repository.decline(transfer.id).await? {
+            return Err(AppError::BadRequest(
+                "Invalid or expired transfer".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            houses::{House, UnitSystem},
+            user_houses::{HouseMembership, MemberAccess},
+            users::{User, UserRole},
+        },
+        repositories::{
+            house_repository::MockHouseRepositoryTrait,
+            house_transfers_repository::MockHouseTransfersRepositoryTrait,
+            user_houses_repository::MockUserHousesRepositoryTrait,
+            user_repository::MockUserRepositoryTrait,
+        },
+        services::{access_control_service::MockAccessControlServiceTrait, mailer::MockMailer},
+    };
+    use chrono_tz::Tz;
+    use mockall::predicate::eq;
+
+    fn user(id: i64, verified: bool) -> User {
+        User {
+            id,
+            first_name: format!("User{}", id),
+            last_name: "Doe".to_string(),
+            phone: "1234567890".to_string(),
+            email: format!("user{}@example.com", id),
+            role: UserRole::User,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            email_verified_at: verified.then(Utc::now),
+            disabled_at: None,
+        }
+    }
+
+    fn transfer(to_user_id: i64) -> HouseTransfer {
+        HouseTransfer {
+            id: 5,
+            house_id: 10,
+            from_user_id: Some(1),
+            to_user_id: Some(to_user_id),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::hours(1),
+            accepted_at: None,
+            declined_at: None,
+            cancelled_at: None,
+            previous_owner_role: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct Mocks {
+        transfers: MockHouseTransfersRepositoryTrait,
+        user_houses: MockUserHousesRepositoryTrait,
+        houses: MockHouseRepositoryTrait,
+        users: MockUserRepositoryTrait,
+        access_control: MockAccessControlServiceTrait,
+        mailer: MockMailer,
+    }
+
+    impl Mocks {
+        fn with_nominee(mut self, role: HouseRole, access: MemberAccess, verified: bool) -> Self {
+            self.user_houses
+                .expect_get_membership()
+                .with(eq(10), eq(2))
+                .returning(move |_, _| {
+                    Ok(Some(HouseMembership {
+                        role,
+                        access: access.clone(),
+                        timezone: Tz::UTC,
+                    }))
+                });
+            self.users
+                .expect_get_user_by_id()
+                .returning(move |id| Ok(user(id, verified)));
+            self
+        }
+
+        fn service(self) -> HouseTransfersService {
+            HouseTransfersService::new(
+                Arc::new(self.transfers),
+                Arc::new(self.user_houses),
+                Arc::new(self.houses),
+                Arc::new(self.users),
+                Arc::new(self.access_control),
+                Arc::new(self.mailer),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_transfer_mails_the_nominee() {
+        let mut mocks =
+            Mocks::default().with_nominee(HouseRole::Admin, MemberAccess::default(), true);
+        mocks
+            .access_control
+            .expect_can()
+            .with(eq(1), eq(10), eq(Action::TransferOwnership))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        mocks
+            .transfers
+            .expect_create()
+            .withf(|house_id, from, to, _| (*house_id, *from, *to) == (10, 1, 2))
+            .times(1)
+            .returning(|_, _, to, _| Ok(transfer(to)));
+        mocks.houses.expect_get_house_by_id().returning(|id| {
+            Ok(House {
+                id,
+                name: "Cottage".to_string(),
+                address: "1 Lake Road".to_string(),
+                r#type: "house".to_string(),
+                description: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                timezone: "UTC".to_string(),
+                latitude: None,
+                longitude: None,
+                unit_system: UnitSystem::Metric,
+                currency: "USD".to_string(),
+            })
+        });
+        mocks
+            .mailer
+            .expect_send()
+            .withf(|message| {
+                message.to == "user2@example.com"
+                    && message.subject == "User1 wants to hand Cottage over to you"
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let created = mocks
+            .service()
+            .create_transfer(1, 10, CreateHouseTransfer { to_user_id: 2 })
+            .await
+            .unwrap();
+
+        assert_eq!(created.to_user_id, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_nominee_needs_verified_active_access() {
+        let time_boxed = MemberAccess {
+            valid_from: Some(Utc::now() + Duration::days(1)),
+            ..MemberAccess::default()
+        };
+
+        for mocks in [
+            Mocks::default().with_nominee(HouseRole::Member, MemberAccess::default(), false),
+            Mocks::default().with_nominee(HouseRole::Member, time_boxed, true),
+            Mocks::default().with_nominee(HouseRole::Owner, MemberAccess::default(), true),
+        ] {
+            let mut mocks = mocks;
+            mocks
+                .access_control
+                .expect_can()
+                .returning(|_, _, _| Ok(()));
+            mocks.transfers.expect_create().never();
+
+            let result = mocks
+                .service()
+                .create_transfer(1, 10, CreateHouseTransfer { to_user_id: 2 })
+                .await;
+
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_the_nominee_accepts() {
+        let mut mocks = Mocks::default();
+        mocks
+            .transfers
+            .expect_find_pending_by_id()
+            .with(eq(5))
+            .returning(|_| Ok(Some(transfer(2))));
+        mocks.transfers.expect_accept().never();
+
+        let result = mocks.service().accept_transfer(3, 5).await;
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_accept_transfer_rechecks_the_nominee() {
+        let mut mocks =
+            Mocks::default().with_nominee(HouseRole::Member, MemberAccess::default(), true);
+        mocks
+            .transfers
+            .expect_find_pending_by_id()
+            .returning(|_| Ok(Some(transfer(2))));
+        mocks.transfers.expect_accept().with(eq(5)).returning(|_| {
+            Ok(Some(HouseTransfer {
+                accepted_at: Some(Utc::now()),
+                previous_owner_role: Some(HouseRole::Member),
+                ..transfer(2)
+            }))
+        });
+
+        let accepted = mocks.service().accept_transfer(2, 5).await.unwrap();
+
+        assert_eq!(accepted.previous_owner_role, Some(HouseRole::Member));
+    }
+}
@@ -0,0 +1,362 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation,
+};
+use rsa::{
+    pkcs8::{DecodePublicKey, Document, ObjectIdentifier, SubjectPublicKeyInfoRef},
+    traits::PublicKeyParts,
+    RsaPublicKey,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    config::{Config, JwtAlgorithm},
+    errors::{AppError, Result},
+    models::jwks::{JsonWebKey, JsonWebKeySet},
+};
+
+const RSA_ENCRYPTION_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const ED25519_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/// Public key tokens with a matching `kid` header are verified with.
+#[derive(Clone)]
+struct VerificationKey {
+    algorithm: Algorithm,
+    decoding_key: DecodingKey,
+    jwk: JsonWebKey,
+}
+
+#[derive(Clone)]
+enum Verification {
+    /// Tokens are verified with the signing secret.
+    Secret(DecodingKey),
+    /// Tokens are verified with the public key named by their `kid` header.
+    PublicKeys(BTreeMap<String, VerificationKey>),
+}
+
+/// Keys access tokens are signed and verified with.
+///
+/// In `HS256` mode the shared `jwt_secret` is used for both. In `RS256` and
+/// `EdDSA` mode tokens are signed with the private key and carry its `kid`,
+/// and every public key in `jwt_public_keys_dir` is accepted, so keys can be
+/// rotated by adding the new public key before switching the signing key.
+#[derive(Clone)]
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    kid: Option<String>,
+    encoding_key: EncodingKey,
+    verification: Verification,
+}
+
+impl JwtKeys {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let algorithm = match config.jwt_algorithm {
+            JwtAlgorithm::HS256 => return Ok(Self::hs256(&config.jwt_secret)),
+            JwtAlgorithm::RS256 => Algorithm::RS256,
+            JwtAlgorithm::EdDSA => Algorithm::EdDSA,
+        };
+        let required = |value: &Option<String>, name: &str| {
+            value.clone().ok_or_else(|| {
+                invalid(format!(
+                    "{} is required for {:?} signing",
+                    name, config.jwt_algorithm
+                ))
+            })
+        };
+        let private_key_path = required(&config.jwt_private_key_path, "JWT_PRIVATE_KEY_PATH")?;
+        let kid = required(&config.jwt_key_id, "JWT_KEY_ID")?;
+        let public_keys_dir = required(&config.jwt_public_keys_dir, "JWT_PUBLIC_KEYS_DIR")?;
+
+        let pem = fs::read(&private_key_path)
+            .map_err(|e| invalid(format!("Cannot read {}: {}", private_key_path, e)))?;
+        let encoding_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&pem),
+            _ => EncodingKey::from_ed_pem(&pem),
+        }
+        .map_err(|e| invalid(format!("Invalid private key {}: {}", private_key_path, e)))?;
+
+        let public_keys = load_public_keys(Path::new(&public_keys_dir))?;
+        match public_keys.get(&kid) {
+            Some(key) if key.algorithm == algorithm => {}
+            _ => {
+                return Err(invalid(format!(
+                    "No {:?} public key for kid {} in {}",
+                    algorithm, kid, public_keys_dir
+                )))
+            }
+        }
+
+        let keys = Self {
+            algorithm,
+            kid: Some(kid.clone()),
+            encoding_key,
+            verification: Verification::PublicKeys(public_keys),
+        };
+        keys.check_key_pair(&kid)?;
+
+        Ok(keys)
+    }
+
+    /// Signs and verifies tokens with a shared secret.
+    pub fn hs256(secret: &str) -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            kid: None,
+            encoding_key: EncodingKey::from_secret(secret.as_ref()),
+            verification: Verification::Secret(DecodingKey::from_secret(secret.as_ref())),
+        }
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.kid.clone();
+
+        Ok(jsonwebtoken::encode(&header, claims, &self.encoding_key)?)
+    }
+
+    /// Verifies the signature and expiration of a token. Tokens with an
+    /// unknown `kid` fail with `ErrorKind::InvalidSignature`.
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        token: &str,
+    ) -> std::result::Result<TokenData<T>, jsonwebtoken::errors::Error> {
+        match &self.verification {
+            Verification::Secret(decoding_key) => {
+                jsonwebtoken::decode(token, decoding_key, &Validation::default())
+            }
+            Verification::PublicKeys(public_keys) => {
+                let header = jsonwebtoken::decode_header(token)?;
+                let key = header
+                    .kid
+                    .and_then(|kid| public_keys.get(&kid))
+                    .ok_or(ErrorKind::InvalidSignature)?;
+
+                jsonwebtoken::decode(token, &key.decoding_key, &Validation::new(key.algorithm))
+            }
+        }
+    }
+
+    /// Public keys accepted for verification. Empty in `HS256` mode.
+    pub fn jwks(&self) -> JsonWebKeySet {
+        let keys = match &self.verification {
+            Verification::Secret(_) => Vec::new(),
+            Verification::PublicKeys(public_keys) => {
+                public_keys.values().map(|key| key.jwk.clone()).collect()
+            }
+        };
+
+        JsonWebKeySet { keys }
+    }
+
+    /// Makes sure the private key belongs to the public key published as `kid`.
+    fn check_key_pair(&self, kid: &str) -> Result<()> {
+        #[derive(Serialize, Deserialize)]
+        struct Probe {
+            exp: usize,
+        }
+
+        let token = self.encode(&Probe { exp: usize::MAX })?;
+        self.decode::<Probe>(&token).map_err(|_| {
+            invalid(format!(
+                "Private key does not match the public key for kid {}",
+                kid
+            ))
+        })?;
+
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> AppError {
+    AppError::InternalServerError(format!("Invalid JWT key configuration: {}", message))
+}
+
+/// Loads every `<kid>.pem` public key in `dir`.
+fn load_public_keys(dir: &Path) -> Result<BTreeMap<String, VerificationKey>> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| invalid(format!("Cannot read {}: {}", dir.display(), e)))?;
+    let mut public_keys = BTreeMap::new();
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| invalid(format!("Cannot read {}: {}", dir.display(), e)))?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("pem") {
+            continue;
+        }
+        let Some(kid) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let pem = fs::read_to_string(&path)
+            .map_err(|e| invalid(format!("Cannot read {}: {}", path.display(), e)))?;
+        let key = parse_public_key(kid, &pem)
+            .map_err(|e| invalid(format!("Invalid public key {}: {}", path.display(), e)))?;
+        public_keys.insert(kid.to_string(), key);
+    }
+
+    Ok(public_keys)
+}
+
+/// Parses an RSA or Ed25519 public key in SPKI PEM format.
+fn parse_public_key(kid: &str, pem: &str) -> std::result::Result<VerificationKey, String> {
+    let (_, document) = Document::from_pem(pem).map_err(|e| e.to_string())?;
+    let spki = SubjectPublicKeyInfoRef::try_from(document.as_bytes()).map_err(|e| e.to_string())?;
+
+    match spki.algorithm.oid {
+        RSA_ENCRYPTION_OID => {
+            let public_key = RsaPublicKey::from_public_key_der(document.as_bytes())
+                .map_err(|e| e.to_string())?;
+            let n = URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be());
+            let e = URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be());
+
+            Ok(VerificationKey {
+                algorithm: Algorithm::RS256,
+                decoding_key: DecodingKey::from_rsa_components(&n, &e)
+                    .map_err(|e| e.to_string())?,
+                jwk: JsonWebKey {
+                    kty: "RSA".to_string(),
+                    kid: kid.to_string(),
+                    alg: "RS256".to_string(),
+                    use_: "sig".to_string(),
+                    n: Some(n),
+                    e: Some(e),
+                    crv: None,
+                    x: None,
+                },
+            })
+        }
+        ED25519_OID => {
+            let x = URL_SAFE_NO_PAD.encode(spki.subject_public_key.raw_bytes());
+
+            Ok(VerificationKey {
+                algorithm: Algorithm::EdDSA,
+                decoding_key: DecodingKey::from_ed_components(&x).map_err(|e| e.to_string())?,
+                jwk: JsonWebKey {
+                    kty: "OKP".to_string(),
+                    kid: kid.to_string(),
+                    alg: "EdDSA".to_string(),
+                    use_: "sig".to_string(),
+                    n: None,
+                    e: None,
+                    crv: Some("Ed25519".to_string()),
+                    x: Some(x),
+                },
+            })
+        }
+        oid => Err(format!("Unsupported key algorithm {}", oid)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::auth::Claims;
+    use chrono::Utc;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/fixtures/jwt");
+
+    fn config(algorithm: JwtAlgorithm, kid: &str) -> Config {
+        Config {
+            port: 3000,
+            db_host: "localhost".to_string(),
+            db_name: "test".to_string(),
+            db_port: 5432,
+            db_user: "test".to_string(),
+            db_pass: "test".to_string(),
+            jwt_secret: "test_secret_key_that_is_long_enough".to_string(),
+            jwt_algorithm: algorithm,
+            jwt_private_key_path: Some(format!("{}/private/{}.pem", FIXTURES, kid)),
+            jwt_key_id: Some(kid.to_string()),
+            jwt_public_keys_dir: Some(format!("{}/public", FIXTURES)),
+            jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
+            frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
+        }
+    }
+
+    fn claims() -> Claims {
+        Claims {
+            sub: 1,
+            exp: (Utc::now().timestamp() + 60) as usize,
+            sid: 2,
+        }
+    }
+
+    #[test]
+    fn test_hs256_round_trip_without_jwks() {
+        let keys = JwtKeys::hs256("secret");
+
+        let token = keys.encode(&claims()).unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+
+        assert_eq!(header.alg, Algorithm::HS256);
+        assert!(header.kid.is_none());
+        assert_eq!(keys.decode::<Claims>(&token).unwrap().claims.sub, 1);
+        assert!(keys.jwks().keys.is_empty());
+    }
+
+    #[test]
+    fn test_eddsa_tokens_carry_kid() {
+        let keys = JwtKeys::from_config(&config(JwtAlgorithm::EdDSA, "ed-2026-10")).unwrap();
+
+        let token = keys.encode(&claims()).unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+
+        assert_eq!(header.alg, Algorithm::EdDSA);
+        assert_eq!(header.kid.as_deref(), Some("ed-2026-10"));
+        assert_eq!(keys.decode::<Claims>(&token).unwrap().claims.sid, 2);
+    }
+
+    #[test]
+    fn test_rotated_keys_remain_valid() {
+        let old_keys = JwtKeys::from_config(&config(JwtAlgorithm::RS256, "rsa-2026-01")).unwrap();
+        let new_keys = JwtKeys::from_config(&config(JwtAlgorithm::RS256, "rsa-2026-10")).unwrap();
+        let old_token = old_keys.encode(&claims()).unwrap();
+
+        assert!(new_keys.decode::<Claims>(&old_token).is_ok());
+
+        let kids: Vec<_> = new_keys.jwks().keys.into_iter().map(|k| k.kid).collect();
+        assert_eq!(kids, vec!["ed-2026-10", "rsa-2026-01", "rsa-2026-10"]);
+    }
+
+    #[test]
+    fn test_rejects_unknown_kid_and_secret_tokens() {
+        let keys = JwtKeys::from_config(&config(JwtAlgorithm::RS256, "rsa-2026-10")).unwrap();
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("rsa-2026-10".to_string());
+        let secret_token = jsonwebtoken::encode(
+            &header,
+            &claims(),
+            &EncodingKey::from_secret(b"test_secret_key_that_is_long_enough"),
+        )
+        .unwrap();
+
+        assert!(keys.decode::<Claims>(&secret_token).is_err());
+
+        let token = JwtKeys::hs256("secret").encode(&claims()).unwrap();
+        let error = keys.decode::<Claims>(&token).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidSignature);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_configuration() {
+        let mut mismatched = config(JwtAlgorithm::RS256, "rsa-2026-10");
+        mismatched.jwt_key_id = Some("rsa-2026-01".to_string());
+        assert!(JwtKeys::from_config(&mismatched).is_err());
+
+        let mut missing = config(JwtAlgorithm::EdDSA, "ed-2026-10");
+        missing.jwt_key_id = Some("ed-2027-01".to_string());
+        assert!(JwtKeys::from_config(&missing).is_err());
+
+        let wrong_algorithm = config(JwtAlgorithm::EdDSA, "rsa-2026-10");
+        assert!(JwtKeys::from_config(&wrong_algorithm).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use mockall::automock;
+
+use crate::{
+    errors::{AppError, Result},
+    models::login_attempts::{AttemptScope, ThrottleKey},
+    repositories::LoginAttemptsRepositoryTrait,
+};
+
+/// Failures are counted within this window; older ones are forgotten.
+const FAILURE_WINDOW: i64 = 3600;
+/// Failures allowed per account before it is locked out.
+const FREE_ACCOUNT_ATTEMPTS: i32 = 5;
+/// Failures allowed per IP address. Higher than per account, since several
+/// users can share an address.
+const FREE_IP_ATTEMPTS: i32 = 20;
+const BASE_LOCKOUT: u64 = 30;
+const MAX_LOCKOUT: u64 = 3600;
+
+/// Slows down password guessing by locking accounts and IP addresses with
+/// too many recent failures.
+#[automock]
+#[async_trait]
+pub trait LoginThrottleServiceTrait {
+    /// Fails with `TooManyRequests` if any of the keys is locked.
+    async fn check(&self, keys: &[ThrottleKey]) -> Result<()>;
+    /// Counts a failed attempt and locks keys over their limit.
+    async fn record_failure(&self, keys: &[ThrottleKey]) -> Result<()>;
+    /// Forgets failures of the keys after a successful attempt.
+    async fn reset(&self, keys: &[ThrottleKey]) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct LoginThrottleService {
+    login_attempts_repository: Arc<dyn LoginAttemptsRepositoryTrait + Send + Sync>,
+}
+
+impl LoginThrottleService {
+    pub fn new(
+        login_attempts_repository: Arc<dyn LoginAttemptsRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            login_attempts_repository,
+        }
+    }
+
+    /// Lockout after `failed_count` failures: doubles with every failure past
+    /// the free ones, up to an hour.
+    fn lockout_seconds(scope: AttemptScope, failed_count: i32) -> Option<u64> {
+        let free_attempts = match scope {
+            AttemptScope::Account => FREE_ACCOUNT_ATTEMPTS,
+            AttemptScope::Ip => FREE_IP_ATTEMPTS,
+        };
+        let excess = failed_count - free_attempts;
+        if excess <= 0 {
+            return None;
+        }
+
+        let factor = 1u64.checked_shl((excess - 1) as u32).unwrap_or(u64::MAX);
+        Some(BASE_LOCKOUT.saturating_mul(factor).min(MAX_LOCKOUT))
+    }
+}
+
+#[async_trait]
+impl LoginThrottleServiceTrait for LoginThrottleService {
+    async fn check(&self, keys: &[ThrottleKey]) -> Result<()> {
+        let now = Utc::now();
+        let mut retry_after = 0;
+
+        for key in keys {
+            let locked_until = self
+                .login_attempts_repository
+                .find(key)
+                .await?
+                .and_then(|attempt| attempt.locked_until);
+
+            if let Some(locked_until) = locked_until.filter(|until| *until > now) {
+                let seconds = (locked_until - now).num_seconds().max(1) as u64;
+                retry_after = retry_after.max(seconds);
+            }
+        }
+
+        if retry_after > 0 {
+            return Err(AppError::TooManyRequests {
+                message: "Too many failed attempts, try again later".to_string(),
+                retry_after,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn record_failure(&self, keys: &[ThrottleKey]) -> Result<()> {
+        let window_start = Utc::now() - chrono::Duration::seconds(FAILURE_WINDOW);
+
+        for key in keys {
+            let attempt = self
+                .login_attempts_repository
+                .record_failure(key, window_start)
+                .await?;
+
+            if let Some(seconds) = Self::lockout_seconds(key.scope, attempt.failed_count) {
+                self.login_attempts_repository
+                    .lock(key, Utc::now() + chrono::Duration::seconds(seconds as i64))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reset(&self, keys: &[ThrottleKey]) -> Result<()> {
+        for key in keys {
+            self.login_attempts_repository.reset(key).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::login_attempts::LoginAttempt,
+        repositories::login_attempts_repository::MockLoginAttemptsRepositoryTrait,
+    };
+
+    fn attempt(key: &ThrottleKey, failed_count: i32) -> LoginAttempt {
+        LoginAttempt {
+            scope: key.scope.as_str().to_string(),
+            key: key.key.clone(),
+            failed_count,
+            last_failed_at: Utc::now(),
+            locked_until: None,
+        }
+    }
+
+    #[test]
+    fn test_lockout_grows_exponentially_up_to_limit() {
+        assert_eq!(
+            LoginThrottleService::lockout_seconds(AttemptScope::Account, 5),
+            None
+        );
+        assert_eq!(
+            LoginThrottleService::lockout_seconds(AttemptScope::Account, 6),
+            Some(30)
+        );
+        assert_eq!(
+            LoginThrottleService::lockout_seconds(AttemptScope::Account, 8),
+            Some(120)
+        );
+        assert_eq!(
+            LoginThrottleService::lockout_seconds(AttemptScope::Account, 100),
+            Some(MAX_LOCKOUT)
+        );
+        assert_eq!(
+            LoginThrottleService::lockout_seconds(AttemptScope::Ip, 20),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_longest_lockout() {
+        let mut mock_repo = MockLoginAttemptsRepositoryTrait::new();
+        mock_repo.expect_find().times(2).returning(|key| {
+            let mut attempt = attempt(key, 10);
+            let seconds = match key.scope {
+                AttemptScope::Account => 60,
+                AttemptScope::Ip => 600,
+            };
+            attempt.locked_until = Some(Utc::now() + chrono::Duration::seconds(seconds));
+            Ok(Some(attempt))
+        });
+
+        let service = LoginThrottleService::new(Arc::new(mock_repo));
+        let result = service
+            .check(&[
+                ThrottleKey::account("john@example.com"),
+                ThrottleKey::ip("10.0.0.1"),
+            ])
+            .await;
+
+        match result {
+            Err(AppError::TooManyRequests { retry_after, .. }) => {
+                assert!((590..=600).contains(&retry_after))
+            }
+            _ => panic!("Expected TooManyRequests"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_ignores_expired_lockout() {
+        let mut mock_repo = MockLoginAttemptsRepositoryTrait::new();
+        mock_repo.expect_find().times(1).returning(|key| {
+            let mut attempt = attempt(key, 10);
+            attempt.locked_until = Some(Utc::now() - chrono::Duration::seconds(1));
+            Ok(Some(attempt))
+        });
+
+        let service = LoginThrottleService::new(Arc::new(mock_repo));
+
+        assert!(service
+            .check(&[ThrottleKey::account("john@example.com")])
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_record_failure_locks_over_limit() {
+        let mut mock_repo = MockLoginAttemptsRepositoryTrait::new();
+        mock_repo
+            .expect_record_failure()
+            .times(2)
+            .returning(|key, _| Ok(attempt(key, FREE_ACCOUNT_ATTEMPTS + 1)));
+        // Only the account is over its limit
+        mock_repo
+            .expect_lock()
+            .withf(|key, until| {
+                key.scope == AttemptScope::Account
+                    && *until > Utc::now() + chrono::Duration::seconds(25)
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = LoginThrottleService::new(Arc::new(mock_repo));
+
+        assert!(service
+            .record_failure(&[
+                ThrottleKey::account("john@example.com"),
+                ThrottleKey::ip("10.0.0.1"),
+            ])
+            .await
+            .is_ok());
+    }
+}
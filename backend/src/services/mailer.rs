@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use mockall::automock;
+
+use crate::{
+    errors::{AppError, Result},
+    models::mail::EmailMessage,
+    repositories::{OutboxRepository, OutboxRepositoryTrait},
+    AppState,
+};
+
+/// Delivers outgoing emails.
+#[automock]
+#[async_trait]
+pub trait Mailer {
+    async fn send(&self, message: EmailMessage) -> Result<()>;
+}
+
+/// Builds the mailer configured for the application: SMTP when `SMTP_URL` is
+/// set, the `outbox` table otherwise.
+pub fn from_app_state(app_state: &AppState) -> Arc<dyn Mailer + Send + Sync> {
+    match &app_state.config.smtp_url {
+        Some(url) => Arc::new(
+            SmtpMailer::new(url, &app_state.config.mail_from).expect("Invalid SMTP configuration"),
+        ),
+        None => Arc::new(OutboxMailer::new(Arc::new(OutboxRepository::new(
+            app_state.db.pool.clone(),
+        )))),
+    }
+}
+
+/// Stores emails in the `outbox` table instead of sending them. Useful for
+/// development and tests, or for a separate process that relays the outbox.
+#[derive(Clone)]
+pub struct OutboxMailer {
+    outbox_repository: Arc<dyn OutboxRepositoryTrait + Send + Sync>,
+}
+
+impl OutboxMailer {
+    pub fn new(outbox_repository: Arc<dyn OutboxRepositoryTrait + Send + Sync>) -> Self {
+        Self { outbox_repository }
+    }
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+    async fn send(&self, message: EmailMessage) -> Result<()> {
+        self.outbox_repository.create(&message).await
+    }
+}
+
+/// Sends emails through an SMTP server, e.g. `smtp://localhost:1025` for a
+/// local catch-all server.
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(url: &str, from: &str) -> Result<Self> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+            .map_err(|e| AppError::InternalServerError(format!("Invalid SMTP URL: {}", e)))?
+            .build();
+        let from = from
+            .parse()
+            .map_err(|e| AppError::InternalServerError(format!("Invalid sender: {}", e)))?;
+
+        Ok(Self { transport, from })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: EmailMessage) -> Result<()> {
+        let to: Mailbox = message
+            .to
+            .parse()
+            .map_err(|_| AppError::BadRequest(format!("Invalid recipient: {}", message.to)))?;
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(message.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body)
+            .map_err(|e| AppError::InternalServerError(format!("Invalid email: {}", e)))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::outbox_repository::MockOutboxRepositoryTrait;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    fn message() -> EmailMessage {
+        EmailMessage {
+            to: "john@example.com".to_string(),
+            subject: "Hello".to_string(),
+            body: "Hello John".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outbox_mailer_stores_message() {
+        let mut mock_repo = MockOutboxRepositoryTrait::new();
+        mock_repo
+            .expect_create()
+            .withf(|m| *m == message())
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mailer = OutboxMailer::new(Arc::new(mock_repo));
+
+        assert!(mailer.send(message()).await.is_ok());
+    }
+
+    /// Minimal catch-all SMTP server that accepts one message and returns its
+    /// DATA section.
+    async fn catch_all_server(listener: TcpListener) -> String {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut data = String::new();
+        let mut in_data = false;
+
+        writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            if in_data {
+                if line == "." {
+                    in_data = false;
+                    writer.write_all(b"250 OK\r\n").await.unwrap();
+                } else {
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                continue;
+            }
+
+            let reply: &[u8] = match line.split(' ').next().unwrap_or_default() {
+                "EHLO" | "HELO" => b"250 localhost\r\n",
+                "DATA" => {
+                    in_data = true;
+                    b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                }
+                "QUIT" => {
+                    writer.write_all(b"221 Bye\r\n").await.unwrap();
+                    break;
+                }
+                _ => b"250 OK\r\n",
+            };
+            writer.write_all(reply).await.unwrap();
+        }
+
+        data
+    }
+
+    #[tokio::test]
+    async fn test_smtp_mailer_sends_message() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(catch_all_server(listener));
+
+        let mailer = SmtpMailer::new(
+            &format!("smtp://127.0.0.1:{}", port),
+            "Smart Home <no-reply@smarthome.local>",
+        )
+        .unwrap();
+
+        mailer.send(message()).await.expect("Failed to send email");
+
+        let data = server.await.unwrap();
+        assert!(data.contains("To: john@example.com"));
+        assert!(data.contains("Subject: Hello"));
+        assert!(data.contains("Hello John"));
+    }
+
+    #[tokio::test]
+    async fn test_smtp_mailer_rejects_invalid_recipient() {
+        let mailer = SmtpMailer::new("smtp://127.0.0.1:1", "no-reply@smarthome.local").unwrap();
+
+        let result = mailer
+            .send(EmailMessage {
+                to: "not-an-email".to_string(),
+                ..message()
+            })
+            .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+}
@@ -4,9 +4,11 @@ use async_trait::async_trait;
 use mockall::automock;
 
 use crate::{
-    errors::Result,
-    models::rooms::{NewRoom, Room},
-    repositories::rooms_repository::RoomsRepositoryTrait,
+    errors::{AppError, Result},
+    models::rooms::{NewRoom, Room, UpdateRoom},
+    repositories::{
+        rooms_repository::RoomsRepositoryTrait, zones_repository::ZonesRepositoryTrait,
+    },
 };
 
 #[automock]
@@ -14,6 +16,7 @@ use crate::{
 pub trait RoomsServiceTrait {
     async fn get_house_rooms(&self, house_id: i64) -> Result<Vec<Room>>;
     async fn create_house_room(&self, house_id: i64, room: NewRoom) -> Result<Room>;
+    async fn update_room(&self, room_id: i64, room: UpdateRoom) -> Result<Room>;
     async fn delete_room(&self, room_id: i64) -> Result<()>;
     async fn get_room(&self, room_id: i64) -> Result<Room>;
 }
@@ -21,12 +24,38 @@ pub trait RoomsServiceTrait {
 #[derive(Clone)]
 pub struct RoomsService {
     rooms_repository: Arc<dyn RoomsRepositoryTrait + Send + Sync>,
+    zones_repository: Arc<dyn ZonesRepositoryTrait + Send + Sync>,
 }
 
 impl RoomsService {
-    pub fn new(rooms_repository: Arc<dyn RoomsRepositoryTrait + Send + Sync>) -> Self {
-        Self { rooms_repository }
+    pub fn new(
+        rooms_repository: Arc<dyn RoomsRepositoryTrait + Send + Sync>,
+        zones_repository: Arc<dyn ZonesRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            rooms_repository,
+            zones_repository,
+        }
     }
+
+    /// Rooms can only be put in zones of their own house.
+    async fn check_zone(&self, house_id: i64, zone_id: i64) -> Result<()> {
+        let zone = match self.zones_repository.get_zone(zone_id).await {
+            Ok(zone) => zone,
+            Err(AppError::NotFound(_)) => return Err(zone_error()),
+            Err(e) => return Err(e),
+        };
+
+        if zone.house_id != house_id {
+            return Err(zone_error());
+        }
+
+        Ok(())
+    }
+}
+
+fn zone_error() -> AppError {
+    AppError::BadRequest("Zone must belong to the house".to_string())
 }
 
 #[async_trait]
@@ -38,6 +67,10 @@ impl RoomsServiceTrait for RoomsService {
     }
 
     async fn create_house_room(&self, house_id: i64, room: NewRoom) -> Result<Room> {
+        if let Some(zone_id) = room.zone_id {
+            self.check_zone(house_id, zone_id).await?;
+        }
+
         let room = self
             .rooms_repository
             .create_house_room(house_id, room)
@@ -46,6 +79,15 @@ impl RoomsServiceTrait for RoomsService {
         Ok(room)
     }
 
+    async fn update_room(&self, room_id: i64, room: UpdateRoom) -> Result<Room> {
+        if let Some(Some(zone_id)) = room.zone_id {
+            let current = self.rooms_repository.get_room(room_id).await?;
+            self.check_zone(current.house_id, zone_id).await?;
+        }
+
+        self.rooms_repository.update_room(room_id, room).await
+    }
+
     async fn delete_room(&self, room_id: i64) -> Result<()> {
         self.rooms_repository.delete_room(room_id).await
     }
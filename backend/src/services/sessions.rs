@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{
+    errors::Result,
+    models::{
+        auth_events::{AuthEventType, NewAuthEvent},
+        sessions::{ClientInfo, PublicSession},
+    },
+    repositories::SessionsRepositoryTrait,
+    services::auth_events::AuthEventsServiceTrait,
+};
+
+#[automock]
+#[async_trait]
+pub trait SessionsServiceTrait {
+    /// Lists the user's active sessions, flagging the one the request was made with.
+    async fn get_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<i64>,
+    ) -> Result<Vec<PublicSession>>;
+    async fn revoke_session(&self, user_id: i64, session_id: i64, client: ClientInfo)
+        -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct SessionsService {
+    sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+    auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
+}
+
+impl SessionsService {
+    pub fn new(
+        sessions_repository: Arc<dyn SessionsRepositoryTrait + Send + Sync>,
+        auth_events_service: Arc<dyn AuthEventsServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            sessions_repository,
+            auth_events_service,
+        }
+    }
+}
+
+#[async_trait]
+impl SessionsServiceTrait for SessionsService {
+    async fn get_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<i64>,
+    ) -> Result<Vec<PublicSession>> {
+        let sessions = self
+            .sessions_repository
+            .find_active_by_user_id(user_id)
+            .await?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| session.into_public(current_session_id))
+            .collect())
+    }
+
+    async fn revoke_session(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        client: ClientInfo,
+    ) -> Result<()> {
+        self.sessions_repository.revoke(session_id, user_id).await?;
+        self.auth_events_service
+            .record(
+                NewAuthEvent::new(AuthEventType::TokenRevoked, Some(user_id), &client)
+                    .with_details(format!("Revoked session {}", session_id)),
+            )
+            .await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::AppError, models::sessions::Session,
+        repositories::sessions_repository::MockSessionsRepositoryTrait,
+        services::auth_events::MockAuthEventsServiceTrait,
+    };
+    use chrono::Utc;
+    use mockall::predicate::eq;
+
+    fn session(id: i64) -> Session {
+        Session {
+            id,
+            user_id: 1,
+            refresh_token_hash: "hash".to_string(),
+            user_agent: Some("test-agent".to_string()),
+            ip: Some("127.0.0.1".to_string()),
+            created_at: Utc::now(),
+            last_used_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::days(30),
+            revoked_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_sessions_marks_current() {
+        let mut mock_repo = MockSessionsRepositoryTrait::new();
+        mock_repo
+            .expect_find_active_by_user_id()
+            .with(eq(1))
+            .times(1)
+            .returning(|_| Ok(vec![session(1), session(2)]));
+
+        let service = SessionsService::new(
+            Arc::new(mock_repo),
+            Arc::new(MockAuthEventsServiceTrait::new()),
+        );
+        let sessions = service.get_sessions(1, Some(2)).await.unwrap();
+
+        assert_eq!(sessions.len(), 2);
+        assert!(!sessions[0].current);
+        assert!(sessions[1].current);
+        assert_eq!(sessions[1].user_agent.as_deref(), Some("test-agent"));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_unknown_session() {
+        let mut mock_repo = MockSessionsRepositoryTrait::new();
+        mock_repo
+            .expect_revoke()
+            .with(eq(5), eq(1))
+            .times(1)
+            .returning(|id, _| {
+                Err(AppError::NotFound(format!(
+                    "Session with id {} not found",
+                    id
+                )))
+            });
+
+        let service = SessionsService::new(
+            Arc::new(mock_repo),
+            Arc::new(MockAuthEventsServiceTrait::new()),
+        );
+        let result = service.revoke_session(1, 5, ClientInfo::default()).await;
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+}
@@ -0,0 +1,394 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use mockall::automock;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::{
+    errors::{AppError, Result},
+    models::two_factor::{RecoveryCodes, TotpEnrollment},
+    repositories::{TwoFactorRepositoryTrait, UserRepositoryTrait},
+};
+
+const TOTP_ISSUER: &str = "Smart Home";
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP: u64 = 30;
+/// Number of steps before and after the current one a code is accepted for,
+/// to tolerate clock drift.
+const TOTP_SKEW: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_CHARSET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[automock]
+#[async_trait]
+pub trait TwoFactorServiceTrait {
+    /// Generates a new TOTP secret. Two-factor authentication is enabled once
+    /// it is confirmed with a code.
+    async fn enroll(&self, user_id: i64) -> Result<TotpEnrollment>;
+    /// Enables two-factor authentication and returns fresh recovery codes.
+    async fn confirm(&self, user_id: i64, code: &str) -> Result<RecoveryCodes>;
+    async fn disable(&self, user_id: i64, code: &str) -> Result<()>;
+    async fn is_enabled(&self, user_id: i64) -> Result<bool>;
+    /// Checks a TOTP code or consumes a recovery code.
+    async fn verify(&self, user_id: i64, code: &str) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct TwoFactorService {
+    user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+    two_factor_repository: Arc<dyn TwoFactorRepositoryTrait + Send + Sync>,
+}
+
+impl TwoFactorService {
+    pub fn new(
+        user_repository: Arc<dyn UserRepositoryTrait + Send + Sync>,
+        two_factor_repository: Arc<dyn TwoFactorRepositoryTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            user_repository,
+            two_factor_repository,
+        }
+    }
+
+    fn totp(secret: &str, account_name: String) -> Result<TOTP> {
+        let secret = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|_| AppError::InternalServerError("Invalid TOTP secret".to_string()))?;
+
+        TOTP::new(
+            Algorithm::SHA1,
+            TOTP_DIGITS,
+            0,
+            TOTP_STEP,
+            secret,
+            Some(TOTP_ISSUER.to_string()),
+            account_name,
+        )
+        .map_err(|e| AppError::InternalServerError(format!("Invalid TOTP parameters: {}", e)))
+    }
+
+    /// Returns the time step `code` is valid for, if any.
+    fn matching_step(totp: &TOTP, code: &str) -> Option<i64> {
+        let current_step = Utc::now().timestamp() / TOTP_STEP as i64;
+
+        (current_step - TOTP_SKEW..=current_step + TOTP_SKEW)
+            .find(|step| totp.check(code, *step as u64 * TOTP_STEP))
+    }
+
+    fn generate_recovery_code() -> String {
+        let mut rng = rand::rng();
+        let code: String = (0..10)
+            .map(|_| {
+                RECOVERY_CODE_CHARSET[rng.random_range(0..RECOVERY_CODE_CHARSET.len())] as char
+            })
+            .collect();
+        format!("{}-{}", &code[..5], &code[5..])
+    }
+
+    /// Recovery codes are random, so like refresh tokens an unsalted SHA-256
+    /// is enough. Dashes and case are ignored when they are typed in.
+    fn hash_recovery_code(code: &str) -> String {
+        let normalized: String = code
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        hex::encode(Sha256::digest(normalized.as_bytes()))
+    }
+
+    async fn verify_totp(&self, user_id: i64, secret: &str, code: &str) -> Result<bool> {
+        let totp = Self::totp(secret, user_id.to_string())?;
+        match Self::matching_step(&totp, code) {
+            Some(step) => self.two_factor_repository.use_step(user_id, step).await,
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl TwoFactorServiceTrait for TwoFactorService {
+    async fn enroll(&self, user_id: i64) -> Result<TotpEnrollment> {
+        if self.is_enabled(user_id).await? {
+            return Err(AppError::BadRequest(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        let user = self.user_repository.get_user_by_id(user_id).await?;
+        let secret = match Secret::generate_secret().to_encoded() {
+            Secret::Encoded(secret) => secret,
+            Secret::Raw(_) => unreachable!("to_encoded returns an encoded secret"),
+        };
+        let totp = Self::totp(&secret, user.email)?;
+
+        self.two_factor_repository
+            .save_pending(user_id, &secret)
+            .await?;
+
+        Ok(TotpEnrollment {
+            otpauth_uri: totp.get_url(),
+            secret,
+        })
+    }
+
+    async fn confirm(&self, user_id: i64, code: &str) -> Result<RecoveryCodes> {
+        let totp = self
+            .two_factor_repository
+            .find_by_user_id(user_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest("Two-factor authentication is not set up".to_string())
+            })?;
+
+        if totp.confirmed_at.is_some() {
+            return Err(AppError::BadRequest(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        let step = Self::matching_step(&Self::totp(&totp.secret, user_id.to_string())?, code)
+            .ok_or_else(|| AppError::BadRequest("Invalid two-factor code".to_string()))?;
+
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| Self::generate_recovery_code())
+            .collect();
+        let hashes: Vec<String> = recovery_codes
+            .iter()
+            .map(|code| Self::hash_recovery_code(code))
+            .collect();
+
+        self.two_factor_repository
+            .confirm(user_id, step, &hashes)
+            .await?;
+
+        Ok(RecoveryCodes { recovery_codes })
+    }
+
+    async fn disable(&self, user_id: i64, code: &str) -> Result<()> {
+        if !self.is_enabled(user_id).await? {
+            return Err(AppError::BadRequest(
+                "Two-factor authentication is not enabled".to_string(),
+            ));
+        }
+
+        self.verify(user_id, code)
+            .await
+            .map_err(|_| AppError::BadRequest("Invalid two-factor code".to_string()))?;
+
+        self.two_factor_repository.delete(user_id).await
+    }
+
+    async fn is_enabled(&self, user_id: i64) -> Result<bool> {
+        let totp = self.two_factor_repository.find_by_user_id(user_id).await?;
+
+        Ok(totp.is_some_and(|totp| totp.confirmed_at.is_some()))
+    }
+
+    async fn verify(&self, user_id: i64, code: &str) -> Result<()> {
+        let invalid = || AppError::AuthenticationError("Invalid two-factor code".to_string());
+
+        let totp = self
+            .two_factor_repository
+            .find_by_user_id(user_id)
+            .await?
+            .filter(|totp| totp.confirmed_at.is_some())
+            .ok_or_else(invalid)?;
+
+        let is_totp_code = code.len() == TOTP_DIGITS && code.chars().all(|c| c.is_ascii_digit());
+        let valid = if is_totp_code {
+            self.verify_totp(user_id, &totp.secret, code).await?
+        } else {
+            self.two_factor_repository
+                .use_recovery_code(user_id, &Self::hash_recovery_code(code))
+                .await?
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        models::{
+            two_factor::UserTotp,
+            users::{User, UserRole},
+        },
+        repositories::{
+            two_factor_repository::MockTwoFactorRepositoryTrait,
+            user_repository::MockUserRepositoryTrait,
+        },
+    };
+
+    const SECRET: &str = "KRSXG5CTMVRXEZLUKN2XAZLSKNSWG4TFOQ";
+
+    fn user_totp(confirmed: bool) -> UserTotp {
+        UserTotp {
+            user_id: 1,
+            secret: SECRET.to_string(),
+            confirmed_at: confirmed.then(Utc::now),
+            last_used_step: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn current_code() -> String {
+        TwoFactorService::totp(SECRET, "test".to_string())
+            .unwrap()
+            .generate(Utc::now().timestamp() as u64)
+    }
+
+    fn service(
+        user_repository: MockUserRepositoryTrait,
+        two_factor_repository: MockTwoFactorRepositoryTrait,
+    ) -> TwoFactorService {
+        TwoFactorService::new(Arc::new(user_repository), Arc::new(two_factor_repository))
+    }
+
+    #[tokio::test]
+    async fn test_enroll_returns_otpauth_uri() {
+        let mut mock_users = MockUserRepositoryTrait::new();
+        let mut mock_two_factor = MockTwoFactorRepositoryTrait::new();
+
+        mock_two_factor
+            .expect_find_by_user_id()
+            .returning(|_| Ok(None));
+        mock_two_factor
+            .expect_save_pending()
+            .withf(|user_id, secret| *user_id == 1 && secret.len() == 32)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_users.expect_get_user_by_id().returning(|id| {
+            Ok(User {
+                id,
+                first_name: "John".to_string(),
+                last_name: "Doe".to_string(),
+                phone: "1234567890".to_string(),
+                email: "john@example.com".to_string(),
+                role: UserRole::User,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                last_login_at: None,
+                email_verified_at: None,
+                disabled_at: None,
+            })
+        });
+
+        let enrollment = service(mock_users, mock_two_factor)
+            .enroll(1)
+            .await
+            .unwrap();
+
+        assert!(enrollment
+            .otpauth_uri
+            .starts_with("otpauth://totp/Smart%20Home:john%40example.com?"));
+        assert!(enrollment
+            .otpauth_uri
+            .contains(&format!("secret={}", enrollment.secret)));
+    }
+
+    #[tokio::test]
+    async fn test_enroll_when_already_enabled() {
+        let mut mock_two_factor = MockTwoFactorRepositoryTrait::new();
+        mock_two_factor
+            .expect_find_by_user_id()
+            .returning(|_| Ok(Some(user_totp(true))));
+
+        let result = service(MockUserRepositoryTrait::new(), mock_two_factor)
+            .enroll(1)
+            .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_returns_recovery_codes() {
+        let mut mock_two_factor = MockTwoFactorRepositoryTrait::new();
+        mock_two_factor
+            .expect_find_by_user_id()
+            .returning(|_| Ok(Some(user_totp(false))));
+        mock_two_factor
+            .expect_confirm()
+            .withf(|user_id, _, hashes| *user_id == 1 && hashes.len() == RECOVERY_CODE_COUNT)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let codes = service(MockUserRepositoryTrait::new(), mock_two_factor)
+            .confirm(1, &current_code())
+            .await
+            .unwrap();
+
+        assert_eq!(codes.recovery_codes.len(), RECOVERY_CODE_COUNT);
+        assert!(codes.recovery_codes.iter().all(|c| c.len() == 11));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_with_wrong_code() {
+        let mut mock_two_factor = MockTwoFactorRepositoryTrait::new();
+        mock_two_factor
+            .expect_find_by_user_id()
+            .returning(|_| Ok(Some(user_totp(false))));
+        mock_two_factor.expect_confirm().never();
+
+        let code = if current_code() == "000000" {
+            "111111"
+        } else {
+            "000000"
+        };
+        let result = service(MockUserRepositoryTrait::new(), mock_two_factor)
+            .confirm(1, code)
+            .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_reused_totp_code() {
+        let mut mock_two_factor = MockTwoFactorRepositoryTrait::new();
+        mock_two_factor
+            .expect_find_by_user_id()
+            .returning(|_| Ok(Some(user_totp(true))));
+        mock_two_factor
+            .expect_use_step()
+            .times(1)
+            .returning(|_, _| Ok(false));
+
+        let result = service(MockUserRepositoryTrait::new(), mock_two_factor)
+            .verify(1, &current_code())
+            .await;
+
+        assert!(matches!(result, Err(AppError::AuthenticationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_recovery_code() {
+        let mut mock_two_factor = MockTwoFactorRepositoryTrait::new();
+        mock_two_factor
+            .expect_find_by_user_id()
+            .returning(|_| Ok(Some(user_totp(true))));
+        mock_two_factor
+            .expect_use_recovery_code()
+            .with(
+                eq(1i64),
+                eq(TwoFactorService::hash_recovery_code("abcde12345")),
+            )
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        let result = service(MockUserRepositoryTrait::new(), mock_two_factor)
+            .verify(1, "ABCDE-12345")
+            .await;
+
+        assert!(result.is_ok());
+    }
+}
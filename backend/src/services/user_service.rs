@@ -1,15 +1,21 @@
-use std::sync::Arc;
+use std::{borrow::Cow, sync::Arc};
 
 use async_trait::async_trait;
 use mockall::automock;
+use validator::{ValidationError, ValidationErrors};
 
-use crate::{errors::Result, models::users::User, repositories::UserRepositoryTrait};
+use crate::{
+    errors::{AppError, Result},
+    models::users::{UpdateProfileRequest, User},
+    repositories::UserRepositoryTrait,
+};
 
 #[automock]
 #[async_trait]
 pub trait UserServiceTrait {
     async fn get_user_by_id(&self, id: i64) -> Result<User>;
     async fn get_user_profile(&self, id: i64) -> Result<User>;
+    async fn update_profile(&self, id: i64, profile: UpdateProfileRequest) -> Result<User>;
     async fn delete_user(&self, id: i64) -> Result<()>;
     async fn user_exists(&self, email: &str) -> Result<bool>;
 }
@@ -39,6 +45,24 @@ impl UserServiceTrait for UserService {
         Ok(user_entity)
     }
 
+    async fn update_profile(&self, id: i64, profile: UpdateProfileRequest) -> Result<User> {
+        if let Some(phone) = &profile.phone {
+            let user_with_phone = self.user_repository.find_by_phone(phone).await?;
+
+            if user_with_phone.is_some_and(|user| user.id != id) {
+                let mut errors = ValidationErrors::new();
+                errors.add(
+                    "phone",
+                    ValidationError::new("already_exists")
+                        .with_message(Cow::from("User with this phone already exists")),
+                );
+                return Err(AppError::ValidationError(errors));
+            }
+        }
+
+        self.user_repository.update_profile(id, &profile).await
+    }
+
     async fn delete_user(&self, id: i64) -> Result<()> {
         self.user_repository.delete_user(id).await?;
 
@@ -74,6 +98,8 @@ mod tests {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
         };
 
         mock_repo
@@ -129,6 +155,8 @@ mod tests {
             created_at: now,
             updated_at: now,
             last_login_at: Some(now),
+            email_verified_at: None,
+            disabled_at: None,
         };
 
         mock_repo
@@ -151,6 +179,91 @@ mod tests {
         assert!(profile.last_login_at.is_some());
     }
 
+    #[tokio::test]
+    async fn test_update_profile_with_phone_of_another_user() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+
+        mock_repo
+            .expect_find_by_phone()
+            .with(mockall::predicate::eq("0987654321"))
+            .times(1)
+            .returning(|_| {
+                Ok(Some(User {
+                    id: 2,
+                    first_name: "Jane".to_string(),
+                    last_name: "Doe".to_string(),
+                    phone: "0987654321".to_string(),
+                    email: "jane@example.com".to_string(),
+                    role: UserRole::User,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    last_login_at: None,
+                    email_verified_at: None,
+                    disabled_at: None,
+                }))
+            });
+        mock_repo.expect_update_profile().never();
+
+        let user_service = UserService::new(Arc::new(mock_repo));
+        let result = user_service
+            .update_profile(
+                1,
+                UpdateProfileRequest {
+                    first_name: None,
+                    last_name: None,
+                    phone: Some("0987654321".to_string()),
+                },
+            )
+            .await;
+
+        match result {
+            Err(AppError::ValidationError(errors)) => {
+                assert!(errors.field_errors().contains_key("phone"))
+            }
+            _ => panic!("Expected ValidationError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_profile_keeping_own_phone() {
+        let mut mock_repo = MockUserRepositoryTrait::new();
+        let user = User {
+            id: 1,
+            first_name: "Johnny".to_string(),
+            last_name: "Doe".to_string(),
+            phone: "1234567890".to_string(),
+            email: "test@example.com".to_string(),
+            role: UserRole::User,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            email_verified_at: None,
+            disabled_at: None,
+        };
+        let update = UpdateProfileRequest {
+            first_name: Some("Johnny".to_string()),
+            last_name: None,
+            phone: Some("1234567890".to_string()),
+        };
+
+        let existing = user.clone();
+        mock_repo
+            .expect_find_by_phone()
+            .times(1)
+            .returning(move |_| Ok(Some(existing.clone())));
+        let expected_update = update.clone();
+        mock_repo
+            .expect_update_profile()
+            .withf(move |id, profile| *id == 1 && *profile == expected_update)
+            .times(1)
+            .returning(move |_, _| Ok(user.clone()));
+
+        let user_service = UserService::new(Arc::new(mock_repo));
+        let profile = user_service.update_profile(1, update).await.unwrap();
+
+        assert_eq!(profile.first_name, "Johnny");
+    }
+
     #[tokio::test]
     async fn test_delete_user_success() {
         let mut mock_repo = MockUserRepositoryTrait::new();
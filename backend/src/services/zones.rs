@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{
+    errors::{AppError, Result},
+    models::zones::{NewZone, UpdateZone, Zone},
+    repositories::zones_repository::ZonesRepositoryTrait,
+    services::access_control_service::{AccessControlServiceTrait, Action},
+};
+
+/// Zones group the rooms of a house, e.g. by floor.
+#[automock]
+#[async_trait]
+pub trait ZonesServiceTrait {
+    async fn get_house_zones(&self, user_id: i64, house_id: i64) -> Result<Vec<Zone>>;
+    /// The zone, if it is in the house and the user may view the house.
+    async fn get_zone(&self, user_id: i64, house_id: i64, zone_id: i64) -> Result<Zone>;
+    async fn create_zone(&self, user_id: i64, house_id: i64, zone: NewZone) -> Result<Zone>;
+    async fn update_zone(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+        zone: UpdateZone,
+    ) -> Result<Zone>;
+    /// Deletes the zone. Its rooms stay in the house without a zone.
+    async fn delete_zone(&self, user_id: i64, house_id: i64, zone_id: i64) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct ZonesService {
+    zones_repository: Arc<dyn ZonesRepositoryTrait + Send + Sync>,
+    access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+}
+
+impl ZonesService {
+    pub fn new(
+        zones_repository: Arc<dyn ZonesRepositoryTrait + Send + Sync>,
+        access_control_service: Arc<dyn AccessControlServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self {
+            zones_repository,
+            access_control_service,
+        }
+    }
+
+    /// Checks the action and that the zone is in the house.
+    async fn house_zone(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+        action: Action,
+    ) -> Result<Zone> {
+        self.access_control_service
+            .can(user_id, house_id, action)
+            .await?;
+
+        let zone = self.zones_repository.get_zone(zone_id).await?;
+        if zone.house_id != house_id {
+            return Err(AppError::NotFound("Zone not found".to_string()));
+        }
+
+        Ok(zone)
+    }
+}
+
+#[async_trait]
+impl ZonesServiceTrait for ZonesService {
+    async fn get_house_zones(&self, user_id: i64, house_id: i64) -> Result<Vec<Zone>> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ViewHouse)
+            .await?;
+
+        self.zones_repository.get_house_zones(house_id).await
+    }
+
+    async fn get_zone(&self, user_id: i64, house_id: i64, zone_id: i64) -> Result<Zone> {
+        self.house_zone(user_id, house_id, zone_id, Action::ViewHouse)
+            .await
+    }
+
+    async fn create_zone(&self, user_id: i64, house_id: i64, zone: NewZone) -> Result<Zone> {
+        self.access_control_service
+            .can(user_id, house_id, Action::ManageRooms)
+            .await?;
+
+        self.zones_repository.create_zone(house_id, zone).await
+    }
+
+    async fn update_zone(
+        &self,
+        user_id: i64,
+        house_id: i64,
+        zone_id: i64,
+        zone: UpdateZone,
+    ) -> Result<Zone> {
+        self.house_zone(user_id, house_id, zone_id, Action::ManageRooms)
+            .await?;
+
+        self.zones_repository.update_zone(zone_id, zone).await
+    }
+
+    async fn delete_zone(&self, user_id: i64, house_id: i64, zone_id: i64) -> Result<()> {
+        self.house_zone(user_id, house_id, zone_id, Action::ManageRooms)
+            .await?;
+
+        self.zones_repository.delete_zone(zone_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        repositories::zones_repository::MockZonesRepositoryTrait,
+        services::access_control_service::MockAccessControlServiceTrait,
+    };
+    use chrono::Utc;
+    use mockall::predicate::eq;
+
+    fn zone(id: i64, house_id: i64) -> Zone {
+        Zone {
+            id,
+            house_id,
+            name: "Ground floor".to_string(),
+            zone_type: "floor".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn access_control_allowing(action: Action) -> MockAccessControlServiceTrait {
+        let mut access_control = MockAccessControlServiceTrait::new();
+        access_control
+            .expect_can()
+            .with(eq(1i64), eq(10i64), eq(action))
+            .returning(|_, _, _| Ok(()));
+        access_control
+    }
+
+    #[tokio::test]
+    async fn test_update_zone_of_another_house() {
+        let mut zones_repository = MockZonesRepositoryTrait::new();
+        zones_repository
+            .expect_get_zone()
+            .with(eq(3i64))
+            .returning(|id| Ok(zone(id, 20)));
+        zones_repository.expect_update_zone().never();
+
+        let service = ZonesService::new(
+            Arc::new(zones_repository),
+            Arc::new(access_control_allowing(Action::ManageRooms)),
+        );
+        let update = UpdateZone {
+            name: Some("Attic".to_string()),
+            zone_type: None,
+        };
+
+        assert!(matches!(
+            service.update_zone(1, 10, 3, update).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_zone_requires_managing_rooms() {
+        let mut zones_repository = MockZonesRepositoryTrait::new();
+        zones_repository.expect_delete_zone().never();
+        let mut access_control = MockAccessControlServiceTrait::new();
+        access_control
+            .expect_can()
+            .with(eq(1i64), eq(10i64), eq(Action::ManageRooms))
+            .returning(|_, _, _| Err(AppError::AuthorizationError("Forbidden".to_string())));
+
+        let service = ZonesService::new(Arc::new(zones_repository), Arc::new(access_control));
+
+        assert!(matches!(
+            service.delete_zone(1, 10, 3).await,
+            Err(AppError::AuthorizationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_house_zones() {
+        let mut zones_repository = MockZonesRepositoryTrait::new();
+        zones_repository
+            .expect_get_house_zones()
+            .with(eq(10i64))
+            .returning(|house_id| Ok(vec![zone(3, house_id)]));
+
+        let service = ZonesService::new(
+            Arc::new(zones_repository),
+            Arc::new(access_control_allowing(Action::ViewHouse)),
+        );
+
+        let zones = service.get_house_zones(1, 10).await.unwrap();
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones[0].id, 3);
+    }
+}
@@ -0,0 +1,234 @@
+#[cfg(test)]
+use crate::{
+    tests::{create_proxied_test_server, create_test_config, setup_test_database},
+    AppState,
+};
+use axum::{http::StatusCode, Router};
+use axum_test::TestServer;
+use rand::Rng;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::create_app;
+use crate::models::{
+    admin::SystemStats,
+    auth_events::{AuthEvent, AuthEventType},
+    common::ListResponse,
+    users::User,
+};
+
+async fn create_test_app() -> Result<(Router, PgPool), Box<dyn std::error::Error>> {
+    let pool = setup_test_database().await?;
+    let config = create_test_config();
+
+    let app_state = AppState::new(crate::db::Database::new(pool.clone()), config);
+
+    let app = create_app(app_state);
+
+    Ok((app, pool))
+}
+
+// Helper to register and login a user
+async fn register_and_login_user(server: &TestServer) -> (String, String, i64) {
+    let unique_id = Uuid::new_v4();
+    let email = format!("admin_test_{}@example.com", unique_id);
+    let phone = format!("111333{:04}", rand::rng().random_range(0..10000));
+
+    let register_payload = json!({
+        "first_name": "Test",
+        "last_name": "User",
+        "phone": phone,
+        "email": email,
+        "password": "password123"
+    });
+    let response = server.post("/auth/signup").json(&register_payload).await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let login_payload = json!({
+        "email": email,
+        "password": "password123"
+    });
+    let response = server.post("/auth/login").json(&login_payload).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let auth_response: serde_json::Value = response.json();
+    let token = auth_response["token"].as_str().unwrap().to_string();
+    let user_id = auth_response["user"]["id"].as_i64().unwrap();
+    (email, token, user_id)
+}
+
+// Helper to register a user with the admin role
+async fn register_admin(server: &TestServer, pool: &PgPool) -> (String, i64) {
+    let (_, token, user_id) = register_and_login_user(server).await;
+    sqlx::query!("UPDATE users SET role = 'admin' WHERE id = $1", user_id)
+        .execute(pool)
+        .await
+        .expect("Failed to promote test user");
+
+    (token, user_id)
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_admin() {
+    let (app, _pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (_, token, _) = register_and_login_user(&server).await;
+
+    let response = server.get("/admin/stats").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .get("/admin/stats")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_admin_manages_users() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (admin_token, admin_id) = register_admin(&server, &pool).await;
+    let admin_header = format!("Bearer {}", admin_token);
+    let (email, user_token, user_id) = register_and_login_user(&server).await;
+
+    // Search
+    let response = server
+        .get("/admin/users")
+        .add_query_param("search", &email)
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let users: ListResponse<User> = response.json();
+    assert_eq!(users.items.len(), 1);
+    assert_eq!(users.items[0].id, user_id);
+
+    // Stats
+    let response = server
+        .get("/admin/stats")
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let stats: SystemStats = response.json();
+    assert!(stats.users >= 2);
+
+    // Roles
+    let response = server
+        .put(&format!("/admin/users/{}/role", admin_id))
+        .add_header("Authorization", admin_header.clone())
+        .json(&json!({ "role": "user" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .put(&format!("/admin/users/{}/role", user_id))
+        .add_header("Authorization", admin_header.clone())
+        .json(&json!({ "role": "admin" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let user: User = response.json();
+    assert_eq!(user.role, crate::models::users::UserRole::Admin);
+
+    // Disabling signs the user out and blocks logins
+    let response = server
+        .post(&format!("/admin/users/{}/disable", user_id))
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", user_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let login_payload = json!({ "email": email, "password": "password123" });
+    let response = server.post("/auth/login").json(&login_payload).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .post(&format!("/admin/users/{}/enable", user_id))
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server.post("/auth/login").json(&login_payload).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let auth_response: serde_json::Value = response.json();
+    let user_token = auth_response["token"].as_str().unwrap().to_string();
+
+    // Force logout
+    let response = server
+        .post(&format!("/admin/users/{}/logout", user_id))
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", user_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post(&format!("/admin/users/{}/logout", i64::MAX))
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_auth_events_are_recorded() {
+    let (server, pool) = create_proxied_test_server().await;
+
+    let (admin_token, _) = register_admin(&server, &pool).await;
+    let (email, user_token, user_id) = register_and_login_user(&server).await;
+    // Failures of an address outlive test runs, so each run uses its own
+    let client_ip = format!("198.51.100.{}", rand::rng().random_range(0..256));
+
+    let response = server
+        .post("/auth/login")
+        .add_header("X-Forwarded-For", client_ip.clone())
+        .json(&json!({ "email": email, "password": "wrong-password" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // The user sees their own events, newest first
+    let response = server
+        .get("/profile/security-events")
+        .add_header("Authorization", format!("Bearer {}", user_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let events: ListResponse<AuthEvent> = response.json();
+    let types: Vec<AuthEventType> = events.items.iter().map(|event| event.event_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            AuthEventType::LoginFailed,
+            AuthEventType::LoginSucceeded,
+            AuthEventType::Signup
+        ]
+    );
+    assert_eq!(events.items[0].ip.as_deref(), Some(client_ip.as_str()));
+    assert_eq!(events.items[0].details.as_deref(), Some("Invalid password"));
+
+    // Admins search across users
+    let response = server
+        .get("/admin/auth-events")
+        .add_query_param("email", email.to_uppercase())
+        .add_query_param("event_type", "login_failed")
+        .add_header("Authorization", format!("Bearer {}", admin_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let events: ListResponse<AuthEvent> = response.json();
+    assert_eq!(events.items.len(), 1);
+    assert_eq!(events.items[0].user_id, Some(user_id));
+
+    let response = server
+        .get("/admin/auth-events")
+        .add_header("Authorization", format!("Bearer {}", user_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
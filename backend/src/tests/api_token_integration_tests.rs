@@ -1,6 +1,6 @@
 #[cfg(test)]
 use crate::{
-    tests::{create_test_config, setup_test_database},
+    tests::{create_proxied_test_server, create_test_config, setup_test_database},
     AppState,
 };
 use axum::{http::StatusCode, Router};
@@ -64,7 +64,8 @@ async fn test_create_and_get_api_tokens() {
 
     // Create a token
     let create_token_payload = json!({
-        "name": "My Test Token"
+        "name": "My Test Token",
+        "scopes": ["houses:read"]
     });
 
     let response = server
@@ -90,3 +91,262 @@ async fn test_create_and_get_api_tokens() {
     assert_eq!(tokens.items.len(), 1);
     assert_eq!(tokens.items[0].name, "My Test Token");
 }
+
+#[tokio::test]
+async fn test_api_token_authenticates_requests() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, user_id) = register_and_login_user(&server, &pool).await;
+
+    let response = server
+        .post("/tokens")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Gateway Token", "scopes": ["houses:read"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let new_token: NewApiToken = response.json();
+
+    // The API token can be used in place of a JWT
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", new_token.token))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let profile: serde_json::Value = response.json();
+    assert_eq!(profile["id"].as_i64().unwrap(), user_id);
+
+    // An unknown API token is rejected
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", "Bearer sh_unknown")
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_api_token_usage_and_revocation() {
+    let (server, pool) = create_proxied_test_server().await;
+
+    let (token, _user_id) = register_and_login_user(&server, &pool).await;
+
+    let response = server
+        .post("/tokens")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Gateway Token", "scopes": ["houses:read"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let new_token: NewApiToken = response.json();
+
+    // Using the token records when and from where it was used
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", new_token.token))
+        .add_header("X-Forwarded-For", "203.0.113.7, 10.0.0.1")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get("/tokens")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let tokens: ListResponse<PublicApiToken> = response.json();
+    assert!(tokens.items[0].last_used_at.is_some());
+    assert_eq!(tokens.items[0].last_used_ip.as_deref(), Some("203.0.113.7"));
+
+    // A revoked token no longer authenticates
+    let response = server
+        .delete(&format!("/tokens/{}", new_token.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", new_token.token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .delete(&format!("/tokens/{}", new_token.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_expired_api_token_is_rejected() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, _user_id) = register_and_login_user(&server, &pool).await;
+
+    let response = server
+        .post("/tokens")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "name": "Short Lived Token",
+            "scopes": ["houses:read"],
+            "expires_at": chrono::Utc::now() + chrono::Duration::hours(1)
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let new_token: NewApiToken = response.json();
+    assert!(new_token.expires_at.is_some());
+
+    sqlx::query!(
+        "UPDATE api_tokens SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1",
+        new_token.id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", new_token.token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+// Helper to create a house with one room and one device, returning their ids
+async fn create_house_with_device(server: &TestServer, token: &str) -> (i64, i64) {
+    let name = format!("Scoped House {}", Uuid::new_v4());
+    let response = server
+        .post("/houses")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": name, "address": name, "type": "apartment" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let house_id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+    let response = server
+        .post(&format!("/houses/{}/rooms", house_id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Hall", "room_type": "Living Room" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let room_id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Sensor", "device_type": "light", "room_id": room_id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let device_id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+    (house_id, device_id)
+}
+
+#[tokio::test]
+async fn test_scoped_api_token_is_limited_to_house_and_scopes() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, _user_id) = register_and_login_user(&server, &pool).await;
+    let (house_id, device_id) = create_house_with_device(&server, &token).await;
+    let (other_house_id, other_device_id) = create_house_with_device(&server, &token).await;
+
+    let response = server
+        .post("/tokens")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "name": "Gateway Token",
+            "scopes": ["metrics:write"],
+            "house_id": house_id
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let gateway: NewApiToken = response.json();
+    assert_eq!(gateway.scopes, vec!["metrics:write".to_string()]);
+    assert_eq!(gateway.house_id, Some(house_id));
+    let gateway_auth = format!("Bearer {}", gateway.token);
+
+    let metric = |device_id: i64| {
+        json!({
+            "device_id": device_id,
+            "metric_type": "temperature",
+            "metric_value": 21.5,
+            "unit": "C"
+        })
+    };
+
+    // Metrics can be written for devices of the restricted house only
+    let response = server
+        .post("/metrics")
+        .add_header("Authorization", gateway_auth.clone())
+        .json(&metric(device_id))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let response = server
+        .post("/metrics")
+        .add_header("Authorization", gateway_auth.clone())
+        .json(&metric(other_device_id))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Heartbeats take the same scope as metrics
+    let response = server
+        .post(&format!("/devices/{}/heartbeat", device_id))
+        .add_header("Authorization", gateway_auth.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .post(&format!("/devices/{}/heartbeat", other_device_id))
+        .add_header("Authorization", gateway_auth.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Anything outside metrics:write is rejected
+    let response = server
+        .get(&format!("/houses/{}/devices", house_id))
+        .add_header("Authorization", gateway_auth.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .delete(&format!("/houses/{}", house_id))
+        .add_header("Authorization", gateway_auth.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // API tokens cannot mint new tokens
+    let response = server
+        .post("/tokens")
+        .add_header("Authorization", gateway_auth.clone())
+        .json(&json!({ "name": "Escalated Token", "scopes": ["houses:admin"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Even houses:admin does not extend past the restricted house
+    let response = server
+        .post("/tokens")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "name": "House Admin Token",
+            "scopes": ["houses:admin"],
+            "house_id": house_id
+        }))
+        .await;
+    let house_admin: NewApiToken = response.json();
+
+    let response = server
+        .get(&format!("/houses/{}/devices", other_house_id))
+        .add_header("Authorization", format!("Bearer {}", house_admin.token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .get("/houses")
+        .add_header("Authorization", format!("Bearer {}", house_admin.token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let houses: serde_json::Value = response.json();
+    assert_eq!(houses["items"].as_array().unwrap().len(), 1);
+    assert_eq!(houses["items"][0]["id"].as_i64(), Some(house_id));
+}
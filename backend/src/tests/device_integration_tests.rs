@@ -11,7 +11,10 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::models::{common::ListResponse, houses, rooms};
-use crate::{create_app, models::devices::Device};
+use crate::{
+    create_app,
+    models::devices::{Device, DeviceStatus},
+};
 
 async fn create_test_app() -> Result<(Router, PgPool), Box<dyn std::error::Error>> {
     let pool = setup_test_database().await?;
@@ -28,7 +31,8 @@ async fn create_test_app() -> Result<(Router, PgPool), Box<dyn std::error::Error
 async fn create_house(server: &TestServer, token: &str, name: &str) -> houses::House {
     let create_house_payload = json!({
         "name": name,
-        "address": name
+        "address": name,
+        "type": "apartment"
     });
     let response = server
         .post("/houses")
@@ -93,7 +97,7 @@ async fn test_create_device() {
 
     let create_device_payload = json!({
         "name": "Living Room Light",
-        "device_type": "Light",
+        "device_type": "light",
         "room_id": room.id,
     });
 
@@ -119,7 +123,7 @@ async fn test_create_device() {
 
     let device: Device = response.json();
     assert_eq!(device.name, "Living Room Light");
-    assert_eq!(device.device_type, "Light");
+    assert_eq!(device.device_type, "light");
     assert_eq!(device.room_id, room.id);
 }
 
@@ -134,7 +138,7 @@ async fn test_get_device_by_id() {
 
     let create_device_payload = json!({
         "name": "Bedroom Thermostat",
-        "device_type": "Thermostat",
+        "device_type": "thermostat",
         "room_id": room.id
     });
 
@@ -152,9 +156,60 @@ async fn test_get_device_by_id() {
 
     assert_eq!(response.status_code(), StatusCode::OK);
 
+    let device: serde_json::Value = response.json();
+    assert_eq!(device["id"], created_device.id);
+    assert_eq!(device["name"], "Bedroom Thermostat");
+    let capabilities = device["capabilities"].as_array().unwrap();
+    assert_eq!(capabilities.len(), 2);
+    assert_eq!(capabilities[0]["kind"], "setpoint");
+    assert_eq!(capabilities[0]["writable"], true);
+    assert_eq!(capabilities[0]["unit"], "°C");
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_device_types() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, _user_id) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &token, "Test House for Device Types").await;
+    let room = create_room(&server, &token, house.id, "Test Room for Device Types").await;
+
+    let response = server
+        .get("/device-types")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let types: serde_json::Value = response.json();
+    let dimmable = types["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["key"] == "dimmable_light")
+        .unwrap();
+    assert_eq!(dimmable["capabilities"][1]["kind"], "brightness");
+    assert_eq!(dimmable["capabilities"][1]["max"], 100.0);
+
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Lamp", "device_type": "Light", "room_id": room.id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Lamp", "device_type": "light", "room_id": room.id }))
+        .await;
     let device: Device = response.json();
-    assert_eq!(device.id, created_device.id);
-    assert_eq!(device.name, "Bedroom Thermostat");
+
+    let response = server
+        .patch(&format!("/devices/{}", device.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "device_type": "lava_lamp" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 }
 
 #[tokio::test] // Requires test database setup
@@ -168,7 +223,7 @@ async fn test_update_device() {
 
     let create_device_payload = json!({
         "name": "Old Device Name",
-        "device_type": "SmartPlug",
+        "device_type": "smart_plug",
         "room_id": room.id
     });
 
@@ -207,7 +262,7 @@ async fn test_delete_device() {
 
     let create_device_payload = json!({
         "name": "Device to Delete",
-        "device_type": "Sensor",
+        "device_type": "temperature_sensor",
         "room_id": room.id
     });
 
@@ -247,7 +302,7 @@ async fn test_get_devices_by_room_id() {
     // Create devices in Room 1
     let device1_payload = json!({
         "name": "Device A",
-        "device_type": "Light",
+        "device_type": "light",
         "room_id": room1.id
     });
     server
@@ -258,7 +313,7 @@ async fn test_get_devices_by_room_id() {
 
     let device2_payload = json!({
         "name": "Device B",
-        "device_type": "SmartPlug",
+        "device_type": "smart_plug",
         "room_id": room1.id
     });
     server
@@ -270,7 +325,7 @@ async fn test_get_devices_by_room_id() {
     // Create a device in Room 2
     let device3_payload = json!({
         "name": "Device C",
-        "device_type": "Sensor",
+        "device_type": "temperature_sensor",
         "room_id": room2.id
     });
     server
@@ -310,3 +365,353 @@ async fn test_get_devices_by_room_id() {
 
     assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
 }
+
+#[tokio::test]
+async fn test_move_device() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, user_id) = register_and_login_user(&server, &pool).await;
+    let (stranger_token, _) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &token, &Uuid::new_v4().to_string()).await;
+    let other_house = create_house(&server, &token, &Uuid::new_v4().to_string()).await;
+    let stranger_house = create_house(&server, &stranger_token, &Uuid::new_v4().to_string()).await;
+    let kitchen = create_room(&server, &token, house.id, "Kitchen").await;
+    let garage = create_room(&server, &token, other_house.id, "Garage").await;
+    let stranger_room = create_room(&server, &stranger_token, stranger_house.id, "Hall").await;
+
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Lamp", "device_type": "light", "room_id": kitchen.id }))
+        .await;
+    let device: Device = response.json();
+
+    // Editing a device doesn't move it, and a room in the edit is an error
+    // rather than silently ignored
+    let response = server
+        .patch(&format!("/devices/{}", device.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Desk Lamp", "room_id": stranger_room.id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("room_id"));
+
+    let response = server
+        .get(&format!("/devices/{}", device.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let unchanged: Device = response.json();
+    assert_eq!(unchanged.name, "Lamp");
+    assert_eq!(unchanged.room_id, kitchen.id);
+
+    // Not into a house the user doesn't belong to
+    let response = server
+        .post(&format!("/devices/{}/move", device.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "room_id": stranger_room.id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Nor out of one
+    let response = server
+        .post(&format!("/devices/{}/move", device.id))
+        .add_header("Authorization", format!("Bearer {}", stranger_token))
+        .json(&json!({ "room_id": stranger_room.id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post(&format!("/devices/{}/move", device.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "room_id": i64::MAX }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server
+        .post(&format!("/devices/{}/move", device.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "room_id": garage.id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let moved: Device = response.json();
+    assert_eq!(moved.room_id, garage.id);
+
+    let response = server
+        .get(&format!("/devices/{}/locations", device.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let history: serde_json::Value = response.json();
+    let history = history["items"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["room_id"], kitchen.id);
+    assert!(history[0]["moved_by"].is_null());
+    assert_eq!(history[0]["removed_at"], history[1]["placed_at"]);
+    assert_eq!(history[1]["room_id"], garage.id);
+    assert_eq!(history[1]["moved_by"], user_id);
+    assert!(history[1]["removed_at"].is_null());
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_device_state() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, _user_id) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &token, "Test House for Device State").await;
+    let room = create_room(&server, &token, house.id, "Test Room for Device State").await;
+
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Lamp", "device_type": "dimmable_light", "room_id": room.id }))
+        .await;
+    let device: Device = response.json();
+    let state_path = format!("/devices/{}/state", device.id);
+
+    let response = server
+        .get(&state_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let state: serde_json::Value = response.json();
+    assert_eq!(state["version"], 0);
+    assert_eq!(state["desired"], json!({}));
+
+    let response = server
+        .patch(&state_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "desired": { "on_off": 1, "brightness": 40 }, "version": 0 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let state: serde_json::Value = response.json();
+    assert_eq!(state["version"], 1);
+
+    // Out of range and stale writes are rejected
+    let response = server
+        .patch(&state_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "desired": { "brightness": 140 } }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let response = server
+        .patch(&state_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "desired": { "brightness": 60 }, "version": 0 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+    let response = server
+        .patch(&state_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "reported": { "on_off": 1 } }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get(&format!("{}/delta", state_path))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let delta: serde_json::Value = response.json();
+    assert_eq!(delta["version"], 2);
+    assert_eq!(delta["delta"], json!({ "brightness": 40 }));
+
+    // Removing a desired value
+    let response = server
+        .patch(&state_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "desired": { "brightness": null } }))
+        .await;
+    let state: serde_json::Value = response.json();
+    assert_eq!(state["desired"], json!({ "on_off": 1 }));
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_device_commands() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, _user_id) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &token, "Test House for Device Commands").await;
+    let room = create_room(&server, &token, house.id, "Test Room for Device Commands").await;
+
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "name": "Thermostat", "device_type": "thermostat", "room_id": room.id }))
+        .await;
+    let device: Device = response.json();
+    let commands_path = format!("/devices/{}/commands", device.id);
+
+    for invalid in [
+        json!({ "command": "set", "capability": "setpoint", "value": 50 }),
+        json!({ "command": "set", "capability": "on_off", "value": 1 }),
+        json!({ "command": "set", "capability": "setpoint" }),
+        json!({ "command": "reboot", "value": 1 }),
+    ] {
+        let response = server
+            .post(&commands_path)
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&invalid)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    let response = server
+        .post(&commands_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "command": "set", "capability": "setpoint", "value": 20 }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let set: serde_json::Value = response.json();
+    assert_eq!(set["status"], "pending");
+    let response = server
+        .post(&commands_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "command": "reboot" }))
+        .await;
+    let reboot: serde_json::Value = response.json();
+
+    let response = server
+        .post(&format!("/houses/{}/commands/fetch", house.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let fetched: serde_json::Value = response.json();
+    assert_eq!(fetched["items"][0]["id"], set["id"]);
+    assert_eq!(fetched["items"][0]["status"], "delivered");
+    assert_eq!(fetched["items"][1]["id"], reboot["id"]);
+
+    let response = server
+        .post(&format!("{}/fetch", commands_path))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let fetched: serde_json::Value = response.json();
+    assert_eq!(fetched["items"], json!([]));
+
+    let ack_path = format!("{}/{}/ack", commands_path, set["id"]);
+    let response = server
+        .post(&ack_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let response = server
+        .post(&ack_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+    let response = server
+        .post(&format!("{}/{}/fail", commands_path, reboot["id"]))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "error": "Busy" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .post(&commands_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "command": "reboot" }))
+        .await;
+    let cancelled: serde_json::Value = response.json();
+    let response = server
+        .post(&format!("{}/{}/expire", commands_path, cancelled["id"]))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get(&commands_path)
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let commands: serde_json::Value = response.json();
+    let statuses: Vec<&str> = commands["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|command| command["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, ["expired", "failed", "acknowledged"]);
+
+    let response = server
+        .get(&format!("{}?status=failed", commands_path))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let commands: serde_json::Value = response.json();
+    assert_eq!(commands["items"][0]["error"], "Busy");
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_device_connectivity() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, _user_id) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &token, "Test House for Connectivity").await;
+    let room = create_room(&server, &token, house.id, "Test Room for Connectivity").await;
+
+    let mut devices = Vec::new();
+    for name in ["Lamp", "Plug", "Sensor"] {
+        let response = server
+            .post("/devices")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({ "name": name, "device_type": "smart_plug", "room_id": room.id }))
+            .await;
+        let device: Device = response.json();
+        assert_eq!(device.status, DeviceStatus::Offline);
+        assert!(device.last_seen_at.is_none());
+        devices.push(device);
+    }
+
+    let response = server
+        .post(&format!("/devices/{}/heartbeat", devices[0].id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let device: Device = response.json();
+    assert_eq!(device.status, DeviceStatus::Online);
+    assert!(device.last_seen_at.is_some());
+
+    // Reported metrics count as heartbeats
+    let response = server
+        .post("/metrics")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "device_id": devices[1].id,
+            "metric_type": "power",
+            "metric_value": 42.0,
+            "unit": "W",
+            "measured_at": chrono::Utc::now()
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let response = server
+        .get(&format!("/houses/{}/devices?status=online", house.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let online: ListResponse<Device> = response.json();
+    assert_eq!(online.items.len(), 2);
+
+    let response = server
+        .get(&format!(
+            "/houses/{}/rooms/{}/devices?status=offline",
+            house.id, room.id
+        ))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let offline: ListResponse<Device> = response.json();
+    assert_eq!(offline.items.len(), 1);
+    assert_eq!(offline.items[0].id, devices[2].id);
+
+    let response = server
+        .get(&format!("/devices/{}/connectivity", devices[1].id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let events: serde_json::Value = response.json();
+    assert_eq!(events["items"][0]["status"], "online");
+}
@@ -28,7 +28,8 @@ async fn create_test_app() -> Result<(Router, PgPool), Box<dyn std::error::Error
 async fn create_house(server: &TestServer, token: &str, name: &str) -> houses::House {
     let create_house_payload = json!({
         "name": name,
-        "address": name
+        "address": name,
+        "type": "apartment"
     });
     let response = server
         .post("/houses")
@@ -63,7 +64,7 @@ async fn create_device(
 ) -> devices::Device {
     let create_device_payload = json!({
         "name": name,
-        "device_type": "Light",
+        "device_type": "light",
         "room_id": room_id,
     });
 
@@ -0,0 +1,596 @@
+#[cfg(test)]
+use crate::{
+    tests::{create_test_config, setup_test_database},
+    AppState,
+};
+use axum::{http::StatusCode, Router};
+use axum_test::TestServer;
+use chrono::{Duration, Utc};
+use rand::Rng;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::create_app;
+use crate::models::{
+    common::ListResponse,
+    device_metrics::DeviceMetric,
+    devices::Device,
+    house_invitations::{CreatedHouseInvitation, HouseInvitation, ReceivedHouseInvitation},
+    house_transfers::{HouseTransfer, ReceivedHouseTransfer},
+    houses, rooms,
+    user_houses::{HouseMember, HouseRole},
+};
+use crate::routes::house_members::HouseMembersRouterState;
+
+async fn create_test_app() -> Result<(Router, PgPool), Box<dyn std::error::Error>> {
+    let pool = setup_test_database().await?;
+    let config = create_test_config();
+
+    let app_state = AppState::new(crate::db::Database::new(pool.clone()), config);
+
+    let app = create_app(app_state);
+
+    Ok((app, pool))
+}
+
+// Helper to register and login a user
+async fn register_and_login_user(server: &TestServer) -> (String, String, i64) {
+    let unique_id = Uuid::new_v4();
+    let email = format!("members_test_{}@example.com", unique_id);
+    let phone = format!("111444{:04}", rand::rng().random_range(0..10000));
+
+    let register_payload = json!({
+        "first_name": "Test",
+        "last_name": "User",
+        "phone": phone,
+        "email": email,
+        "password": "password123"
+    });
+    let response = server.post("/auth/signup").json(&register_payload).await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let login_payload = json!({
+        "email": email,
+        "password": "password123"
+    });
+    let response = server.post("/auth/login").json(&login_payload).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let auth_response: serde_json::Value = response.json();
+    let token = auth_response["token"].as_str().unwrap().to_string();
+    let user_id = auth_response["user"]["id"].as_i64().unwrap();
+    (email, token, user_id)
+}
+
+// Helper to create a house
+async fn create_house(server: &TestServer, token: &str) -> houses::House {
+    let response = server
+        .post("/houses")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "name": "Shared House",
+            "address": Uuid::new_v4().to_string(),
+            "type": "house"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    response.json()
+}
+
+#[tokio::test]
+async fn test_invite_accept_and_remove_members() {
+    let (app, _pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (_, owner_token, owner_id) = register_and_login_user(&server).await;
+    let (_, guest_token, guest_id) = register_and_login_user(&server).await;
+    let owner_header = format!("Bearer {}", owner_token);
+    let guest_header = format!("Bearer {}", guest_token);
+    let house = create_house(&server, &owner_token).await;
+
+    // Open code
+    let response = server
+        .post(&format!("/houses/{}/invitations", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({ "role": "guest" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let invitation: CreatedHouseInvitation = response.json();
+    assert_eq!(invitation.invitation.role, HouseRole::Guest);
+    assert!(invitation.link.ends_with(&invitation.code));
+
+    let response = server
+        .post("/invitations/accept")
+        .add_header("Authorization", guest_header.clone())
+        .json(&json!({ "code": invitation.code }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let accepted: HouseInvitation = response.json();
+    assert_eq!(accepted.accepted_by, Some(guest_id));
+
+    // Codes work once
+    let response = server
+        .post("/invitations/accept")
+        .add_header("Authorization", guest_header.clone())
+        .json(&json!({ "code": invitation.code }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .get(&format!("/houses/{}/members", house.id))
+        .add_header("Authorization", guest_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let members: ListResponse<HouseMember> = response.json();
+    let members: Vec<(i64, HouseRole)> = members
+        .items
+        .iter()
+        .map(|member| (member.user_id, member.role))
+        .collect();
+    assert_eq!(
+        members,
+        vec![(owner_id, HouseRole::Owner), (guest_id, HouseRole::Guest)]
+    );
+
+    // Guests can't invite
+    let response = server
+        .post(&format!("/houses/{}/invitations", house.id))
+        .add_header("Authorization", guest_header.clone())
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // The last owner can't leave
+    let response = server
+        .post(&format!("/houses/{}/leave", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .delete(&format!("/houses/{}/members/{}", house.id, guest_id))
+        .add_header("Authorization", owner_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get(&format!("/houses/{}", house.id))
+        .add_header("Authorization", guest_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_email_invitations() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (_, owner_token, _) = register_and_login_user(&server).await;
+    let (email, invitee_token, invitee_id) = register_and_login_user(&server).await;
+    let owner_header = format!("Bearer {}", owner_token);
+    let invitee_header = format!("Bearer {}", invitee_token);
+    let house = create_house(&server, &owner_token).await;
+
+    for role in ["admin", "member"] {
+        let response = server
+            .post(&format!("/houses/{}/invitations", house.id))
+            .add_header("Authorization", owner_header.clone())
+            .json(&json!({ "email": email.to_uppercase(), "role": role }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+
+    let mail_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM outbox WHERE recipient = $1 AND subject LIKE 'You''re invited%'"#,
+        email
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(mail_count, 2);
+
+    let response = server
+        .get(&format!("/houses/{}/invitations", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let pending: ListResponse<HouseInvitation> = response.json();
+    assert_eq!(pending.items.len(), 2);
+
+    // Invitations are only listed for verified email addresses
+    let response = server
+        .get("/invitations")
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    sqlx::query!(
+        "UPDATE users SET email_verified_at = NOW() WHERE id = $1",
+        invitee_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let response = server
+        .get("/invitations")
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let received: ListResponse<ReceivedHouseInvitation> = response.json();
+    assert_eq!(received.items.len(), 2);
+    assert_eq!(received.items[0].house_name, "Shared House");
+    let (member_invitation, admin_invitation) = (&received.items[0], &received.items[1]);
+    assert_eq!(member_invitation.role, HouseRole::Member);
+
+    let response = server
+        .post(&format!("/invitations/{}/decline", member_invitation.id))
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .post(&format!("/invitations/{}/accept", admin_invitation.id))
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get("/invitations")
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    let received: ListResponse<ReceivedHouseInvitation> = response.json();
+    assert!(received.items.is_empty());
+
+    // Admins can't remove owners, but can leave
+    let response = server
+        .get(&format!("/houses/{}/members", house.id))
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    let members: ListResponse<HouseMember> = response.json();
+    let owner_id = members.items[0].user_id;
+
+    let response = server
+        .delete(&format!("/houses/{}/members/{}", house.id, owner_id))
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .post(&format!("/houses/{}/leave", house.id))
+        .add_header("Authorization", invitee_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_time_boxed_guest_access() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (owner_email, owner_token, _) = register_and_login_user(&server).await;
+    let (_, guest_token, guest_id) = register_and_login_user(&server).await;
+    let owner_header = format!("Bearer {}", owner_token);
+    let guest_header = format!("Bearer {}", guest_token);
+    let house = create_house(&server, &owner_token).await;
+
+    // Owners can't be time-boxed
+    let response = server
+        .post(&format!("/houses/{}/invitations", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({ "role": "owner", "valid_until": Utc::now() + Duration::days(1) }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // A window that starts in an hour
+    let now = Utc::now();
+    let schedule = json!([{
+        "days": ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"],
+        "start": (now + Duration::hours(1)).format("%H:%M:%S").to_string(),
+        "end": (now + Duration::hours(2)).format("%H:%M:%S").to_string()
+    }]);
+    let response = server
+        .post(&format!("/houses/{}/invitations", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({
+            "role": "guest",
+            "valid_until": now + Duration::days(1),
+            "schedule": schedule
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let invitation: CreatedHouseInvitation = response.json();
+    assert!(invitation.invitation.access.schedule.is_some());
+
+    let response = server
+        .post("/invitations/accept")
+        .add_header("Authorization", guest_header.clone())
+        .json(&json!({ "code": invitation.code }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get(&format!("/houses/{}", house.id))
+        .add_header("Authorization", guest_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .put(&format!("/houses/{}/members/{}/access", house.id, guest_id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({ "valid_until": now + Duration::days(1) }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get(&format!("/houses/{}", house.id))
+        .add_header("Authorization", guest_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    // Let the access end and run the cleanup
+    sqlx::query!(
+        "UPDATE user_houses SET valid_until = NOW() - INTERVAL '1 minute' WHERE user_id = $1 AND house_id = $2",
+        guest_id,
+        house.id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let app_state = AppState::new(crate::db::Database::new(pool.clone()), create_test_config());
+    let removed = HouseMembersRouterState::new(app_state)
+        .house_members_service
+        .remove_expired_members()
+        .await
+        .unwrap();
+    assert!(removed >= 1);
+
+    let response = server
+        .get(&format!("/houses/{}", house.id))
+        .add_header("Authorization", guest_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let notified = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM outbox WHERE recipient = $1 AND subject = 'Access to Shared House ended'"#,
+        owner_email
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(notified, 1);
+}
+
+#[tokio::test]
+async fn test_room_scoped_membership() {
+    let (app, _pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (_, owner_token, _) = register_and_login_user(&server).await;
+    let (_, member_token, member_id) = register_and_login_user(&server).await;
+    let owner_header = format!("Bearer {}", owner_token);
+    let member_header = format!("Bearer {}", member_token);
+    let house = create_house(&server, &owner_token).await;
+
+    let mut devices = vec![];
+    for name in ["Bedroom", "Kitchen"] {
+        let response = server
+            .post(&format!("/houses/{}/rooms", house.id))
+            .add_header("Authorization", owner_header.clone())
+            .json(&json!({ "name": name, "room_type": "bedroom" }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let room: rooms::Room = response.json();
+
+        let response = server
+            .post("/devices")
+            .add_header("Authorization", owner_header.clone())
+            .json(&json!({ "name": "Lamp", "device_type": "light", "room_id": room.id }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let device: Device = response.json();
+
+        let response = server
+            .post("/metrics")
+            .add_header("Authorization", owner_header.clone())
+            .json(&json!({
+                "device_id": device.id,
+                "metric_type": "temperature",
+                "metric_value": 21.5,
+                "unit": "C"
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        devices.push(device);
+    }
+    let (bedroom_device, kitchen_device) = (&devices[0], &devices[1]);
+
+    // Admins need the whole house
+    let response = server
+        .post(&format!("/houses/{}/invitations", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({ "role": "admin", "room_ids": [bedroom_device.room_id] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post(&format!("/houses/{}/invitations", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({ "role": "member", "room_ids": [bedroom_device.room_id] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let invitation: CreatedHouseInvitation = response.json();
+
+    let response = server
+        .post("/invitations/accept")
+        .add_header("Authorization", member_header.clone())
+        .json(&json!({ "code": invitation.code }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", member_header.clone())
+        .await;
+    let visible_rooms: ListResponse<rooms::Room> = response.json();
+    assert_eq!(visible_rooms.items.len(), 1);
+    assert_eq!(visible_rooms.items[0].name, "Bedroom");
+
+    let response = server
+        .get(&format!("/houses/{}/devices", house.id))
+        .add_header("Authorization", member_header.clone())
+        .await;
+    let visible_devices: ListResponse<Device> = response.json();
+    // The device is online since a metric was reported for it
+    let visible_ids: Vec<i64> = visible_devices.items.iter().map(|d| d.id).collect();
+    assert_eq!(visible_ids, vec![bedroom_device.id]);
+
+    let response = server
+        .get(&format!("/houses/{}/metrics", house.id))
+        .add_header("Authorization", member_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let metrics: Vec<DeviceMetric> = response.json();
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].device_id, bedroom_device.id);
+
+    let response = server
+        .patch(&format!("/devices/{}", bedroom_device.id))
+        .add_header("Authorization", member_header.clone())
+        .json(&json!({ "name": "Reading Lamp" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .get(&format!("/devices/{}", kitchen_device.id))
+        .add_header("Authorization", member_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Lifting the limit gives access to the whole house
+    let response = server
+        .put(&format!(
+            "/houses/{}/members/{}/access",
+            house.id, member_id
+        ))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get(&format!("/devices/{}", kitchen_device.id))
+        .add_header("Authorization", member_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_house_ownership_transfer() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (_, owner_token, owner_id) = register_and_login_user(&server).await;
+    let (_, admin_token, admin_id) = register_and_login_user(&server).await;
+    let owner_header = format!("Bearer {}", owner_token);
+    let admin_header = format!("Bearer {}", admin_token);
+    let house = create_house(&server, &owner_token).await;
+    sqlx::query!(
+        "INSERT INTO user_houses (user_id, house_id, role) VALUES ($1, $2, 'admin')",
+        admin_id,
+        house.id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Admins can't hand the house over
+    let response = server
+        .post(&format!("/houses/{}/transfers", house.id))
+        .add_header("Authorization", admin_header.clone())
+        .json(&json!({ "to_user_id": owner_id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // The nominee has to verify their email address first
+    let response = server
+        .post(&format!("/houses/{}/transfers", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({ "to_user_id": admin_id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    sqlx::query!(
+        "UPDATE users SET email_verified_at = NOW() WHERE id = $1",
+        admin_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let response = server
+        .post(&format!("/houses/{}/transfers", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .json(&json!({ "to_user_id": admin_id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let transfer: HouseTransfer = response.json();
+
+    let response = server
+        .get("/transfers")
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    let received: ListResponse<ReceivedHouseTransfer> = response.json();
+    assert_eq!(received.items.len(), 1);
+    assert_eq!(received.items[0].house_id, house.id);
+
+    // Only the nominee accepts
+    let response = server
+        .post(&format!("/transfers/{}/accept", transfer.id))
+        .add_header("Authorization", owner_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server
+        .post(&format!("/transfers/{}/accept", transfer.id))
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let accepted: HouseTransfer = response.json();
+    assert_eq!(accepted.previous_owner_role, Some(HouseRole::Admin));
+
+    let response = server
+        .get(&format!("/houses/{}/members", house.id))
+        .add_header("Authorization", admin_header.clone())
+        .await;
+    let members: ListResponse<HouseMember> = response.json();
+    let members: Vec<(i64, HouseRole)> = members
+        .items
+        .iter()
+        .map(|member| (member.user_id, member.role))
+        .collect();
+    assert_eq!(
+        members,
+        vec![(admin_id, HouseRole::Owner), (owner_id, HouseRole::Admin)]
+    );
+
+    // The former owner lost the owner-only actions
+    let response = server
+        .delete(&format!("/houses/{}", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // The transfer stays in the audit trail
+    let response = server
+        .get(&format!("/houses/{}/transfers", house.id))
+        .add_header("Authorization", owner_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let trail: ListResponse<HouseTransfer> = response.json();
+    assert_eq!(trail.items.len(), 1);
+    assert_eq!(trail.items[0].from_user_id, Some(owner_id));
+    assert_eq!(trail.items[0].to_user_id, Some(admin_id));
+    assert!(trail.items[0].accepted_at.is_some());
+}
@@ -1,13 +1,20 @@
-use crate::{create_app, models::auth::AuthResponse};
+use crate::{
+    config::JwtAlgorithm,
+    create_app,
+    models::{auth::AuthResponse, jwks::JsonWebKeySet},
+};
 #[cfg(test)]
 use crate::{
-    tests::{create_test_config, setup_test_database},
+    tests::{
+        create_proxied_test_server, create_test_config, create_test_pool, setup_test_database,
+    },
     AppState,
 };
 use axum::{http::StatusCode, Router};
 use axum_test::TestServer;
 use rand::Rng;
 use serde_json::json;
+use std::net::SocketAddr;
 use uuid::Uuid;
 
 async fn create_test_app() -> Result<Router, Box<dyn std::error::Error>> {
@@ -123,9 +130,10 @@ async fn test_invalid_credentials() {
     let app = create_test_app().await.expect("Failed to create test app");
     let server = TestServer::new(app).unwrap();
 
-    // Test login with invalid credentials
+    // Test login with invalid credentials. A unique email keeps repeated runs
+    // from locking the account out.
     let login_payload = json!({
-        "email": "nonexistent@example.com",
+        "email": format!("nonexistent_{}@example.com", Uuid::new_v4()),
         "password": "wrongpassword"
     });
 
@@ -211,3 +219,624 @@ async fn test_invalid_input_validation() {
 
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
 }
+
+#[tokio::test] // Requires test database setup
+async fn test_concurrent_refreshes_rotate_once() {
+    let app = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (email, _phone, _token, _) = register_unique_user(&server).await;
+    let response = server
+        .post("/auth/login")
+        .json(&json!({ "email": email, "password": "password123" }))
+        .await;
+    let auth_response: serde_json::Value = response.json();
+    let refresh = json!({ "refresh_token": auth_response["refresh_token"] });
+
+    let (first, second) = tokio::join!(
+        server.post("/auth/refresh").json(&refresh),
+        server.post("/auth/refresh").json(&refresh)
+    );
+    let mut statuses = vec![first.status_code(), second.status_code()];
+    statuses.sort();
+    assert_eq!(statuses, vec![StatusCode::OK, StatusCode::UNAUTHORIZED]);
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_refresh_token_rotation_and_session_revocation() {
+    let app = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (email, _phone, _token, _) = register_unique_user(&server).await;
+    let login_payload = json!({
+        "email": email,
+        "password": "password123"
+    });
+
+    let response = server
+        .post("/auth/login")
+        .add_header("User-Agent", "integration-test")
+        .json(&login_payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let auth_response: serde_json::Value = response.json();
+    let refresh_token = auth_response["refresh_token"].as_str().unwrap().to_string();
+
+    // Refreshing rotates the refresh token
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": refresh_token }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let tokens: serde_json::Value = response.json();
+    let token = tokens["token"].as_str().unwrap().to_string();
+    let new_refresh_token = tokens["refresh_token"].as_str().unwrap().to_string();
+    assert_ne!(new_refresh_token, refresh_token);
+
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": refresh_token }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Sessions from signup, the helper's login and this login are all listed
+    let response = server
+        .get("/profile/sessions")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let sessions: serde_json::Value = response.json();
+    let items = sessions["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    let current = items
+        .iter()
+        .find(|s| s["current"].as_bool().unwrap())
+        .expect("current session should be marked");
+    assert_eq!(current["user_agent"], "integration-test");
+    let other_id = items
+        .iter()
+        .find(|s| !s["current"].as_bool().unwrap())
+        .unwrap()["id"]
+        .as_i64()
+        .unwrap();
+
+    let response = server
+        .delete(&format!("/profile/sessions/{}", other_id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .delete(&format!("/profile/sessions/{}", other_id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Logging out revokes the session and its access tokens
+    let response = server
+        .post("/auth/logout")
+        .json(&json!({ "refresh_token": new_refresh_token }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post("/auth/refresh")
+        .json(&json!({ "refresh_token": new_refresh_token }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+// Reads the token from the link in the latest outbox email sent to `email`
+async fn latest_outbox_token(email: &str) -> String {
+    let pool = create_test_pool().await.unwrap();
+    let body = sqlx::query_scalar!(
+        "SELECT body FROM outbox WHERE recipient = $1 ORDER BY id DESC LIMIT 1",
+        email
+    )
+    .fetch_one(&pool)
+    .await
+    .expect("No email in outbox");
+
+    body.split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("No token in email")
+        .to_string()
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_email_verification_flow() {
+    let app = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (email, _phone, token, _) = register_unique_user(&server).await;
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let profile: serde_json::Value = response.json();
+    assert!(profile["email_verified_at"].is_null());
+
+    let verification_token = latest_outbox_token(&email).await;
+
+    let response = server
+        .post("/auth/email/verify")
+        .json(&json!({ "token": verification_token }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    // Tokens are single-use
+    let response = server
+        .post("/auth/email/verify")
+        .json(&json!({ "token": verification_token }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let profile: serde_json::Value = response.json();
+    assert!(profile["email_verified_at"].is_string());
+
+    let response = server
+        .post("/profile/email/verification")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_password_reset_flow() {
+    let app = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (email, _phone, token, _) = register_unique_user(&server).await;
+
+    // Unknown addresses get the same response
+    let response = server
+        .post("/auth/password/forgot")
+        .json(&json!({ "email": format!("unknown_{}@example.com", Uuid::new_v4()) }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+
+    let response = server
+        .post("/auth/password/forgot")
+        .json(&json!({ "email": email }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+
+    let reset_token = latest_outbox_token(&email).await;
+
+    let response = server
+        .post("/auth/password/reset")
+        .json(&json!({ "token": reset_token, "new_password": "new_password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .post("/auth/password/reset")
+        .json(&json!({ "token": reset_token, "new_password": "another_password" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Existing sessions are revoked
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post("/auth/login")
+        .json(&json!({ "email": email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post("/auth/login")
+        .json(&json!({ "email": email, "password": "new_password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+fn totp_code(secret: &str, time: i64) -> String {
+    let secret = totp_rs::Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .unwrap();
+    totp_rs::TOTP::new_unchecked(
+        totp_rs::Algorithm::SHA1,
+        6,
+        0,
+        30,
+        secret,
+        None,
+        String::new(),
+    )
+    .generate(time as u64)
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_two_factor_login_flow() {
+    let app = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (email, _phone, token, _) = register_unique_user(&server).await;
+    let auth_header = format!("Bearer {}", token);
+    let now = chrono::Utc::now().timestamp();
+
+    let response = server
+        .post("/profile/2fa/totp")
+        .add_header("Authorization", auth_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let enrollment: serde_json::Value = response.json();
+    let secret = enrollment["secret"].as_str().unwrap().to_string();
+    assert!(enrollment["otpauth_uri"]
+        .as_str()
+        .unwrap()
+        .starts_with("otpauth://totp/"));
+
+    let response = server
+        .post("/profile/2fa/totp/confirm")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({ "code": totp_code(&secret, now) }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let codes: serde_json::Value = response.json();
+    let recovery_codes: Vec<String> = codes["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(recovery_codes.len(), 10);
+
+    // The password alone only yields a challenge
+    let login_payload = json!({ "email": email, "password": "password123" });
+    let response = server.post("/auth/login").json(&login_payload).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let challenge: serde_json::Value = response.json();
+    assert_eq!(challenge["two_factor_required"], true);
+    assert!(challenge["token"].is_null());
+    let challenge_token = challenge["challenge_token"].as_str().unwrap().to_string();
+
+    // The challenge token is not an access token
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", challenge_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // The code used for confirmation can't be replayed
+    let response = server
+        .post("/auth/login/2fa")
+        .json(&json!({ "challenge_token": challenge_token, "code": totp_code(&secret, now) }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post("/auth/login/2fa")
+        .json(&json!({ "challenge_token": challenge_token, "code": totp_code(&secret, now + 30) }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let auth_response: AuthResponse = response.json();
+    assert_eq!(auth_response.user.email, email);
+
+    // Recovery codes work once
+    let response = server.post("/auth/login").json(&login_payload).await;
+    let challenge: serde_json::Value = response.json();
+    let challenge_token = challenge["challenge_token"].as_str().unwrap().to_string();
+    let response = server
+        .post("/auth/login/2fa")
+        .json(&json!({ "challenge_token": challenge_token, "code": recovery_codes[0] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let response = server
+        .post("/auth/login/2fa")
+        .json(&json!({ "challenge_token": challenge_token, "code": recovery_codes[0] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post("/profile/2fa/totp/disable")
+        .add_header("Authorization", format!("Bearer {}", auth_response.token))
+        .json(&json!({ "code": recovery_codes[1] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server.post("/auth/login").json(&login_payload).await;
+    let auth_response: serde_json::Value = response.json();
+    assert!(auth_response["token"].is_string());
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_repeated_failed_logins_lock_account() {
+    let (server, _) = create_proxied_test_server().await;
+
+    let (email, _phone, _token, _) = register_unique_user(&server).await;
+    let client_ip = format!(
+        "10.8.{}.{}",
+        rand::rng().random_range(0..256),
+        rand::rng().random_range(0..256)
+    );
+    let bad_login = json!({ "email": email, "password": "wrongpassword" });
+    let good_login = json!({ "email": email, "password": "password123" });
+
+    // A successful login forgets earlier failures of the account
+    for _ in 0..4 {
+        let response = server
+            .post("/auth/login")
+            .add_header("X-Forwarded-For", client_ip.clone())
+            .json(&bad_login)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+    let response = server
+        .post("/auth/login")
+        .add_header("X-Forwarded-For", client_ip.clone())
+        .json(&good_login)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    for _ in 0..6 {
+        let response = server
+            .post("/auth/login")
+            .add_header("X-Forwarded-For", client_ip.clone())
+            .json(&bad_login)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Locked out even with the right password, and from any address
+    let response = server.post("/auth/login").json(&good_login).await;
+    assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response
+        .header("Retry-After")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=30).contains(&retry_after));
+
+    let pool = create_test_pool().await.unwrap();
+    let failed_count = sqlx::query_scalar!(
+        "SELECT failed_count FROM login_attempts WHERE scope = 'ip' AND key = $1",
+        client_ip
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    // but not those of the address, which only age out
+    assert_eq!(failed_count, 10);
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_spoofed_forwarded_for_does_not_avoid_ip_lockout() {
+    let (server, _) = create_proxied_test_server().await;
+
+    let client_ip = format!(
+        "10.9.{}.{}",
+        rand::rng().random_range(0..256),
+        rand::rng().random_range(0..256)
+    );
+    // The client makes up a new leftmost hop every time; the proxy appends the
+    // address it saw the request from.
+    let forwarded_for = || {
+        format!(
+            "203.0.113.{}, {}",
+            rand::rng().random_range(0..256),
+            client_ip
+        )
+    };
+    let bad_login = || {
+        json!({
+            "email": format!("nobody_{}@example.com", Uuid::new_v4()),
+            "password": "wrongpassword"
+        })
+    };
+
+    for _ in 0..21 {
+        let response = server
+            .post("/auth/login")
+            .add_header("X-Forwarded-For", forwarded_for())
+            .json(&bad_login())
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    let response = server
+        .post("/auth/login")
+        .add_header("X-Forwarded-For", forwarded_for())
+        .json(&bad_login())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Without a trusted proxy in front the header is ignored altogether
+    let app = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::builder()
+        .http_transport()
+        .build(app.into_make_service_with_connect_info::<SocketAddr>())
+        .unwrap();
+    // Logging in successfully, so the loopback address shared by all tests
+    // collects no failures
+    let (email, _phone, _token, _) = register_unique_user(&server).await;
+    let response = server
+        .post("/auth/login")
+        .add_header("X-Forwarded-For", "198.51.100.1")
+        .json(&json!({ "email": email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let token = response.json::<serde_json::Value>()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = server
+        .get("/profile/security-events")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    let events: serde_json::Value = response.json();
+    assert_eq!(events["items"][0]["event_type"], "login_succeeded");
+    assert_eq!(events["items"][0]["ip"], "127.0.0.1");
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_profile_update_password_change_and_deletion() {
+    let app = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (email, _phone, token, user_id) = register_unique_user(&server).await;
+    let (_, other_phone, _, _) = register_unique_user(&server).await;
+    let auth_header = format!("Bearer {}", token);
+
+    // Profile update
+    let response = server
+        .patch("/profile")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({ "first_name": "Johnny" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let profile: serde_json::Value = response.json();
+    assert_eq!(profile["first_name"], "Johnny");
+    assert_eq!(profile["last_name"], "User");
+
+    let response = server
+        .patch("/profile")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({ "phone": other_phone }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Password change keeps the current session only
+    let response = server
+        .post("/auth/login")
+        .json(&json!({ "email": email, "password": "password123" }))
+        .await;
+    let other_session: serde_json::Value = response.json();
+    let other_token = other_session["token"].as_str().unwrap().to_string();
+
+    let response = server
+        .post("/profile/password")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({ "current_password": "wrong", "new_password": "new_password" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post("/profile/password")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({ "current_password": "password123", "new_password": "new_password" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", auth_header.clone())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", other_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Deletion takes the houses nobody else can access with it
+    let response = server
+        .post("/houses")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({
+            "name": "Doomed House",
+            "address": format!("{} Doomed Street", Uuid::new_v4()),
+            "type": "apartment"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let house: serde_json::Value = response.json();
+    let house_id = house["id"].as_i64().unwrap();
+
+    let response = server
+        .delete("/profile")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({ "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .delete("/profile")
+        .add_header("Authorization", auth_header.clone())
+        .json(&json!({ "password": "new_password" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .post("/auth/login")
+        .json(&json!({ "email": email, "password": "new_password" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let pool = create_test_pool().await.unwrap();
+    let remaining = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM houses WHERE id = $1 OR id IN (SELECT house_id FROM user_houses WHERE user_id = $2)",
+        house_id,
+        user_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining, Some(0));
+}
+
+#[tokio::test] // Requires test database setup
+async fn test_asymmetric_tokens_verify_with_jwks() {
+    let server = TestServer::new(create_test_app().await.unwrap()).unwrap();
+    let response = server.get("/.well-known/jwks.json").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.json::<JsonWebKeySet>().keys.is_empty());
+
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/fixtures/jwt");
+    let mut config = create_test_config();
+    config.jwt_algorithm = JwtAlgorithm::RS256;
+    config.jwt_private_key_path = Some(format!("{}/private/rsa-2026-10.pem", fixtures));
+    config.jwt_key_id = Some("rsa-2026-10".to_string());
+    config.jwt_public_keys_dir = Some(format!("{}/public", fixtures));
+    let pool = create_test_pool().await.unwrap();
+    let app = create_app(AppState::new(crate::db::Database::new(pool), config));
+    let server = TestServer::new(app).unwrap();
+
+    let (_, _, token, user_id) = register_unique_user(&server).await;
+
+    let response = server.get("/.well-known/jwks.json").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let jwks: JsonWebKeySet = response.json();
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::RS256);
+    let kid = header.kid.unwrap();
+    assert_eq!(kid, "rsa-2026-10");
+
+    // Verify the token the way an edge service would, from the published key only
+    let jwk = jwks.keys.iter().find(|key| key.kid == kid).unwrap();
+    let jwk: jsonwebtoken::jwk::Jwk =
+        serde_json::from_value(serde_json::to_value(jwk).unwrap()).unwrap();
+    let claims = jsonwebtoken::decode::<crate::models::auth::Claims>(
+        &token,
+        &jsonwebtoken::DecodingKey::from_jwk(&jwk).unwrap(),
+        &jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::RS256),
+    )
+    .unwrap()
+    .claims;
+    assert_eq!(claims.sub, user_id);
+
+    let response = server
+        .get("/profile")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
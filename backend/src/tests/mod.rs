@@ -1,16 +1,21 @@
+pub mod admin_integration_tests;
+pub mod api_token_integration_tests;
 pub mod device_integration_tests;
 pub mod device_metrics_integration_tests;
+pub mod house_members_integration_tests;
 pub mod integration_tests;
 pub mod room_integration_tests;
-pub mod api_token_integration_tests;
 
 #[cfg(test)]
 pub use test_utils::*;
 
 #[cfg(test)]
 mod test_utils {
-    use crate::config::Config;
+    use crate::config::{Config, JwtAlgorithm};
+    use crate::{create_app, AppState};
+    use axum_test::TestServer;
     use sqlx::PgPool;
+    use std::net::{IpAddr, SocketAddr};
 
     pub fn create_test_config() -> Config {
         Config {
@@ -21,8 +26,18 @@ mod test_utils {
             db_user: "smart_home_user".to_string(),
             db_pass: "1234".to_string(),
             jwt_secret: "test_secret_key_that_is_long_enough_for_jwt".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_path: None,
+            jwt_key_id: None,
+            jwt_public_keys_dir: None,
             jwt_expires_in: 3600,
+            refresh_token_expires_in: 86400,
+            api_token_secret: "test_api_token_secret".to_string(),
             frontend_origin: "http://localhost:5173".to_string(),
+            smtp_url: None,
+            mail_from: "Smart Home <no-reply@smarthome.local>".to_string(),
+            require_email_verification: false,
+            trusted_proxies: vec![],
         }
     }
 
@@ -72,4 +87,26 @@ mod test_utils {
 
         Ok(new_pool)
     }
+
+    /// Test server behind a reverse proxy on the loopback address. Requests
+    /// carry the peer address, so `X-Forwarded-For` is only trusted from the
+    /// proxy and from `10.0.0.1`, a proxy in front of it.
+    pub async fn create_proxied_test_server() -> (TestServer, PgPool) {
+        let pool = setup_test_database()
+            .await
+            .expect("Failed to set up test database");
+        let mut config = create_test_config();
+        config.trusted_proxies = vec![IpAddr::from([127, 0, 0, 1]), IpAddr::from([10, 0, 0, 1])];
+
+        let app = create_app(AppState::new(
+            crate::db::Database::new(pool.clone()),
+            config,
+        ));
+        let server = TestServer::builder()
+            .http_transport()
+            .build(app.into_make_service_with_connect_info::<SocketAddr>())
+            .unwrap();
+
+        (server, pool)
+    }
 }
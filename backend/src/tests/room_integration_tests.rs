@@ -11,7 +11,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::create_app;
-use crate::models::{houses, rooms};
+use crate::models::{devices, houses, rooms, zones};
 
 async fn create_test_app() -> Result<(Router, PgPool), Box<dyn std::error::Error>> {
     let pool = setup_test_database().await?;
@@ -28,7 +28,8 @@ async fn create_test_app() -> Result<(Router, PgPool), Box<dyn std::error::Error
 async fn create_house(server: &TestServer, token: &str, name: &str) -> houses::House {
     let create_house_payload = json!({
         "name": name,
-        "address": name
+        "address": name,
+        "type": "apartment"
     });
     let response = server
         .post("/houses")
@@ -92,3 +93,413 @@ async fn test_create_room() {
     assert_eq!(room.name, "Living Room");
     assert_eq!(room.house_id, house.id);
 }
+
+// Helper to add a user to a house with the given role
+async fn add_member(pool: &PgPool, house_id: i64, user_id: i64, role: &str) {
+    sqlx::query!(
+        "INSERT INTO user_houses (user_id, house_id, role) VALUES ($1, $2, $3::TEXT::house_role)",
+        user_id,
+        house_id,
+        role
+    )
+    .execute(pool)
+    .await
+    .expect("Failed to add house member");
+}
+
+#[tokio::test]
+async fn test_house_roles_limit_actions() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (owner_token, _) = register_and_login_user(&server, &pool).await;
+    let (member_token, member_id) = register_and_login_user(&server, &pool).await;
+    let (guest_token, guest_id) = register_and_login_user(&server, &pool).await;
+    let (stranger_token, _) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &owner_token, &Uuid::new_v4().to_string()).await;
+    add_member(&pool, house.id, member_id, "member").await;
+    add_member(&pool, house.id, guest_id, "guest").await;
+
+    let room_payload = json!({ "name": "Kitchen", "room_type": "kitchen" });
+
+    // Non-members can't see the house
+    let response = server
+        .get(&format!("/houses/{}", house.id))
+        .add_header("Authorization", format!("Bearer {}", stranger_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Guests can look but not change anything
+    let response = server
+        .get(&format!("/houses/{}", house.id))
+        .add_header("Authorization", format!("Bearer {}", guest_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .post(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", guest_token))
+        .json(&room_payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Members manage devices but not rooms
+    let response = server
+        .post(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .json(&room_payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .post(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&room_payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let room: rooms::Room = response.json();
+
+    let device_payload = json!({ "name": "Lamp", "device_type": "light", "room_id": room.id });
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", guest_token))
+        .json(&device_payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .post("/devices")
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .json(&device_payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    // Only the owner deletes the house
+    let response = server
+        .delete(&format!("/houses/{}", house.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .delete(&format!("/houses/{}", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_edit_house_and_rooms() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (owner_token, _) = register_and_login_user(&server, &pool).await;
+    let (member_token, member_id) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &owner_token, &Uuid::new_v4().to_string()).await;
+    let other_house = create_house(&server, &owner_token, &Uuid::new_v4().to_string()).await;
+    add_member(&pool, house.id, member_id, "member").await;
+
+    // Members can't edit the house
+    let response = server
+        .patch(&format!("/houses/{}", house.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .json(&json!({ "name": "Renamed House" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Addresses stay unique
+    let response = server
+        .patch(&format!("/houses/{}", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "address": other_house.address }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .patch(&format!("/houses/{}", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Renamed House", "address": house.address }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let updated: houses::House = response.json();
+    assert_eq!(updated.name, "Renamed House");
+    assert_eq!(updated.address, house.address);
+    assert_eq!(updated.r#type, house.r#type);
+
+    let response = server
+        .post(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Kitchen", "room_type": "kitchen" }))
+        .await;
+    let room: rooms::Room = response.json();
+
+    let response = server
+        .patch(&format!("/houses/{}/rooms/{}", house.id, room.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .json(&json!({ "name": "Dining Room" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .patch(&format!("/houses/{}/rooms/{}", house.id, room.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Rooms are only edited through their own house
+    let response = server
+        .patch(&format!("/houses/{}/rooms/{}", other_house.id, room.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Dining Room" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .patch(&format!("/houses/{}/rooms/{}", house.id, room.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Dining Room" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let updated: rooms::Room = response.json();
+    assert_eq!(updated.name, "Dining Room");
+    assert_eq!(updated.room_type, "kitchen");
+
+    let response = server
+        .delete(&format!("/houses/{}/rooms/{}", house.id, room.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_house_zones() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (owner_token, _) = register_and_login_user(&server, &pool).await;
+    let (member_token, member_id) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &owner_token, &Uuid::new_v4().to_string()).await;
+    let other_house = create_house(&server, &owner_token, &Uuid::new_v4().to_string()).await;
+    add_member(&pool, house.id, member_id, "member").await;
+
+    let response = server
+        .post(&format!("/houses/{}/zones", house.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .json(&json!({ "name": "Ground floor", "zone_type": "floor" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .post(&format!("/houses/{}/zones", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Ground floor", "zone_type": "floor" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let zone: zones::Zone = response.json();
+
+    let response = server
+        .post(&format!("/houses/{}/zones", other_house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Garden", "zone_type": "outdoor" }))
+        .await;
+    let other_zone: zones::Zone = response.json();
+
+    // Rooms can only be put in zones of their own house
+    let response = server
+        .post(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Kitchen", "room_type": "kitchen", "zone_id": other_zone.id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Kitchen", "room_type": "kitchen", "zone_id": zone.id }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let kitchen: rooms::Room = response.json();
+    assert_eq!(kitchen.zone_id, Some(zone.id));
+
+    let response = server
+        .post(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "Attic", "room_type": "storage" }))
+        .await;
+    let attic: rooms::Room = response.json();
+    assert_eq!(attic.zone_id, None);
+
+    for room_id in [kitchen.id, attic.id] {
+        let response = server
+            .post("/devices")
+            .add_header("Authorization", format!("Bearer {}", owner_token))
+            .json(&json!({ "name": "Sensor", "device_type": "temperature_sensor", "room_id": room_id }))
+            .await;
+        let device: devices::Device = response.json();
+        let response = server
+            .post("/metrics")
+            .add_header("Authorization", format!("Bearer {}", owner_token))
+            .json(&json!({
+                "device_id": device.id,
+                "metric_type": "temperature",
+                "metric_value": 21.5,
+                "unit": "C"
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+
+    let response = server
+        .get(&format!("/houses/{}/zones", house.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+
+    let response = server
+        .get(&format!("/houses/{}/zones/{}/devices", house.id, zone.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+
+    let response = server
+        .get(&format!("/houses/{}/zones/{}/metrics", house.id, zone.id))
+        .add_header("Authorization", format!("Bearer {}", member_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    // Zones are only reached through their own house
+    let response = server
+        .get(&format!(
+            "/houses/{}/zones/{}/devices",
+            house.id, other_zone.id
+        ))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server
+        .patch(&format!("/houses/{}/zones/{}", house.id, zone.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "name": "First floor" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let updated: zones::Zone = response.json();
+    assert_eq!(updated.name, "First floor");
+    assert_eq!(updated.zone_type, "floor");
+
+    // `null` takes the room out of its zone
+    let response = server
+        .patch(&format!("/houses/{}/rooms/{}", house.id, kitchen.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "zone_id": null }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let updated: rooms::Room = response.json();
+    assert_eq!(updated.zone_id, None);
+
+    let response = server
+        .patch(&format!("/houses/{}/rooms/{}", house.id, attic.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .json(&json!({ "zone_id": zone.id }))
+        .await;
+    let updated: rooms::Room = response.json();
+    assert_eq!(updated.zone_id, Some(zone.id));
+
+    let response = server
+        .delete(&format!("/houses/{}/zones/{}", house.id, zone.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server
+        .get(&format!("/houses/{}/rooms", house.id))
+        .add_header("Authorization", format!("Bearer {}", owner_token))
+        .await;
+    let rooms: serde_json::Value = response.json();
+    let rooms = rooms["items"].as_array().unwrap();
+    assert_eq!(rooms.len(), 2);
+    assert!(rooms.iter().all(|room| room["zone_id"].is_null()));
+}
+
+#[tokio::test]
+async fn test_house_settings() {
+    let (app, pool) = create_test_app().await.expect("Failed to create test app");
+    let server = TestServer::new(app).unwrap();
+
+    let (token, _) = register_and_login_user(&server, &pool).await;
+    let house = create_house(&server, &token, &Uuid::new_v4().to_string()).await;
+    assert_eq!(house.timezone, "UTC");
+    assert_eq!(house.unit_system, houses::UnitSystem::Metric);
+    assert_eq!(house.currency, "USD");
+    assert_eq!(house.latitude, None);
+
+    for invalid in [
+        json!({ "timezone": "Mars/Olympus_Mons" }),
+        json!({ "latitude": 52.52 }),
+        json!({ "latitude": 91.0, "longitude": 13.4 }),
+        json!({ "currency": "eur" }),
+        json!({ "unit_system": "nautical" }),
+    ] {
+        let mut payload = json!({
+            "name": "Berlin Flat",
+            "address": Uuid::new_v4().to_string(),
+            "type": "apartment"
+        });
+        payload
+            .as_object_mut()
+            .unwrap()
+            .extend(invalid.as_object().unwrap().clone());
+        let response = server
+            .post("/houses")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&payload)
+            .await;
+        assert_eq!(
+            response.status_code(),
+            StatusCode::BAD_REQUEST,
+            "{}",
+            invalid
+        );
+    }
+
+    let response = server
+        .post("/houses")
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({
+            "name": "Berlin Flat",
+            "address": Uuid::new_v4().to_string(),
+            "type": "apartment",
+            "timezone": "Europe/Berlin",
+            "latitude": 52.52,
+            "longitude": 13.405,
+            "unit_system": "imperial",
+            "currency": "EUR"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let created: houses::House = response.json();
+    assert_eq!(created.timezone, "Europe/Berlin");
+    assert_eq!(created.latitude, Some(52.52));
+    assert_eq!(created.longitude, Some(13.405));
+    assert_eq!(created.unit_system, houses::UnitSystem::Imperial);
+    assert_eq!(created.currency, "EUR");
+
+    let response = server
+        .patch(&format!("/houses/{}", created.id))
+        .add_header("Authorization", format!("Bearer {}", token))
+        .json(&json!({ "timezone": "America/New_York", "unit_system": "metric" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let updated: houses::House = response.json();
+    assert_eq!(updated.timezone, "America/New_York");
+    assert_eq!(updated.unit_system, houses::UnitSystem::Metric);
+    assert_eq!(updated.currency, "EUR");
+    assert_eq!(updated.latitude, Some(52.52));
+}